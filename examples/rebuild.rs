@@ -0,0 +1,28 @@
+use dnfile::writer::metadata::MetaDataBuilder;
+
+pub fn main() -> dnfile::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let (input, output) = match (args.next(), args.next()) {
+        (Some(i), Some(o)) => (i, o),
+        _ => {
+            println!("usage: rebuild <input> <output>");
+            return Ok(());
+        }
+    };
+    let df = dnfile::DnPe::new(input.as_str())?;
+    let mut writer = df.writer();
+    writer.set_rebuild_metadata(true);
+    let rebuilt = writer.build_dnpe()?;
+
+    let before = MetaDataBuilder::new(&df.net()?.metadata).build()?;
+    let after = MetaDataBuilder::new(&rebuilt.net()?.metadata).build()?;
+    if before != after || df.net()?.functions() != rebuilt.net()?.functions() {
+        return Err(dnfile::error::Error::MetaDataEncodeError(format!(
+            "{}: rebuilt metadata does not round-trip",
+            input
+        )));
+    }
+    writer.save(output.as_str())?;
+    println!("{} -> {}: {} bytes of metadata", input, output, after.len());
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use dnfile::lang::cil::function::{reader::Reader, Function};
+
+pub fn main() -> dnfile::Result<()> {
+    let mut failed = 0;
+    for arg in std::env::args().skip(1) {
+        let df = dnfile::DnPe::new(arg.as_str())?;
+        let functions = df.net()?.functions();
+        for function in functions {
+            let encoded = function.encode()?;
+            let decoded = Function::new(&mut Reader::new(&encoded))?;
+            if decoded != *function {
+                println!("{}: mismatch in {}", arg, function.full_name());
+                failed += 1;
+            }
+            let mut short = function.method_body();
+            short.short_branches = true;
+            let decoded = Function::new(&mut Reader::new(&short.encode()?))?;
+            if decoded.instructions.len() != function.instructions.len() {
+                println!("{}: short branch mismatch in {}", arg, function.full_name());
+                failed += 1;
+            }
+        }
+        println!("{}: {} method bodies", arg, functions.len());
+    }
+    if failed > 0 {
+        return Err(dnfile::error::Error::MethodBodyEncodeError(format!(
+            "{} round-trip failures",
+            failed
+        )));
+    }
+    Ok(())
+}
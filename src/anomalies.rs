@@ -0,0 +1,380 @@
+use crate::{
+    stream::{
+        meta_data_tables::{
+            mdtables::{table_name_2_index, MethodDef},
+            MDTablesStruct,
+        },
+        Stream,
+    },
+    writer::metadata::Column,
+    DnPe, Result,
+};
+
+const METADATA_SIGNATURE: u32 = 0x424a_5342;
+const TABLE_COUNT: usize = 0x2d;
+const SORTED_TABLES: &[&str] = &[
+    "InterfaceImpl",
+    "Constant",
+    "CustomAttribute",
+    "FieldMarshal",
+    "DeclSecurity",
+    "ClassLayout",
+    "FieldLayout",
+    "MethodSemantics",
+    "MethodImpl",
+    "ImplMap",
+    "FieldRVA",
+    "NestedClass",
+    "GenericParam",
+    "GenericParamConstraint",
+];
+const STANDARD_STREAMS: &[&str] = &["#~", "#-", "#Strings", "#US", "#GUID", "#Blob"];
+const KNOWN_STREAMS: &[&str] = &["#Pdb", "#JTD", "#Schema"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum AnomalyKind {
+    InvalidSignature,
+    ReservedNotZero,
+    DuplicateStream,
+    NonStandardStream,
+    BothTableStreams,
+    InvalidValidMask,
+    InvalidSortedMask,
+    HeapSizeFlags,
+    ExtraData,
+    CodedIndexOutOfRange,
+    MethodRvaOutsideSections,
+    InvalidUtf8,
+    RangeOverflow,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Anomaly {
+    pub severity: Severity,
+    pub kind: AnomalyKind,
+    pub location: String,
+    pub rva: Option<u32>,
+    pub description: String,
+}
+
+impl Anomaly {
+    fn new(
+        severity: Severity,
+        kind: AnomalyKind,
+        location: impl Into<String>,
+        rva: Option<u32>,
+        description: String,
+    ) -> Self {
+        Self {
+            severity,
+            kind,
+            location: location.into(),
+            rva,
+            description,
+        }
+    }
+}
+
+impl DnPe {
+    pub fn anomalies(&self) -> Result<Vec<Anomaly>> {
+        let net = self.net()?;
+        let clr_struct = self.clr_struct()?;
+        let metadata = &net.metadata;
+        let metadata_rva = clr_struct.meta_data_rva;
+        let mut res = vec![];
+
+        if metadata.header.signature != METADATA_SIGNATURE {
+            res.push(Anomaly::new(
+                Severity::Error,
+                AnomalyKind::InvalidSignature,
+                "metadata root",
+                Some(metadata_rva),
+                format!("signature 0x{:08x}", metadata.header.signature),
+            ));
+        }
+        if metadata_rva
+            .checked_add(clr_struct.meta_data_size)
+            .is_none()
+        {
+            res.push(Anomaly::new(
+                Severity::Error,
+                AnomalyKind::RangeOverflow,
+                "metadata root",
+                Some(metadata_rva),
+                format!(
+                    "metadata size 0x{:08x} runs past the end of the address space",
+                    clr_struct.meta_data_size
+                ),
+            ));
+        }
+        if metadata.header.reserved != 0 {
+            res.push(Anomaly::new(
+                Severity::Warning,
+                AnomalyKind::ReservedNotZero,
+                "metadata root",
+                Some(metadata_rva + 8),
+                format!("reserved 0x{:08x}", metadata.header.reserved),
+            ));
+        }
+
+        let headers = metadata.stream_headers();
+        for (i, h) in headers.iter().enumerate() {
+            let rva = Some(metadata_rva + h.offset);
+            if headers[..i].iter().any(|p| p.name == h.name) {
+                res.push(Anomaly::new(
+                    Severity::Warning,
+                    AnomalyKind::DuplicateStream,
+                    format!("stream {}", h.name),
+                    rva,
+                    format!("stream {} appears more than once", h.name),
+                ));
+            }
+            if !STANDARD_STREAMS.contains(&h.name.as_str()) {
+                res.push(Anomaly::new(
+                    if KNOWN_STREAMS.contains(&h.name.as_str()) {
+                        Severity::Info
+                    } else {
+                        Severity::Warning
+                    },
+                    AnomalyKind::NonStandardStream,
+                    format!("stream {}", h.name),
+                    rva,
+                    format!("non-standard stream {:?}", h.name),
+                ));
+            }
+        }
+        if headers.iter().any(|h| h.name == "#~") && headers.iter().any(|h| h.name == "#-") {
+            res.push(Anomaly::new(
+                Severity::Warning,
+                AnomalyKind::BothTableStreams,
+                "streams",
+                None,
+                "both #~ and #- streams are present".to_string(),
+            ));
+        }
+
+        for s in metadata.streams.values() {
+            if s.rva.checked_add(s.size as u32).is_none() {
+                res.push(Anomaly::new(
+                    Severity::Error,
+                    AnomalyKind::RangeOverflow,
+                    format!("stream {}", s.name),
+                    Some(s.rva),
+                    format!(
+                        "stream size 0x{:08x} runs past the end of the address space",
+                        s.size
+                    ),
+                ));
+            }
+            match &s.stream {
+                Stream::MetaDataTables(tables) => {
+                    if let Some(header) = tables.header() {
+                        self.table_header_anomalies(&s.name, tables.rva(), header, &mut res);
+                    }
+                }
+                Stream::StringHeap(heap) => {
+                    let data = heap.data();
+                    let mut offset = 0;
+                    for entry in data.split(|b| *b == 0) {
+                        if std::str::from_utf8(entry).is_err() {
+                            res.push(Anomaly::new(
+                                Severity::Warning,
+                                AnomalyKind::InvalidUtf8,
+                                format!("{}+0x{:x}", s.name, offset),
+                                Some(s.rva + offset as u32),
+                                format!("invalid UTF-8 in {} bytes", entry.len()),
+                            ));
+                        }
+                        offset += entry.len() + 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        self.coded_index_anomalies(&mut res)?;
+
+        if let Ok(table) = net.md_table("MethodDef") {
+            for i in 0..table.row_count() {
+                let row = table.row::<MethodDef>(i)?;
+                if row.rva == 0 {
+                    continue;
+                }
+                let inside = self
+                    .offset(row.rva)
+                    .is_ok_and(|offset| offset < self.data.len());
+                if !inside {
+                    res.push(Anomaly::new(
+                        Severity::Error,
+                        AnomalyKind::MethodRvaOutsideSections,
+                        format!("MethodDef[{}]", i + 1),
+                        Some(row.rva),
+                        format!(
+                            "{} body RVA 0x{:08x} is not backed by file data",
+                            row.name, row.rva
+                        ),
+                    ));
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    fn table_header_anomalies(
+        &self,
+        stream: &str,
+        rva: u32,
+        header: &MDTablesStruct,
+        res: &mut Vec<Anomaly>,
+    ) {
+        let location = format!("{} header", stream);
+        if header.reserved_1 != 0 {
+            res.push(Anomaly::new(
+                Severity::Warning,
+                AnomalyKind::ReservedNotZero,
+                location.clone(),
+                Some(rva),
+                format!("reserved 0x{:08x}", header.reserved_1),
+            ));
+        }
+        if header.reserved_2 != 1 {
+            res.push(Anomaly::new(
+                Severity::Warning,
+                AnomalyKind::ReservedNotZero,
+                location.clone(),
+                Some(rva + 7),
+                format!("reserved byte 0x{:02x}, expected 1", header.reserved_2),
+            ));
+        }
+        let undefined = !((1u64 << TABLE_COUNT) - 1);
+        if header.mask_valid & undefined != 0 {
+            res.push(Anomaly::new(
+                Severity::Error,
+                AnomalyKind::InvalidValidMask,
+                location.clone(),
+                Some(rva + 8),
+                format!(
+                    "valid mask has undefined table bits 0x{:016x}",
+                    header.mask_valid & undefined
+                ),
+            ));
+        }
+        if header.mask_sorted & undefined != 0 {
+            res.push(Anomaly::new(
+                Severity::Warning,
+                AnomalyKind::InvalidSortedMask,
+                location.clone(),
+                Some(rva + 16),
+                format!(
+                    "sorted mask has undefined table bits 0x{:016x}",
+                    header.mask_sorted & undefined
+                ),
+            ));
+        }
+        for name in SORTED_TABLES {
+            let bit = match table_name_2_index(name) {
+                Ok(i) => 1u64 << i,
+                Err(_) => continue,
+            };
+            if header.mask_valid & bit != 0 && header.mask_sorted & bit == 0 {
+                res.push(Anomaly::new(
+                    Severity::Warning,
+                    AnomalyKind::InvalidSortedMask,
+                    location.clone(),
+                    Some(rva + 16),
+                    format!("{} must be sorted but its sorted bit is clear", name),
+                ));
+            }
+        }
+        if header.heap_offset_sizes & 0x40 != 0 {
+            res.push(Anomaly::new(
+                Severity::Warning,
+                AnomalyKind::ExtraData,
+                location.clone(),
+                Some(rva + 6),
+                "extra data flag set, 4 bytes follow the row counts".to_string(),
+            ));
+        }
+        if header.heap_offset_sizes & 0xb8 != 0 {
+            res.push(Anomaly::new(
+                Severity::Info,
+                AnomalyKind::HeapSizeFlags,
+                location,
+                Some(rva + 6),
+                format!("heap size flags 0x{:02x}", header.heap_offset_sizes),
+            ));
+        }
+    }
+
+    fn coded_index_anomalies(&self, res: &mut Vec<Anomaly>) -> Result<()> {
+        let net = self.net()?;
+        let row_count = |name: &'static str| {
+            net.md_table(name)
+                .map(|t| t.row_count())
+                .unwrap_or_default()
+        };
+        for i in 0..64 {
+            let table = match net.md_table_by_index(&i) {
+                Ok(t) => t,
+                Err(_) => continue,
+            };
+            let mut bad: std::collections::BTreeMap<usize, (usize, usize, String)> =
+                std::collections::BTreeMap::new();
+            for r in 0..table.row_count() {
+                let row = table.get_row(r)?;
+                let data = row.data();
+                let mut pos = 0;
+                for (c, column) in row.columns()?.iter().enumerate() {
+                    let size = column.size();
+                    let value = match data.get(pos..pos + size) {
+                        Some(bytes) => crate::utils::read_usize(bytes)?,
+                        None => break,
+                    };
+                    pos += size;
+                    let (tag_bits, tables, list) = match column {
+                        Column::Index {
+                            tag_bits,
+                            tables,
+                            list,
+                            ..
+                        } => (*tag_bits, tables, *list),
+                        _ => continue,
+                    };
+                    let rid = value >> tag_bits;
+                    let problem = match tables.get(value & ((1 << tag_bits) - 1)) {
+                        Some(&"") | None => Some(format!("invalid tag in 0x{:x}", value)),
+                        Some(target) => {
+                            let count = row_count(target);
+                            if rid > count + list as usize {
+                                Some(format!("{} row {} of {}", target, rid, count))
+                            } else {
+                                None
+                            }
+                        }
+                    };
+                    if let Some(problem) = problem {
+                        let entry = bad.entry(c).or_insert((r + 1, 0, problem));
+                        entry.1 += 1;
+                    }
+                }
+            }
+            for (column, (first_row, count, problem)) in bad {
+                res.push(Anomaly::new(
+                    Severity::Error,
+                    AnomalyKind::CodedIndexOutOfRange,
+                    format!("{}[{}] column {}", table.table.name(), first_row, column),
+                    Some(table.rva + ((first_row - 1) * table.row_size) as u32),
+                    format!("{} rows out of range, first: {}", count, problem),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,519 @@
+use crate::{
+    error::Error,
+    stream::meta_data_tables::mdtables::enums::AssemblyHashAlgorithm,
+    strong_name::{hasher, rsa_pkcs1_verify},
+    DnPe, Result,
+};
+use sha1::Digest;
+
+const WIN_CERT_TYPE_PKCS_SIGNED_DATA: u16 = 0x0002;
+const CERTIFICATE_TABLE_INDEX: usize = 4;
+const CHECKSUM_OFFSET: usize = 64;
+
+const TAG_INTEGER: u8 = 0x02;
+const TAG_BIT_STRING: u8 = 0x03;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_OID: u8 = 0x06;
+const TAG_UTC_TIME: u8 = 0x17;
+const TAG_GENERALIZED_TIME: u8 = 0x18;
+const TAG_BMP_STRING: u8 = 0x1e;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_SET: u8 = 0x31;
+const TAG_CONTEXT_0: u8 = 0xa0;
+const TAG_CONTEXT_0_PRIMITIVE: u8 = 0x80;
+const TAG_CONTEXT_1: u8 = 0xa1;
+
+const OID_SIGNED_DATA: &str = "1.2.840.113549.1.7.2";
+const OID_SPC_INDIRECT_DATA: &str = "1.3.6.1.4.1.311.2.1.4";
+const OID_SPC_SP_OPUS_INFO: &str = "1.3.6.1.4.1.311.2.1.12";
+const OID_MESSAGE_DIGEST: &str = "1.2.840.113549.1.9.4";
+const OID_RSA_ENCRYPTION: &str = "1.2.840.113549.1.1.1";
+const OID_RSA_SIGNATURES: &str = "1.2.840.113549.1.1.";
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Certificate {
+    pub subject: String,
+    pub issuer: String,
+    pub serial_number: String,
+    pub not_before: String,
+    pub not_after: String,
+    pub thumbprint: String,
+    #[serde(skip_serializing)]
+    public_key: Option<(Vec<u8>, Vec<u8>)>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SignerInfo {
+    pub issuer: String,
+    pub serial_number: String,
+    pub subject: Option<String>,
+    pub digest_algorithm: AssemblyHashAlgorithm,
+    pub program_name: Option<String>,
+    pub signature_valid: Option<bool>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AuthenticodeSignature {
+    pub offset: usize,
+    pub revision: u16,
+    pub certificate_type: u16,
+    pub digest_algorithm: AssemblyHashAlgorithm,
+    pub signed_digest: Vec<u8>,
+    pub image_digest: Vec<u8>,
+    pub digest_matches: bool,
+    pub certificates: Vec<Certificate>,
+    pub signers: Vec<SignerInfo>,
+}
+
+#[derive(Clone, Copy)]
+struct Der<'a> {
+    tag: u8,
+    content: &'a [u8],
+    raw: &'a [u8],
+}
+
+fn der_error(message: &str) -> Error {
+    Error::FormatError(format!("authenticode: {}", message))
+}
+
+fn read_der(data: &[u8]) -> Result<(Der<'_>, &[u8])> {
+    let tag = *data
+        .first()
+        .ok_or_else(|| der_error("unexpected end of data"))?;
+    let first = *data.get(1).ok_or_else(|| der_error("missing length"))?;
+    let (len, header) = match first {
+        b if b & 0x80 == 0 => (b as usize, 2),
+        0x81..=0x84 => {
+            let count = (first & 0x7f) as usize;
+            let bytes = data
+                .get(2..2 + count)
+                .ok_or_else(|| der_error("truncated length"))?;
+            (
+                bytes.iter().fold(0usize, |a, b| (a << 8) | *b as usize),
+                2 + count,
+            )
+        }
+        _ => return Err(der_error("unsupported length encoding")),
+    };
+    let end = header
+        .checked_add(len)
+        .filter(|end| *end <= data.len())
+        .ok_or_else(|| der_error("element exceeds its container"))?;
+    Ok((
+        Der {
+            tag,
+            content: &data[header..end],
+            raw: &data[..end],
+        },
+        &data[end..],
+    ))
+}
+
+impl<'a> Der<'a> {
+    fn children(&self) -> Result<Vec<Der<'a>>> {
+        let mut res = vec![];
+        let mut rest = self.content;
+        while !rest.is_empty() {
+            let (child, next) = read_der(rest)?;
+            res.push(child);
+            rest = next;
+        }
+        Ok(res)
+    }
+
+    fn expect(self, tag: u8) -> Result<Self> {
+        if self.tag == tag {
+            Ok(self)
+        } else {
+            Err(der_error(&format!(
+                "expected tag 0x{:02x}, found 0x{:02x}",
+                tag, self.tag
+            )))
+        }
+    }
+
+    fn oid(&self) -> String {
+        let mut parts = vec![];
+        let mut value = 0u64;
+        for b in self.content {
+            value = (value << 7) | (b & 0x7f) as u64;
+            if b & 0x80 != 0 {
+                continue;
+            }
+            if parts.is_empty() {
+                let first = (value / 40).min(2);
+                parts.push(first.to_string());
+                parts.push((value - first * 40).to_string());
+            } else {
+                parts.push(value.to_string());
+            }
+            value = 0;
+        }
+        parts.join(".")
+    }
+
+    fn text(&self) -> String {
+        match self.tag {
+            TAG_BMP_STRING => {
+                let units: Vec<u16> = self
+                    .content
+                    .chunks_exact(2)
+                    .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                    .collect();
+                String::from_utf16_lossy(&units)
+            }
+            TAG_UTC_TIME | TAG_GENERALIZED_TIME => {
+                let s = String::from_utf8_lossy(self.content);
+                let s = s.trim_end_matches('Z');
+                let s = match self.tag {
+                    TAG_UTC_TIME if s.get(..2).is_some_and(|y| y < "50") => format!("20{}", s),
+                    TAG_UTC_TIME => format!("19{}", s),
+                    _ => s.to_string(),
+                };
+                match (
+                    s.get(..4),
+                    s.get(4..6),
+                    s.get(6..8),
+                    s.get(8..10),
+                    s.get(10..12),
+                    s.get(12..14),
+                ) {
+                    (Some(y), Some(mo), Some(d), Some(h), Some(mi), Some(sec)) => {
+                        format!("{}-{}-{} {}:{}:{}", y, mo, d, h, mi, sec)
+                    }
+                    _ => s,
+                }
+            }
+            _ => String::from_utf8_lossy(self.content).to_string(),
+        }
+    }
+}
+
+fn hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn digest_algorithm(algorithm: &Der) -> Result<AssemblyHashAlgorithm> {
+    let children = algorithm.children()?;
+    let oid = children
+        .first()
+        .ok_or_else(|| der_error("empty algorithm identifier"))?
+        .expect(TAG_OID)?
+        .oid();
+    Ok(match oid.as_str() {
+        "1.2.840.113549.2.5" => AssemblyHashAlgorithm::Md5,
+        "1.3.14.3.2.26" => AssemblyHashAlgorithm::Sha1,
+        "2.16.840.1.101.3.4.2.1" => AssemblyHashAlgorithm::Sha256,
+        "2.16.840.1.101.3.4.2.2" => AssemblyHashAlgorithm::Sha384,
+        "2.16.840.1.101.3.4.2.3" => AssemblyHashAlgorithm::Sha512,
+        _ => AssemblyHashAlgorithm::None,
+    })
+}
+
+fn name(name: &Der) -> Result<String> {
+    let mut res = vec![];
+    for rdn in name.children()? {
+        for attribute in rdn.children()? {
+            let parts = attribute.children()?;
+            if parts.len() < 2 {
+                continue;
+            }
+            let oid = parts[0].oid();
+            let key = match oid.as_str() {
+                "2.5.4.3" => "CN",
+                "2.5.4.6" => "C",
+                "2.5.4.7" => "L",
+                "2.5.4.8" => "ST",
+                "2.5.4.10" => "O",
+                "2.5.4.11" => "OU",
+                "1.2.840.113549.1.9.1" => "E",
+                _ => oid.as_str(),
+            };
+            res.push(format!("{}={}", key, parts[1].text()));
+        }
+    }
+    Ok(res.join(", "))
+}
+
+fn rsa_public_key(spki: &Der) -> Option<(Vec<u8>, Vec<u8>)> {
+    let parts = spki.children().ok()?;
+    let algorithm = parts.first()?.children().ok()?;
+    if algorithm.first()?.oid() != OID_RSA_ENCRYPTION {
+        return None;
+    }
+    let bits = parts.get(1)?.expect(TAG_BIT_STRING).ok()?;
+    let (key, _) = read_der(bits.content.get(1..)?).ok()?;
+    let numbers = key.children().ok()?;
+    Some((
+        numbers.first()?.content.to_vec(),
+        numbers.get(1)?.content.to_vec(),
+    ))
+}
+
+fn parse_certificate(certificate: &Der) -> Result<Certificate> {
+    let tbs = certificate
+        .children()?
+        .first()
+        .ok_or_else(|| der_error("empty certificate"))?
+        .expect(TAG_SEQUENCE)?
+        .children()?;
+    let offset = match tbs.first() {
+        Some(d) if d.tag == TAG_CONTEXT_0 => 1,
+        _ => 0,
+    };
+    let field = |i: usize| {
+        tbs.get(offset + i)
+            .copied()
+            .ok_or_else(|| der_error("truncated certificate"))
+    };
+    let validity = field(3)?.children()?;
+    Ok(Certificate {
+        serial_number: hex(field(0)?.expect(TAG_INTEGER)?.content),
+        issuer: name(&field(2)?)?,
+        not_before: validity.first().map(|t| t.text()).unwrap_or_default(),
+        not_after: validity.get(1).map(|t| t.text()).unwrap_or_default(),
+        subject: name(&field(4)?)?,
+        thumbprint: hex(&sha1::Sha1::digest(certificate.raw)),
+        public_key: rsa_public_key(&field(5)?),
+    })
+}
+
+fn program_name(opus_info: &Der) -> Option<String> {
+    let info = opus_info.children().ok()?;
+    let program = info.first()?.expect(TAG_CONTEXT_0).ok()?;
+    let (value, _) = read_der(program.content).ok()?;
+    match value.tag {
+        TAG_CONTEXT_0_PRIMITIVE => Some(
+            Der {
+                tag: TAG_BMP_STRING,
+                ..value
+            }
+            .text(),
+        ),
+        _ => Some(value.text()),
+    }
+}
+
+fn parse_signer(
+    signer: &Der,
+    certificates: &[Certificate],
+    content_digest: &dyn Fn(AssemblyHashAlgorithm) -> Option<Vec<u8>>,
+) -> Result<SignerInfo> {
+    let parts = signer.children()?;
+    let part = |i: usize| {
+        parts
+            .get(i)
+            .copied()
+            .ok_or_else(|| der_error("truncated signer info"))
+    };
+    let sid = part(1)?.expect(TAG_SEQUENCE)?.children()?;
+    let issuer = name(sid.first().ok_or_else(|| der_error("empty signer id"))?)?;
+    let serial_number = hex(sid
+        .get(1)
+        .ok_or_else(|| der_error("missing signer serial number"))?
+        .content);
+    let algorithm = digest_algorithm(&part(2)?)?;
+    let (signed_attributes, next) = match part(3)? {
+        d if d.tag == TAG_CONTEXT_0 => (Some(d), 4),
+        _ => (None, 3),
+    };
+    let signature_algorithm = part(next)?.children()?;
+    let signature = part(next + 1)?.expect(TAG_OCTET_STRING)?.content;
+
+    let mut message_digest = None;
+    let mut program = None;
+    for attribute in signed_attributes
+        .map(|a| a.children())
+        .transpose()?
+        .unwrap_or_default()
+    {
+        let fields = attribute.children()?;
+        let value = fields
+            .get(1)
+            .and_then(|s| s.children().ok()?.first().copied());
+        match (fields.first().map(|o| o.oid()).as_deref(), value) {
+            (Some(OID_MESSAGE_DIGEST), Some(v)) => message_digest = Some(v.content),
+            (Some(OID_SPC_SP_OPUS_INFO), Some(v)) => program = program_name(&v),
+            _ => {}
+        }
+    }
+
+    let certificate = certificates
+        .iter()
+        .find(|c| c.serial_number == serial_number && c.issuer == issuer);
+    let rsa = signature_algorithm
+        .first()
+        .is_some_and(|o| o.oid().starts_with(OID_RSA_SIGNATURES));
+    let signature_valid = match (certificate.and_then(|c| c.public_key.as_ref()), rsa) {
+        (Some((modulus, exponent)), true) => {
+            let (data, digest_ok) = match signed_attributes {
+                Some(attributes) => {
+                    let mut data = attributes.raw.to_vec();
+                    data[0] = TAG_SET;
+                    (
+                        data,
+                        message_digest
+                            .is_some_and(|d| content_digest(algorithm).as_deref() == Some(d)),
+                    )
+                }
+                None => (vec![], false),
+            };
+            hasher(algorithm).ok().map(|mut h| {
+                h.update(&data);
+                digest_ok
+                    && rsa_pkcs1_verify(modulus, exponent, signature, algorithm, &h.finalize())
+            })
+        }
+        _ => None,
+    };
+    Ok(SignerInfo {
+        issuer,
+        serial_number,
+        subject: certificate.map(|c| c.subject.clone()),
+        digest_algorithm: algorithm,
+        program_name: program,
+        signature_valid,
+    })
+}
+
+impl DnPe {
+    pub fn authenticode(&self) -> Result<Vec<AuthenticodeSignature>> {
+        let mut res = vec![];
+        let pe = self.pe()?;
+        let optional_header = match pe.header.optional_header {
+            Some(oh) => oh,
+            None => return Ok(res),
+        };
+        let table = match optional_header.data_directories.get_certificate_table() {
+            Some(dd) if dd.size > 0 => *dd,
+            _ => return Ok(res),
+        };
+        let start = table.virtual_address as usize;
+        let end = start + table.size as usize;
+        let certificates = self
+            .data
+            .get(start..end)
+            .ok_or(Error::NotEnoughData(self.data.len(), end))?;
+        let nt_optional_header = pe.header.dos_header.pe_pointer as usize + 24;
+        let checksum = nt_optional_header + CHECKSUM_OFFSET;
+        let entry_offset =
+            nt_optional_header + if pe.is_64 { 112 } else { 96 } + CERTIFICATE_TABLE_INDEX * 8;
+        let mut offset = 0;
+        while offset + 8 <= certificates.len() {
+            let length = u32::from_le_bytes(certificates[offset..offset + 4].try_into()?) as usize;
+            let revision = u16::from_le_bytes(certificates[offset + 4..offset + 6].try_into()?);
+            let certificate_type =
+                u16::from_le_bytes(certificates[offset + 6..offset + 8].try_into()?);
+            if length < 8 || offset + length > certificates.len() {
+                break;
+            }
+            if certificate_type == WIN_CERT_TYPE_PKCS_SIGNED_DATA {
+                let mut signature =
+                    self.parse_signed_data(&certificates[offset + 8..offset + length])?;
+                signature.offset = start + offset;
+                signature.revision = revision;
+                signature.certificate_type = certificate_type;
+                if let Ok(mut h) = hasher(signature.digest_algorithm) {
+                    for range in [
+                        0..checksum,
+                        checksum + 4..entry_offset,
+                        entry_offset + 8..start,
+                        end..self.data.len(),
+                    ] {
+                        h.update(self.data.get(range).unwrap_or_default());
+                    }
+                    signature.image_digest = h.finalize().to_vec();
+                    signature.digest_matches = signature.image_digest == signature.signed_digest;
+                }
+                res.push(signature);
+            }
+            offset += (length + 7) & !7;
+        }
+        Ok(res)
+    }
+
+    fn parse_signed_data(&self, data: &[u8]) -> Result<AuthenticodeSignature> {
+        let (content_info, _) = read_der(data)?;
+        let content_info = content_info.expect(TAG_SEQUENCE)?.children()?;
+        if content_info.first().map(|o| o.oid()).as_deref() != Some(OID_SIGNED_DATA) {
+            return Err(der_error("not a PKCS#7 SignedData"));
+        }
+        let (signed_data, _) = read_der(
+            content_info
+                .get(1)
+                .ok_or_else(|| der_error("missing SignedData"))?
+                .expect(TAG_CONTEXT_0)?
+                .content,
+        )?;
+        let parts = signed_data.expect(TAG_SEQUENCE)?.children()?;
+        let encap = parts
+            .get(2)
+            .ok_or_else(|| der_error("missing content info"))?
+            .children()?;
+        if encap.first().map(|o| o.oid()).as_deref() != Some(OID_SPC_INDIRECT_DATA) {
+            return Err(der_error("content is not SpcIndirectDataContent"));
+        }
+        let (indirect, _) = read_der(
+            encap
+                .get(1)
+                .ok_or_else(|| der_error("missing SpcIndirectDataContent"))?
+                .expect(TAG_CONTEXT_0)?
+                .content,
+        )?;
+        let digest_info = indirect
+            .expect(TAG_SEQUENCE)?
+            .children()?
+            .get(1)
+            .ok_or_else(|| der_error("missing DigestInfo"))?
+            .children()?;
+        let algorithm = digest_algorithm(
+            digest_info
+                .first()
+                .ok_or_else(|| der_error("missing digest algorithm"))?,
+        )?;
+        let signed_digest = digest_info
+            .get(1)
+            .ok_or_else(|| der_error("missing digest"))?
+            .expect(TAG_OCTET_STRING)?
+            .content
+            .to_vec();
+
+        let mut certificates = vec![];
+        let mut signers = vec![];
+        for part in &parts[3..] {
+            match part.tag {
+                TAG_CONTEXT_0 => {
+                    for certificate in part.children()? {
+                        if certificate.tag == TAG_SEQUENCE {
+                            certificates.push(parse_certificate(&certificate)?);
+                        }
+                    }
+                }
+                TAG_CONTEXT_1 => {}
+                TAG_SET => {
+                    let content_digest = |a| {
+                        hasher(a).ok().map(|mut h| {
+                            h.update(indirect.content);
+                            h.finalize().to_vec()
+                        })
+                    };
+                    for signer in part.children()? {
+                        signers.push(parse_signer(&signer, &certificates, &content_digest)?);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(AuthenticodeSignature {
+            offset: 0,
+            revision: 0,
+            certificate_type: 0,
+            digest_algorithm: algorithm,
+            signed_digest,
+            image_digest: vec![],
+            digest_matches: false,
+            certificates,
+            signers,
+        })
+    }
+}
@@ -0,0 +1,323 @@
+use crate::{
+    error::Error,
+    stream::meta_data_tables::mdtables::{Assembly, AssemblyRef, Module},
+    DnPe, Result,
+};
+
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    pub threads: usize,
+    pub follow_links: bool,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            include: vec![],
+            exclude: vec![],
+            min_size: None,
+            max_size: None,
+            threads: std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+            follow_links: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FileSummary {
+    pub path: String,
+    pub size: u64,
+    pub metadata_version: String,
+    pub module: Option<String>,
+    pub mvid: Option<String>,
+    pub assembly: Option<String>,
+    pub version: Option<String>,
+    pub assembly_refs: Vec<String>,
+    pub types: usize,
+    pub methods: usize,
+    pub functions: usize,
+    pub resources: usize,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ScanError {
+    pub path: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ScanRecord {
+    File(FileSummary),
+    Error(ScanError),
+}
+
+/// A file name pattern for [`ScanOptions::include`] and
+/// [`ScanOptions::exclude`].
+///
+/// - `*` matches any run of characters except `/`
+/// - `**` matches across directories, and `**/` also matches no directory
+/// - `?` matches one character except `/`
+/// - `[abc]`, `[a-z]` match one listed character, `[!a-z]` or `[^a-z]` one
+///   that is not listed; a `]` right after the opening bracket is literal
+/// - `\` makes the next character literal, e.g. `\*` or `\[`
+///
+/// A pattern containing `/` is matched against the path relative to the scan
+/// root, any other pattern against the file name alone. Matching ignores case
+/// on Windows.
+#[derive(Debug, Clone)]
+pub struct Glob {
+    regex: regex::Regex,
+    path: bool,
+}
+
+impl Glob {
+    pub fn new(pattern: &str) -> Result<Self> {
+        let mut re = String::from("^");
+        let mut chars = pattern.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '*' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    if chars.peek() == Some(&'/') {
+                        chars.next();
+                        re.push_str("(?:.*/)?");
+                    } else {
+                        re.push_str(".*");
+                    }
+                }
+                '*' => re.push_str("[^/]*"),
+                '?' => re.push_str("[^/]"),
+                '[' => {
+                    let negated = matches!(chars.peek(), Some('!') | Some('^'));
+                    if negated {
+                        chars.next();
+                    }
+                    let mut class = String::new();
+                    let mut closed = false;
+                    while let Some(c) = chars.next() {
+                        match c {
+                            ']' if !class.is_empty() => {
+                                closed = true;
+                                break;
+                            }
+                            '-' if !class.is_empty() && chars.peek() != Some(&']') => {
+                                class.push('-')
+                            }
+                            '\\' => {
+                                let c = chars.next().unwrap_or('\\');
+                                class.push_str(&regex::escape(&c.to_string()));
+                            }
+                            c => class.push_str(&regex::escape(&c.to_string())),
+                        }
+                    }
+                    if !closed {
+                        return Err(Error::FormatError(format!(
+                            "unterminated [ in glob {:?}",
+                            pattern
+                        )));
+                    }
+                    if negated {
+                        re.push_str(&format!("[^/{}]", class));
+                    } else {
+                        re.push_str(&format!("[{}]", class));
+                    }
+                }
+                '\\' => {
+                    let c = chars.next().unwrap_or('\\');
+                    re.push_str(&regex::escape(&c.to_string()));
+                }
+                c => re.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        re.push('$');
+        Ok(Self {
+            regex: regex::RegexBuilder::new(&re)
+                .case_insensitive(cfg!(windows))
+                .build()?,
+            path: pattern.contains('/'),
+        })
+    }
+
+    /// `relative` is the path below the scan root with `/` separators.
+    pub fn matches(&self, relative: &str, file_name: &str) -> bool {
+        if self.path {
+            self.regex.is_match(relative)
+        } else {
+            self.regex.is_match(file_name)
+        }
+    }
+}
+
+fn has_clr_header(pe: &goblin::pe::PE) -> bool {
+    pe.header
+        .optional_header
+        .and_then(|oh| oh.data_directories.get_clr_runtime_header().copied())
+        .is_some_and(|dd| dd.virtual_address != 0 && dd.size != 0)
+}
+
+pub fn is_dotnet(data: &[u8]) -> bool {
+    goblin::pe::PE::parse(data).is_ok_and(|pe| has_clr_header(&pe))
+}
+
+pub fn summarize(path: &str, data: Vec<u8>) -> Result<FileSummary> {
+    let size = data.len() as u64;
+    let dn = DnPe::from_bytes(path, data)?;
+    let net = dn.net()?;
+    let module = net.md_table("Module").and_then(|t| t.row::<Module>(0)).ok();
+    let assembly = net
+        .md_table("Assembly")
+        .and_then(|t| t.row::<Assembly>(0))
+        .ok();
+    let mut assembly_refs = vec![];
+    if let Ok(table) = net.md_table("AssemblyRef") {
+        for i in 0..table.row_count() {
+            assembly_refs.push(table.row::<AssemblyRef>(i)?.name.clone());
+        }
+    }
+    let row_count = |name| net.md_table(name).map(|t| t.row_count()).unwrap_or(0);
+    Ok(FileSummary {
+        path: path.to_string(),
+        size,
+        metadata_version: net.metadata.version().to_string(),
+        module: module.map(|m| m.name.clone()),
+        mvid: module.map(|m| m.mvid.to_string()),
+        assembly: assembly.map(|a| a.name.clone()),
+        version: assembly.map(|a| {
+            format!(
+                "{}.{}.{}.{}",
+                a.major_version, a.minor_version, a.build_number, a.revision_number
+            )
+        }),
+        assembly_refs,
+        types: row_count("TypeDef"),
+        methods: row_count("MethodDef"),
+        functions: net.functions().len(),
+        resources: row_count("ManifestResource"),
+    })
+}
+
+fn scan_file(path: &std::path::Path) -> Option<ScanRecord> {
+    let name = path.display().to_string();
+    let error = |e: String| {
+        Some(ScanRecord::Error(ScanError {
+            path: name.clone(),
+            error: e,
+        }))
+    };
+    let data = match std::fs::read(path) {
+        Ok(d) => d,
+        Err(e) => return error(e.to_string()),
+    };
+    match goblin::pe::PE::parse(&data) {
+        Ok(pe) if has_clr_header(&pe) => {}
+        Err(e) if data.starts_with(b"MZ") => return error(e.to_string()),
+        _ => return None,
+    }
+    match summarize(&name, data) {
+        Ok(summary) => Some(ScanRecord::File(summary)),
+        Err(e) => error(e.to_string()),
+    }
+}
+
+pub fn scan<F>(root: &str, options: &ScanOptions, mut callback: F) -> Result<()>
+where
+    F: FnMut(ScanRecord),
+{
+    let include = options
+        .include
+        .iter()
+        .map(|p| Glob::new(p))
+        .collect::<Result<Vec<_>>>()?;
+    let exclude = options
+        .exclude
+        .iter()
+        .map(|p| Glob::new(p))
+        .collect::<Result<Vec<_>>>()?;
+    let root_path = std::path::Path::new(root);
+    if !root_path.exists() {
+        return Err(Error::IoError(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            root.to_string(),
+        )));
+    }
+
+    let mut paths = vec![];
+    for entry in walkdir::WalkDir::new(root_path).follow_links(options.follow_links) {
+        let entry = match entry {
+            Ok(e) => e,
+            Err(e) => {
+                callback(ScanRecord::Error(ScanError {
+                    path: e
+                        .path()
+                        .map(|p| p.display().to_string())
+                        .unwrap_or_else(|| root.to_string()),
+                    error: e.to_string(),
+                }));
+                continue;
+            }
+        };
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let relative = entry
+            .path()
+            .strip_prefix(root_path)
+            .unwrap_or(entry.path())
+            .to_string_lossy()
+            .replace('\\', "/");
+        let file_name = entry.file_name().to_string_lossy();
+        if (!include.is_empty() && !include.iter().any(|g| g.matches(&relative, &file_name)))
+            || exclude.iter().any(|g| g.matches(&relative, &file_name))
+        {
+            continue;
+        }
+        let size = match entry.metadata() {
+            Ok(m) => m.len(),
+            Err(e) => {
+                callback(ScanRecord::Error(ScanError {
+                    path: entry.path().display().to_string(),
+                    error: e.to_string(),
+                }));
+                continue;
+            }
+        };
+        if options.min_size.is_some_and(|min| size < min)
+            || options.max_size.is_some_and(|max| size > max)
+        {
+            continue;
+        }
+        paths.push(entry.into_path());
+    }
+
+    let next = std::sync::atomic::AtomicUsize::new(0);
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::scope(|s| {
+        for _ in 0..options.threads.max(1) {
+            let tx = tx.clone();
+            let (next, paths) = (&next, &paths);
+            s.spawn(move || loop {
+                let i = next.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                let Some(path) = paths.get(i) else {
+                    break;
+                };
+                if let Some(record) = scan_file(path) {
+                    if tx.send(record).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(tx);
+        for record in rx {
+            callback(record);
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,803 @@
+use clap::{Parser, Subcommand, ValueEnum};
+use dnfile::{anomalies::Severity, lang::clr::token::Token, DnPe};
+use std::io::Write;
+
+mod report;
+
+#[derive(Parser)]
+#[command(name = "dnfile", version, about = ".NET binary extractor")]
+struct Cli {
+    /// Print JSON instead of human-readable text
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// CLR header, metadata root, streams and assembly identity
+    Info { file: String },
+    /// Raw COR20 header, metadata root, stream headers and #~ header
+    Headers { file: String },
+    /// Metadata tables with row counts
+    Tables {
+        file: String,
+        /// Only tables whose name contains this text
+        #[arg(long)]
+        name: Option<String>,
+        /// Skip tables without rows
+        #[arg(long)]
+        non_empty: bool,
+    },
+    /// Entries of the #Strings heap
+    Strings {
+        file: String,
+        #[arg(long, default_value_t = 1)]
+        min_len: usize,
+        #[arg(long)]
+        contains: Option<String>,
+    },
+    /// Entries of the #US heap
+    Us {
+        file: String,
+        #[arg(long, default_value_t = 1)]
+        min_len: usize,
+        #[arg(long)]
+        contains: Option<String>,
+    },
+    /// MethodDef rows and their bodies
+    Methods {
+        file: String,
+        /// Only methods whose name contains this text
+        #[arg(long)]
+        name: Option<String>,
+        /// Only methods whose declaring type contains this text
+        #[arg(long = "type")]
+        type_name: Option<String>,
+        /// Only methods with an IL body
+        #[arg(long)]
+        body: bool,
+    },
+    /// Disassemble method bodies
+    Dis {
+        file: String,
+        /// MethodDef token, e.g. 0x06000001
+        #[arg(long, value_parser = parse_token)]
+        token: Option<usize>,
+        /// Only methods whose full name contains this text
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Manifest resources
+    Resources {
+        file: String,
+        #[arg(long)]
+        name: Option<String>,
+        /// Write embedded resources into this directory
+        #[arg(long)]
+        dump: Option<std::path::PathBuf>,
+    },
+    /// P/Invoke and native imports
+    Imports {
+        file: String,
+        /// Only imports from modules whose name contains this text
+        #[arg(long)]
+        module: Option<String>,
+        #[arg(long, value_enum)]
+        kind: Option<ImportKind>,
+    },
+    /// Assembly, type and member references
+    Refs {
+        file: String,
+        #[arg(long, value_enum)]
+        kind: Option<RefKind>,
+        #[arg(long)]
+        contains: Option<String>,
+    },
+    /// Everything above as one JSON document
+    Json { file: String },
+    /// Regex search over strings, user strings, attribute values and resources
+    Search {
+        file: String,
+        #[arg(required_unless_present = "ioc")]
+        patterns: Vec<String>,
+        /// Add the built-in URL, IPv4 and registry path patterns
+        #[arg(long)]
+        ioc: bool,
+        #[arg(short = 'i', long)]
+        ignore_case: bool,
+        /// Only search these sources
+        #[arg(long, value_enum)]
+        source: Vec<Source>,
+    },
+    /// Unreferenced heap entries, slack and gaps between metadata streams
+    Hidden {
+        file: String,
+        /// Write each range into this directory
+        #[arg(long)]
+        dump: Option<std::path::PathBuf>,
+    },
+    /// Invalid or suspicious metadata and CLR header values
+    Anomalies {
+        file: String,
+        /// Only show anomalies of at least this severity
+        #[arg(long, value_enum, default_value = "info")]
+        severity: SeverityArg,
+    },
+    /// Walk a directory and print one JSON record per .NET file
+    Scan {
+        dir: String,
+        /// Only files matching this glob, e.g. "*.dll" or "**/bin/*.exe"
+        #[arg(long)]
+        include: Vec<String>,
+        /// Skip files matching this glob
+        #[arg(long)]
+        exclude: Vec<String>,
+        #[arg(long)]
+        min_size: Option<u64>,
+        #[arg(long)]
+        max_size: Option<u64>,
+        /// Worker threads, defaults to the number of CPUs
+        #[arg(long)]
+        threads: Option<usize>,
+        #[arg(long)]
+        follow_links: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ImportKind {
+    Pinvoke,
+    Native,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum Source {
+    Us,
+    Strings,
+    Attributes,
+    Resources,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SeverityArg {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum RefKind {
+    Assembly,
+    Type,
+    Member,
+}
+
+fn parse_token(s: &str) -> Result<usize, std::num::ParseIntError> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => usize::from_str_radix(hex, 16),
+        None => s.parse(),
+    }
+}
+
+fn matches(value: &str, filter: &Option<String>) -> bool {
+    match filter {
+        Some(f) => value.to_lowercase().contains(&f.to_lowercase()),
+        None => true,
+    }
+}
+
+fn location_text(location: &dnfile::search::Location) -> String {
+    use dnfile::search::Location;
+    match location {
+        Location::UserString { offset } => format!("us 0x{:08x}", offset),
+        Location::Ldstr {
+            token,
+            method,
+            il_offset,
+            ..
+        } => format!("ldstr 0x{:08x} IL_{:04x} {}", token, il_offset, method),
+        Location::String { offset } => format!("strings 0x{:08x}", offset),
+        Location::CustomAttribute { row, parent } => format!("attribute {} on {}", row, parent),
+        Location::Resource { name, offset } => format!("resource {}+0x{:x}", name, offset),
+        Location::ResourceEntry {
+            resource,
+            entry,
+            offset,
+        } => format!("resource {} entry {:?}+0x{:x}", resource, entry, offset),
+    }
+}
+
+/// Resource names come from the file, so keep them from escaping `dir` or
+/// naming devices and streams when used as a path.
+fn dump_file_name(name: &str) -> String {
+    let res: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let res = res.replace("..", "_");
+    if res.is_empty() || res == "." {
+        "_".to_string()
+    } else {
+        res
+    }
+}
+
+fn print_json<T: serde::Serialize>(out: &mut dyn Write, value: &T) -> dnfile::Result<()> {
+    writeln!(out, "{}", serde_json::to_string_pretty(value)?)?;
+    Ok(())
+}
+
+#[derive(serde::Serialize)]
+struct Everything {
+    info: report::Info,
+    tables: Vec<report::TableInfo>,
+    methods: Vec<report::MethodInfo>,
+    resources: Vec<dnfile::resources::Resource>,
+    imports: Vec<report::ImportInfo>,
+    refs: Vec<report::RefInfo>,
+}
+
+fn run(cli: Cli, out: &mut dyn Write) -> dnfile::Result<()> {
+    match cli.command {
+        Command::Info { file } => {
+            let info = report::info(&DnPe::new(&file)?)?;
+            if cli.json {
+                return print_json(out, &info);
+            }
+            writeln!(out, "file:             {}", info.file)?;
+            writeln!(out, "size:             {}", info.size)?;
+            writeln!(out, "dll:              {}", info.is_dll)?;
+            writeln!(out, "clr flags:        {}", info.clr_flags.join(" "))?;
+            writeln!(out, "metadata version: {}", info.metadata_version)?;
+            writeln!(out, "metadata flags:   0x{:x}", info.metadata_flags)?;
+            if let (Some(module), Some(mvid)) = (&info.module, &info.mvid) {
+                writeln!(out, "module:           {} {{{}}}", module, mvid)?;
+            }
+            if let Some(a) = &info.assembly {
+                writeln!(
+                    out,
+                    "assembly:         {} {} {}",
+                    a.name, a.version, a.culture
+                )?;
+                if !a.public_key_token.is_empty() {
+                    writeln!(out, "public key token: {}", a.public_key_token)?;
+                }
+                writeln!(out, "strong name:      {}", a.strong_name)?;
+            }
+            for s in &info.signatures {
+                writeln!(
+                    out,
+                    "signed by:        {} (issuer {}, {} digest {}, signature {})",
+                    s.signer,
+                    s.issuer,
+                    s.digest_algorithm,
+                    if s.digest_matches { "ok" } else { "mismatch" },
+                    match s.signature_valid {
+                        Some(true) => "ok",
+                        Some(false) => "invalid",
+                        None => "unchecked",
+                    }
+                )?;
+            }
+            for p in &info.pdb {
+                writeln!(
+                    out,
+                    "pdb:              {} {{{}}} age {}",
+                    p.path, p.guid, p.age
+                )?;
+            }
+            if let Some(r) = &info.ready_to_run {
+                writeln!(
+                    out,
+                    "readytorun:       {} {} ({} precompiled methods, {} types) {}",
+                    r.version,
+                    r.compiler.as_deref().unwrap_or("unknown compiler"),
+                    r.methods,
+                    r.available_types,
+                    r.flags.join(" ")
+                )?;
+            }
+            if let Some(e) = &info.entry_point {
+                writeln!(out, "entry point:      {}", e)?;
+            }
+            if info.vtable_fixups > 0 {
+                writeln!(out, "vtable fixups:    {}", info.vtable_fixups)?;
+            }
+            for e in &info.exports {
+                writeln!(
+                    out,
+                    "export:           #{} {} rva 0x{:08x} -> {}",
+                    e.ordinal,
+                    e.name.as_deref().unwrap_or("<unnamed>"),
+                    e.rva,
+                    e.method.as_deref().unwrap_or("<native>")
+                )?;
+            }
+            writeln!(out, "tables:           {}", info.tables)?;
+            writeln!(
+                out,
+                "methods:          {} ({} with body)",
+                info.methods, info.functions
+            )?;
+            writeln!(out, "streams:")?;
+            for s in &info.streams {
+                writeln!(
+                    out,
+                    "  {:<10} rva 0x{:08x} size 0x{:x}",
+                    s.name, s.rva, s.size
+                )?;
+            }
+        }
+        Command::Headers { file } => {
+            let dn = DnPe::new(&file)?;
+            let h = report::headers(dn.net()?);
+            if cli.json {
+                return print_json(out, &h);
+            }
+            let c = &h.clr;
+            writeln!(out, "cor20 header:")?;
+            writeln!(out, "  cb                   0x{:x}", c.cb)?;
+            writeln!(
+                out,
+                "  runtime version      {}.{}",
+                c.major_runtime_version, c.minor_runtime_version
+            )?;
+            writeln!(out, "  flags                0x{:08x}", c.flags)?;
+            writeln!(
+                out,
+                "  entry point          0x{:08x}",
+                c.entry_point_token_or_rva
+            )?;
+            for (name, rva, size) in [
+                ("metadata", c.meta_data_rva, c.meta_data_size),
+                ("resources", c.resources_rva, c.resources_size),
+                (
+                    "strong name",
+                    c.strong_name_signature_rva,
+                    c.strong_name_signature_size,
+                ),
+                (
+                    "code manager",
+                    c.code_manager_table_rva,
+                    c.code_manager_table_size,
+                ),
+                ("vtable fixups", c.v_table_fixups_rva, c.v_table_fixups_size),
+                (
+                    "export jumps",
+                    c.export_address_table_jumps_rva,
+                    c.export_address_table_jumps_size,
+                ),
+                (
+                    "native header",
+                    c.managed_native_header_rva,
+                    c.managed_native_header_size,
+                ),
+            ] {
+                writeln!(out, "  {:<20} rva 0x{:08x} size 0x{:x}", name, rva, size)?;
+            }
+            let m = &h.metadata;
+            writeln!(out, "metadata root:")?;
+            writeln!(out, "  signature            0x{:08x}", m.signature)?;
+            writeln!(
+                out,
+                "  version              {}.{} {}",
+                m.major_version, m.minor_version, h.version
+            )?;
+            writeln!(out, "  version length       {}", m.version_length)?;
+            writeln!(out, "  flags                0x{:x}", h.flags)?;
+            writeln!(out, "streams:")?;
+            for s in &h.streams {
+                writeln!(
+                    out,
+                    "  {:<10} offset 0x{:08x} size 0x{:x}",
+                    s.name, s.offset, s.size
+                )?;
+            }
+            if let Some(t) = &h.tables {
+                writeln!(out, "tables header:")?;
+                writeln!(
+                    out,
+                    "  version              {}.{}",
+                    t.major_version, t.minor_version
+                )?;
+                writeln!(out, "  heap sizes           0x{:02x}", t.heap_offset_sizes)?;
+                writeln!(out, "  valid                0x{:016x}", t.mask_valid)?;
+                writeln!(out, "  sorted               0x{:016x}", t.mask_sorted)?;
+            }
+        }
+        Command::Tables {
+            file,
+            name,
+            non_empty,
+        } => {
+            let dn = DnPe::new(&file)?;
+            let tables: Vec<_> = report::tables(dn.net()?)
+                .into_iter()
+                .filter(|t| matches(&t.name, &name) && (!non_empty || t.rows > 0))
+                .collect();
+            if cli.json {
+                return print_json(out, &tables);
+            }
+            for t in &tables {
+                writeln!(
+                    out,
+                    "{:2} {:<24} rows {:<8} row size {:<3}{}",
+                    t.index,
+                    t.name,
+                    t.rows,
+                    t.row_size,
+                    if t.sorted { " sorted" } else { "" }
+                )?;
+            }
+        }
+        Command::Strings {
+            file,
+            min_len,
+            contains,
+        } => {
+            let dn = DnPe::new(&file)?;
+            let strings: Vec<_> = report::strings(dn.net()?)
+                .into_iter()
+                .filter(|s| s.value.chars().count() >= min_len && matches(&s.value, &contains))
+                .collect();
+            if cli.json {
+                return print_json(out, &strings);
+            }
+            for s in &strings {
+                writeln!(out, "0x{:08x} {}", s.offset, s.value)?;
+            }
+        }
+        Command::Us {
+            file,
+            min_len,
+            contains,
+        } => {
+            let dn = DnPe::new(&file)?;
+            let strings: Vec<_> = report::user_strings(dn.net()?)
+                .into_iter()
+                .filter(|s| s.value.chars().count() >= min_len && matches(&s.value, &contains))
+                .collect();
+            if cli.json {
+                return print_json(out, &strings);
+            }
+            for s in &strings {
+                match s.flag {
+                    Some(flag) => writeln!(out, "0x{:08x} {:02x} {:?}", s.offset, flag, s.value)?,
+                    None => writeln!(out, "0x{:08x} -- {:?}", s.offset, s.value)?,
+                }
+            }
+        }
+        Command::Methods {
+            file,
+            name,
+            type_name,
+            body,
+        } => {
+            let dn = DnPe::new(&file)?;
+            let methods: Vec<_> = report::methods(dn.net()?)?
+                .into_iter()
+                .filter(|m| {
+                    matches(&m.name, &name)
+                        && matches(&m.type_name, &type_name)
+                        && (!body || m.code_size.is_some())
+                })
+                .collect();
+            if cli.json {
+                return print_json(out, &methods);
+            }
+            for m in &methods {
+                let full_name = if m.type_name.is_empty() {
+                    m.name.clone()
+                } else {
+                    format!("{}::{}", m.type_name, m.name)
+                };
+                match (m.code_size, m.max_stack) {
+                    (Some(code_size), Some(max_stack)) => writeln!(
+                        out,
+                        "0x{:08x} rva 0x{:08x} code {:<6} stack {:<3} {}",
+                        m.token, m.rva, code_size, max_stack, full_name
+                    )?,
+                    _ => writeln!(out, "0x{:08x} rva 0x{:08x} {}", m.token, m.rva, full_name)?,
+                }
+            }
+        }
+        Command::Dis { file, token, name } => {
+            let dn = DnPe::new(&file)?;
+            let net = dn.net()?;
+            if let Some(t) = token {
+                if net.function_for(&Token::new(t)).is_none() {
+                    return Err(dnfile::error::Error::FormatError(format!(
+                        "no method body with token 0x{:08x}",
+                        t
+                    )));
+                }
+            }
+            let listings: Vec<_> = net
+                .functions()
+                .iter()
+                .filter(|f| {
+                    token.is_none_or(|t| f.method_token == Token::new(t))
+                        && matches(&f.full_name(), &name)
+                })
+                .map(|f| report::listing(net, f))
+                .collect();
+            if cli.json {
+                return print_json(out, &listings);
+            }
+            for l in &listings {
+                writeln!(out, "// 0x{:08x} {}", l.token, l.name)?;
+                writeln!(out, "// max stack {}", l.max_stack)?;
+                for m in &l.marshal {
+                    writeln!(out, "// marshal {}", m)?;
+                }
+                for i in &l.instructions {
+                    if i.operand.is_empty() {
+                        writeln!(out, "IL_{:04x}: {}", i.offset, i.opcode)?;
+                    } else {
+                        writeln!(out, "IL_{:04x}: {:<12} {}", i.offset, i.opcode, i.operand)?;
+                    }
+                }
+                writeln!(out)?;
+            }
+        }
+        Command::Resources { file, name, dump } => {
+            let dn = DnPe::new(&file)?;
+            let resources: Vec<_> = dn
+                .resources()?
+                .into_iter()
+                .filter(|r| matches(&r.name, &name))
+                .collect();
+            if let Some(dir) = &dump {
+                std::fs::create_dir_all(dir)?;
+                for r in resources
+                    .iter()
+                    .filter(|r| r.implementation.is_none() && r.error.is_none())
+                {
+                    std::fs::write(dir.join(dump_file_name(&r.name)), &r.data)?;
+                }
+            }
+            if cli.json {
+                return print_json(out, &resources);
+            }
+            for r in &resources {
+                writeln!(
+                    out,
+                    "{:<8} {:<10} {:<24} {}",
+                    if r.public { "public" } else { "private" },
+                    r.size,
+                    r.implementation.as_deref().unwrap_or("embedded"),
+                    r.name
+                )?;
+                if let Some(e) = &r.error {
+                    writeln!(out, "         error: {}", e)?;
+                }
+            }
+        }
+        Command::Imports { file, module, kind } => {
+            let dn = DnPe::new(&file)?;
+            let imports: Vec<_> = report::imports(&dn)?
+                .into_iter()
+                .filter(|i| {
+                    matches(&i.module, &module)
+                        && match kind {
+                            Some(ImportKind::Pinvoke) => i.kind == "pinvoke",
+                            Some(ImportKind::Native) => i.kind == "native",
+                            None => true,
+                        }
+                })
+                .collect();
+            if cli.json {
+                return print_json(out, &imports);
+            }
+            for i in &imports {
+                match &i.member {
+                    Some(member) => {
+                        writeln!(out, "{:<8} {}!{} -> {}", i.kind, i.module, i.name, member)?
+                    }
+                    None => writeln!(out, "{:<8} {}!{}", i.kind, i.module, i.name)?,
+                }
+                for m in &i.marshal {
+                    writeln!(out, "{:<8} {}", "", m)?;
+                }
+            }
+        }
+        Command::Refs {
+            file,
+            kind,
+            contains,
+        } => {
+            let dn = DnPe::new(&file)?;
+            let refs: Vec<_> = report::refs(dn.net()?)?
+                .into_iter()
+                .filter(|r| {
+                    matches(&r.name, &contains)
+                        && match kind {
+                            Some(RefKind::Assembly) => r.kind == "assembly",
+                            Some(RefKind::Type) => r.kind == "type",
+                            Some(RefKind::Member) => r.kind == "member",
+                            None => true,
+                        }
+                })
+                .collect();
+            if cli.json {
+                return print_json(out, &refs);
+            }
+            for r in &refs {
+                writeln!(
+                    out,
+                    "0x{:08x} {:<8} {:<48} {}",
+                    r.token, r.kind, r.name, r.scope
+                )?;
+            }
+        }
+        Command::Json { file } => {
+            let dn = DnPe::new(&file)?;
+            let net = dn.net()?;
+            print_json(
+                out,
+                &Everything {
+                    info: report::info(&dn)?,
+                    tables: report::tables(net),
+                    methods: report::methods(net)?,
+                    resources: dn.resources()?,
+                    imports: report::imports(&dn)?,
+                    refs: report::refs(net)?,
+                },
+            )?;
+        }
+        Command::Search {
+            file,
+            mut patterns,
+            ioc,
+            ignore_case,
+            source,
+        } => {
+            if ioc {
+                patterns.extend(
+                    dnfile::search::IOC_PATTERNS
+                        .iter()
+                        .map(|(_, p)| p.to_string()),
+                );
+            }
+            let regexes = patterns
+                .iter()
+                .map(|p| {
+                    regex::RegexBuilder::new(p)
+                        .case_insensitive(ignore_case)
+                        .build()
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let options = if source.is_empty() {
+                dnfile::search::SearchOptions::default()
+            } else {
+                dnfile::search::SearchOptions {
+                    user_strings: source.contains(&Source::Us),
+                    strings: source.contains(&Source::Strings),
+                    attributes: source.contains(&Source::Attributes),
+                    resources: source.contains(&Source::Resources),
+                    ..Default::default()
+                }
+            };
+            let matches = DnPe::new(&file)?.search(&regexes, &options)?;
+            if cli.json {
+                return print_json(out, &matches);
+            }
+            for m in &matches {
+                writeln!(out, "{:<56} {:?}", location_text(&m.location), m.matched)?;
+            }
+        }
+        Command::Hidden { file, dump } => {
+            let dn = DnPe::new(&file)?;
+            let hidden = dn.hidden_data()?;
+            if let Some(dir) = &dump {
+                std::fs::create_dir_all(dir)?;
+                for h in &hidden {
+                    if let Some(data) = h.offset.and_then(|o| dn.data().get(o..o + h.size)) {
+                        std::fs::write(dir.join(format!("{:?}_{:08x}.bin", h.kind, h.rva)), data)?;
+                    }
+                }
+            }
+            if cli.json {
+                return print_json(out, &hidden);
+            }
+            for h in &hidden {
+                writeln!(
+                    out,
+                    "{:<20} {:<9} rva 0x{:08x} offset {} size 0x{:x}{}",
+                    format!("{:?}", h.kind),
+                    h.stream.as_deref().unwrap_or("-"),
+                    h.rva,
+                    h.offset
+                        .map(|o| format!("0x{:08x}", o))
+                        .unwrap_or_else(|| "-".to_string()),
+                    h.size,
+                    if h.entries > 0 {
+                        format!(" ({} entries)", h.entries)
+                    } else {
+                        String::new()
+                    }
+                )?;
+            }
+        }
+        Command::Anomalies { file, severity } => {
+            let dn = DnPe::new(&file)?;
+            let min = match severity {
+                SeverityArg::Info => Severity::Info,
+                SeverityArg::Warning => Severity::Warning,
+                SeverityArg::Error => Severity::Error,
+            };
+            let anomalies: Vec<_> = dn
+                .anomalies()?
+                .into_iter()
+                .filter(|a| a.severity >= min)
+                .collect();
+            if cli.json {
+                return print_json(out, &anomalies);
+            }
+            for a in &anomalies {
+                writeln!(
+                    out,
+                    "{:<8} {:<25} {:<30} {} {}",
+                    format!("{:?}", a.severity),
+                    format!("{:?}", a.kind),
+                    a.location,
+                    a.rva
+                        .map(|r| format!("0x{:08x}", r))
+                        .unwrap_or_else(|| "-".repeat(10)),
+                    a.description
+                )?;
+            }
+        }
+        Command::Scan {
+            dir,
+            include,
+            exclude,
+            min_size,
+            max_size,
+            threads,
+            follow_links,
+        } => {
+            let mut options = dnfile::batch::ScanOptions {
+                include,
+                exclude,
+                min_size,
+                max_size,
+                follow_links,
+                ..Default::default()
+            };
+            if let Some(threads) = threads {
+                options.threads = threads;
+            }
+            let mut result = Ok(());
+            dnfile::batch::scan(&dir, &options, |record| {
+                if result.is_ok() {
+                    result = serde_json::to_string(&record)
+                        .map_err(dnfile::error::Error::from)
+                        .and_then(|line| Ok(writeln!(out, "{}", line)?));
+                }
+            })?;
+            result?;
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    match run(Cli::parse(), &mut out).and_then(|_| Ok(out.flush()?)) {
+        // the reader went away, e.g. `dnfile strings x.dll | head`
+        Err(dnfile::error::Error::IoError(e)) if e.kind() == std::io::ErrorKind::BrokenPipe => {}
+        Err(e) => {
+            eprintln!("dnfile: {}", e);
+            std::process::exit(1);
+        }
+        Ok(()) => {}
+    }
+}
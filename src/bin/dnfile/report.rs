@@ -0,0 +1,541 @@
+use dnfile::{
+    debug_info::DebugRecord,
+    entry_point::EntryPoint,
+    generics::GenericContext,
+    lang::{
+        cil::{function::Function, instruction::Operand},
+        clr::token::Token,
+    },
+    stream::{
+        meta_data_tables::{
+            mdtables::{
+                AssemblyRef, ImplMap, MemberRef, MethodDef, Module, ModuleRef, Param, TypeRef,
+            },
+            MDTablesStruct,
+        },
+        Stream, StreamHeader,
+    },
+    ClrData, ClrStruct, DnPe, MetaDataStruct, Result,
+};
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct StreamInfo {
+    pub name: String,
+    pub rva: u32,
+    pub size: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AssemblyInfo {
+    pub name: String,
+    pub version: String,
+    pub culture: String,
+    pub public_key_token: String,
+    pub strong_name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SignatureInfo {
+    pub signer: String,
+    pub issuer: String,
+    pub digest_algorithm: String,
+    pub digest_matches: bool,
+    pub signature_valid: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PdbInfo {
+    pub path: String,
+    pub guid: String,
+    pub age: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadyToRunInfo {
+    pub version: String,
+    pub compiler: Option<String>,
+    pub flags: Vec<String>,
+    pub methods: usize,
+    pub available_types: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportInfo {
+    pub ordinal: u32,
+    pub name: Option<String>,
+    pub rva: u32,
+    pub method: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Info {
+    pub file: String,
+    pub size: usize,
+    pub is_dll: bool,
+    pub clr_flags: Vec<String>,
+    pub metadata_version: String,
+    pub metadata_flags: u16,
+    pub streams: Vec<StreamInfo>,
+    pub tables: usize,
+    pub methods: usize,
+    pub functions: usize,
+    pub module: Option<String>,
+    pub mvid: Option<String>,
+    pub assembly: Option<AssemblyInfo>,
+    pub signatures: Vec<SignatureInfo>,
+    pub pdb: Vec<PdbInfo>,
+    pub ready_to_run: Option<ReadyToRunInfo>,
+    pub entry_point: Option<String>,
+    pub vtable_fixups: usize,
+    pub exports: Vec<ExportInfo>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TableInfo {
+    pub index: usize,
+    pub name: String,
+    pub rows: usize,
+    pub row_size: usize,
+    pub sorted: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HeapString {
+    pub offset: usize,
+    pub value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flag: Option<u8>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MethodInfo {
+    pub token: usize,
+    pub rva: u32,
+    pub name: String,
+    pub type_name: String,
+    pub code_size: Option<usize>,
+    pub max_stack: Option<usize>,
+    pub locals: Option<usize>,
+    pub exception_handlers: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Line {
+    pub offset: usize,
+    pub opcode: String,
+    pub operand: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Listing {
+    pub token: usize,
+    pub name: String,
+    pub max_stack: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub marshal: Vec<String>,
+    pub instructions: Vec<Line>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportInfo {
+    pub kind: &'static str,
+    pub module: String,
+    pub name: String,
+    pub member: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub marshal: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RefInfo {
+    pub kind: &'static str,
+    pub token: usize,
+    pub name: String,
+    pub scope: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Headers {
+    pub clr: ClrStruct,
+    pub metadata: MetaDataStruct,
+    pub version: String,
+    pub flags: u16,
+    pub streams: Vec<StreamHeader>,
+    pub tables: Option<MDTablesStruct>,
+}
+
+pub fn headers(net: &ClrData) -> Headers {
+    Headers {
+        clr: net.clr_header().clone(),
+        metadata: net.metadata.header().clone(),
+        version: net.metadata.version().to_string(),
+        flags: net.metadata.flags(),
+        streams: net.metadata.stream_headers().to_vec(),
+        tables: net.metadata.tables_header().cloned(),
+    }
+}
+
+fn token(table: &'static str, rid: usize) -> Result<Token> {
+    Ok(Token::new(
+        (dnfile::stream::meta_data_tables::mdtables::table_name_2_index(table)?
+            << dnfile::lang::clr::token::TABLE_SHIFT)
+            | rid,
+    ))
+}
+
+pub fn info(dn: &DnPe) -> Result<Info> {
+    let net = dn.net()?;
+    let mut streams: Vec<StreamInfo> = net
+        .metadata
+        .streams
+        .values()
+        .map(|s| StreamInfo {
+            name: s.name.clone(),
+            rva: s.rva,
+            size: s.size,
+        })
+        .collect();
+    streams.sort_by_key(|s| s.rva);
+    let (module, mvid) = match net.md_table("Module").and_then(|t| t.row::<Module>(0)) {
+        Ok(m) => (Some(m.name.clone()), Some(m.mvid.to_string())),
+        Err(_) => (None, None),
+    };
+    let strong_name = dn.strong_name().ok();
+    let assembly = net
+        .md_table("Assembly")
+        .and_then(|t| t.row::<dnfile::stream::meta_data_tables::mdtables::Assembly>(0))
+        .ok()
+        .map(|a| AssemblyInfo {
+            name: a.name.clone(),
+            version: format!(
+                "{}.{}.{}.{}",
+                a.major_version, a.minor_version, a.build_number, a.revision_number
+            ),
+            culture: a.culture.clone(),
+            public_key_token: strong_name
+                .iter()
+                .flat_map(|s| s.public_key_token.iter().map(|b| format!("{:02x}", b)))
+                .collect(),
+            strong_name: strong_name
+                .as_ref()
+                .map(|s| format!("{:?}", s.status))
+                .unwrap_or_else(|| "Unknown".to_string()),
+        });
+    Ok(Info {
+        file: dn.name().to_string(),
+        size: dn.data().len(),
+        is_dll: dn.pe()?.is_lib,
+        clr_flags: net.flags.iter().map(|f| format!("{:?}", f)).collect(),
+        metadata_version: net.metadata.version().to_string(),
+        metadata_flags: net.metadata.flags(),
+        streams,
+        tables: tables(net).len(),
+        methods: net
+            .md_table("MethodDef")
+            .map(|t| t.row_count())
+            .unwrap_or(0),
+        functions: net.functions().len(),
+        module,
+        mvid,
+        assembly,
+        signatures: dn
+            .authenticode()
+            .unwrap_or_default()
+            .iter()
+            .flat_map(|s| {
+                s.signers.iter().map(|signer| SignatureInfo {
+                    signer: signer.subject.clone().unwrap_or_default(),
+                    issuer: signer.issuer.clone(),
+                    digest_algorithm: format!("{:?}", s.digest_algorithm),
+                    digest_matches: s.digest_matches,
+                    signature_valid: signer.signature_valid,
+                })
+            })
+            .collect(),
+        pdb: dn
+            .debug_info()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|e| match e.record {
+                DebugRecord::CodeView {
+                    guid, age, path, ..
+                } => Some(PdbInfo { path, guid, age }),
+                _ => None,
+            })
+            .collect(),
+        ready_to_run: dn.ready_to_run().ok().flatten().map(|r| ReadyToRunInfo {
+            version: format!("{}.{}", r.major_version, r.minor_version),
+            compiler: r.compiler_identifier,
+            flags: r.flags.iter().map(|f| format!("{:?}", f)).collect(),
+            methods: r.methods.len(),
+            available_types: r.available_types.len(),
+        }),
+        entry_point: match net.entry_point()? {
+            Some(EntryPoint::Method { name, token, .. }) => {
+                Some(format!("{} (0x{:08x})", name, token.value))
+            }
+            Some(EntryPoint::File { name, .. }) => Some(format!("file {}", name)),
+            Some(EntryPoint::Native { rva }) => Some(format!("native rva 0x{:08x}", rva)),
+            None => None,
+        },
+        vtable_fixups: dn.vtable_fixups().map(|f| f.len()).unwrap_or(0),
+        exports: dn
+            .managed_exports()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|e| ExportInfo {
+                ordinal: e.ordinal,
+                name: e.name,
+                rva: e.rva,
+                method: e.token.and_then(|t| net.member_name(&t).ok()),
+            })
+            .collect(),
+    })
+}
+
+pub fn tables(net: &ClrData) -> Vec<TableInfo> {
+    (0..64)
+        .filter_map(|i| net.md_table_by_index(&i).ok().map(|t| (i, t)))
+        .map(|(index, t)| TableInfo {
+            index,
+            name: t.table.name().to_string(),
+            rows: t.row_count(),
+            row_size: t.row_size,
+            sorted: t.is_sorted(),
+        })
+        .collect()
+}
+
+pub fn strings(net: &ClrData) -> Vec<HeapString> {
+    let mut res = vec![];
+    for s in net.metadata.streams.values() {
+        if let Stream::StringHeap(heap) = &s.stream {
+            res.extend(heap.iter().map(|(offset, value)| HeapString {
+                offset,
+                value,
+                flag: None,
+            }));
+        }
+    }
+    res
+}
+
+pub fn user_strings(net: &ClrData) -> Vec<HeapString> {
+    let mut res = vec![];
+    for s in net.metadata.streams.values() {
+        if let Stream::UserStringHeap(heap) = &s.stream {
+            res.extend(heap.iter().map(|(offset, value, flag)| HeapString {
+                offset,
+                value,
+                flag,
+            }));
+        }
+    }
+    res
+}
+
+pub fn methods(net: &ClrData) -> Result<Vec<MethodInfo>> {
+    let table = match net.md_table("MethodDef") {
+        Ok(t) => t,
+        Err(_) => return Ok(vec![]),
+    };
+    let mut res = vec![];
+    for i in 0..table.row_count() {
+        let row = table.row::<MethodDef>(i)?;
+        let token = token("MethodDef", i + 1)?;
+        let function = net.function_for(&token);
+        res.push(MethodInfo {
+            token: token.value,
+            rva: row.rva,
+            name: row.name.clone(),
+            type_name: function.map(|f| f.type_name.clone()).unwrap_or_default(),
+            code_size: function.map(|f| f.code_size()),
+            max_stack: function.map(|f| f.max_stack()),
+            locals: function.map(|f| f.locals.len()),
+            exception_handlers: function.map(|f| f.exception_handlers().len()),
+        });
+    }
+    Ok(res)
+}
+
+fn operand_text(
+    net: &ClrData,
+    context: &GenericContext,
+    operand: &Operand,
+    is_branch: bool,
+) -> String {
+    match operand {
+        Operand::Token(t) => net
+            .member_name_with(t, context)
+            .unwrap_or_else(|_| format!("0x{:08x}", t.value)),
+        Operand::StringToken(t) => match net.get_us(t.rid()) {
+            Ok(s) => format!("{:?}", s),
+            Err(_) => format!("0x{:08x}", t.value),
+        },
+        Operand::Int(i) if is_branch => format!("IL_{:04x}", i),
+        Operand::Int(i) => i.to_string(),
+        Operand::Float(f) => f.to_string(),
+        Operand::Local(l) => format!("V_{}", l.index()),
+        Operand::Argument(a) => format!("A_{}", a.index()),
+        Operand::Arguments(v) => format!(
+            "({})",
+            v.iter()
+                .map(|o| operand_text(net, context, o, is_branch))
+                .collect::<Vec<String>>()
+                .join(", ")
+        ),
+        Operand::None => String::new(),
+    }
+}
+
+fn param_marshal(net: &ClrData, method_rid: usize) -> Vec<String> {
+    let method = match net
+        .md_table("MethodDef")
+        .and_then(|t| t.row::<MethodDef>(method_rid.wrapping_sub(1)))
+    {
+        Ok(row) => row,
+        Err(_) => return vec![],
+    };
+    let mut res = vec![];
+    for p in &method.param_list {
+        let native_type = match net.param_marshal(p.row_index) {
+            Ok(Some(t)) => t,
+            _ => continue,
+        };
+        let name = match net
+            .md_table("Param")
+            .and_then(|t| t.row::<Param>(p.row_index - 1))
+        {
+            Ok(param) if param.sequence > 0 => param.name.clone(),
+            _ => "return".to_string(),
+        };
+        res.push(format!("{}: {}", name, native_type.name()));
+    }
+    res
+}
+
+pub fn listing(net: &ClrData, function: &Function) -> Listing {
+    let base = function.code_base();
+    let context = GenericContext::for_function(function);
+    Listing {
+        token: function.method_token.value,
+        name: function.full_name(),
+        max_stack: function.max_stack(),
+        marshal: param_marshal(net, function.method_token.rid()),
+        instructions: function
+            .instructions
+            .iter()
+            .map(|insn| Line {
+                offset: insn.offset - base,
+                opcode: insn.opcode.name.to_string(),
+                operand: operand_text(
+                    net,
+                    &context,
+                    &insn.rebased_operand(base as i64),
+                    insn.is_branch(),
+                ),
+            })
+            .collect(),
+    }
+}
+
+pub fn imports(dn: &DnPe) -> Result<Vec<ImportInfo>> {
+    let net = dn.net()?;
+    let mut res = vec![];
+    if let Ok(table) = net.md_table("ImplMap") {
+        for i in 0..table.row_count() {
+            let row = table.row::<ImplMap>(i)?;
+            let module = match row.import_scope.row_index.checked_sub(1) {
+                Some(rid) => net
+                    .md_table("ModuleRef")?
+                    .row::<ModuleRef>(rid)?
+                    .name
+                    .clone(),
+                None => String::new(),
+            };
+            let member = token(row.member_forwarded.table, row.member_forwarded.row_index)
+                .and_then(|t| net.member_name(&t))
+                .ok();
+            let marshal = match row.member_forwarded.table {
+                "MethodDef" => param_marshal(net, row.member_forwarded.row_index),
+                _ => vec![],
+            };
+            res.push(ImportInfo {
+                kind: "pinvoke",
+                module,
+                name: row.import_name.clone(),
+                member,
+                marshal,
+            });
+        }
+    }
+    for import in dn.pe()?.imports {
+        res.push(ImportInfo {
+            kind: "native",
+            module: import.dll.to_string(),
+            name: import.name.to_string(),
+            member: None,
+            marshal: vec![],
+        });
+    }
+    Ok(res)
+}
+
+pub fn refs(net: &ClrData) -> Result<Vec<RefInfo>> {
+    let mut res = vec![];
+    if let Ok(table) = net.md_table("AssemblyRef") {
+        for i in 0..table.row_count() {
+            let row = table.row::<AssemblyRef>(i)?;
+            res.push(RefInfo {
+                kind: "assembly",
+                token: token("AssemblyRef", i + 1)?.value,
+                name: row.name.clone(),
+                scope: format!(
+                    "{}.{}.{}.{}",
+                    row.major_version, row.minor_version, row.build_number, row.revision_number
+                ),
+            });
+        }
+    }
+    if let Ok(table) = net.md_table("TypeRef") {
+        for i in 0..table.row_count() {
+            let row = table.row::<TypeRef>(i)?;
+            let scope = match (row.resolution_scope.table, row.resolution_scope.row_index) {
+                (_, 0) => String::new(),
+                ("AssemblyRef", rid) => net
+                    .md_table("AssemblyRef")?
+                    .row::<AssemblyRef>(rid - 1)?
+                    .name
+                    .clone(),
+                ("ModuleRef", rid) => net
+                    .md_table("ModuleRef")?
+                    .row::<ModuleRef>(rid - 1)?
+                    .name
+                    .clone(),
+                ("TypeRef", rid) => net.type_name(&token("TypeRef", rid)?)?,
+                (table, _) => table.to_string(),
+            };
+            let t = token("TypeRef", i + 1)?;
+            res.push(RefInfo {
+                kind: "type",
+                token: t.value,
+                name: net.type_name(&t)?,
+                scope,
+            });
+        }
+    }
+    if let Ok(table) = net.md_table("MemberRef") {
+        for i in 0..table.row_count() {
+            let row = table.row::<MemberRef>(i)?;
+            let t = token("MemberRef", i + 1)?;
+            res.push(RefInfo {
+                kind: "member",
+                token: t.value,
+                name: net.member_name(&t).unwrap_or_else(|_| row.name.clone()),
+                scope: row.class.table.to_string(),
+            });
+        }
+    }
+    Ok(res)
+}
@@ -0,0 +1,124 @@
+use crate::{error::Error, DnPe, Result};
+use goblin::pe::debug::{
+    IMAGE_DEBUG_TYPE_CODEVIEW, IMAGE_DEBUG_TYPE_EMBEDDEDPORTABLEPDB, IMAGE_DEBUG_TYPE_PDBCHECKSUM,
+    IMAGE_DEBUG_TYPE_REPRO,
+};
+
+const RSDS_SIGNATURE: &[u8] = b"RSDS";
+const MPDB_SIGNATURE: &[u8] = b"MPDB";
+const PORTABLE_PDB_MINOR_VERSION: u16 = 0x504d;
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub enum DebugRecord {
+    CodeView {
+        guid: String,
+        age: u32,
+        path: String,
+        portable: bool,
+    },
+    Reproducible,
+    PdbChecksum {
+        algorithm: String,
+        checksum: Vec<u8>,
+    },
+    EmbeddedPortablePdb {
+        uncompressed_size: usize,
+        #[serde(skip_serializing)]
+        metadata: Vec<u8>,
+    },
+    Other,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DebugEntry {
+    pub data_type: u32,
+    pub time_date_stamp: u32,
+    pub major_version: u16,
+    pub minor_version: u16,
+    pub rva: u32,
+    pub offset: u32,
+    pub size: u32,
+    pub record: DebugRecord,
+}
+
+fn code_view(data: &[u8], portable: bool) -> Result<DebugRecord> {
+    if data.get(..4) != Some(RSDS_SIGNATURE) || data.len() < 24 {
+        return Ok(DebugRecord::Other);
+    }
+    let path = &data[24..];
+    Ok(DebugRecord::CodeView {
+        guid: uuid::Uuid::from_bytes_le(data[4..20].try_into()?).to_string(),
+        age: u32::from_le_bytes(data[20..24].try_into()?),
+        path: String::from_utf8_lossy(path.split(|b| *b == 0).next().unwrap_or_default())
+            .to_string(),
+        portable,
+    })
+}
+
+fn pdb_checksum(data: &[u8]) -> DebugRecord {
+    let mut parts = data.splitn(2, |b| *b == 0);
+    DebugRecord::PdbChecksum {
+        algorithm: String::from_utf8_lossy(parts.next().unwrap_or_default()).to_string(),
+        checksum: parts.next().unwrap_or_default().to_vec(),
+    }
+}
+
+fn embedded_portable_pdb(data: &[u8]) -> Result<DebugRecord> {
+    if data.get(..4) != Some(MPDB_SIGNATURE) || data.len() < 8 {
+        return Err(Error::FormatError(
+            "embedded portable pdb without MPDB signature".to_string(),
+        ));
+    }
+    let uncompressed_size = u32::from_le_bytes(data[4..8].try_into()?) as usize;
+    let metadata =
+        miniz_oxide::inflate::decompress_to_vec_with_limit(&data[8..], uncompressed_size).map_err(
+            |e| {
+                Error::FormatError(format!(
+                    "embedded portable pdb inflate error {:?}",
+                    e.status
+                ))
+            },
+        )?;
+    Ok(DebugRecord::EmbeddedPortablePdb {
+        uncompressed_size,
+        metadata,
+    })
+}
+
+impl DnPe {
+    pub fn debug_info(&self) -> Result<Vec<DebugEntry>> {
+        let mut res = vec![];
+        let pe = self.pe()?;
+        let debug_data = match &pe.debug_data {
+            Some(d) => d,
+            None => return Ok(res),
+        };
+        for entry in debug_data.entries() {
+            let entry = entry?;
+            let start = entry.pointer_to_raw_data as usize;
+            let end = start + entry.size_of_data as usize;
+            let data = self.data.get(start..end).unwrap_or_default();
+            let record = match entry.data_type {
+                IMAGE_DEBUG_TYPE_CODEVIEW => {
+                    code_view(data, entry.minor_version == PORTABLE_PDB_MINOR_VERSION)
+                }
+                IMAGE_DEBUG_TYPE_REPRO => Ok(DebugRecord::Reproducible),
+                IMAGE_DEBUG_TYPE_PDBCHECKSUM => Ok(pdb_checksum(data)),
+                IMAGE_DEBUG_TYPE_EMBEDDEDPORTABLEPDB => embedded_portable_pdb(data),
+                _ => Ok(DebugRecord::Other),
+            }
+            .unwrap_or(DebugRecord::Other);
+            res.push(DebugEntry {
+                data_type: entry.data_type,
+                time_date_stamp: entry.time_date_stamp,
+                major_version: entry.major_version,
+                minor_version: entry.minor_version,
+                rva: entry.address_of_raw_data,
+                offset: entry.pointer_to_raw_data,
+                size: entry.size_of_data,
+                record,
+            });
+        }
+        Ok(res)
+    }
+}
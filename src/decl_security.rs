@@ -0,0 +1,53 @@
+use crate::{
+    lang::clr::{
+        security::{parse_permission_set, PermissionSet},
+        token::{Token, TABLE_SHIFT},
+    },
+    stream::meta_data_tables::mdtables::{
+        enums::CorDeclSecurity, table_name_2_index, Assembly, DeclSecurity,
+    },
+    ClrData, Result,
+};
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DeclSecurityInfo {
+    pub token: Token,
+    pub action: CorDeclSecurity,
+    pub owner: Token,
+    pub owner_name: String,
+    pub permission_set: Option<PermissionSet>,
+}
+
+impl ClrData {
+    pub fn decl_security(&self) -> Result<Vec<DeclSecurityInfo>> {
+        let mut res = vec![];
+        let table = match self.md_table("DeclSecurity") {
+            Ok(t) => t,
+            Err(_) => return Ok(res),
+        };
+        let security_table = table_name_2_index("DeclSecurity")?;
+        for i in 0..table.row_count() {
+            let row = table.row::<DeclSecurity>(i)?;
+            let owner = Token::new(
+                (table_name_2_index(row.parent.table)? << TABLE_SHIFT) | row.parent.row_index,
+            );
+            let owner_name = match row.parent.table {
+                "Assembly" => self
+                    .md_table("Assembly")
+                    .and_then(|t| t.row::<Assembly>(row.parent.row_index.wrapping_sub(1)))
+                    .map(|a| a.name.clone()),
+                "TypeDef" => self.type_name(&owner),
+                _ => self.member_name(&owner),
+            }
+            .unwrap_or_default();
+            res.push(DeclSecurityInfo {
+                token: Token::new((security_table << TABLE_SHIFT) | (i + 1)),
+                action: CorDeclSecurity::new(row.action as usize),
+                owner,
+                owner_name,
+                permission_set: parse_permission_set(&row.permission_set).ok(),
+            });
+        }
+        Ok(res)
+    }
+}
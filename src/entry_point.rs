@@ -0,0 +1,154 @@
+use crate::{
+    error::Error,
+    lang::{
+        cil::function::Function,
+        clr::token::{Token, TABLE_SHIFT},
+    },
+    stream::meta_data_tables::mdtables::{table_name_2_index, File, MethodDef},
+    ClrData, ClrHeaderFlags, DnPe, Result,
+};
+
+const MODULE_TYPE_RID: usize = 1;
+
+#[derive(Debug, serde::Serialize)]
+pub enum EntryPoint<'a> {
+    Method {
+        token: Token,
+        name: String,
+        function: Option<&'a Function>,
+    },
+    File {
+        token: Token,
+        name: String,
+    },
+    Native {
+        rva: u32,
+    },
+}
+
+#[derive(Debug, Clone, Copy, serde::Serialize, PartialEq, Eq)]
+pub enum EntryPointKind {
+    Main,
+    ModuleInitializer,
+    StaticConstructor,
+    Export,
+    Native,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct EntryPointInfo {
+    pub kind: EntryPointKind,
+    pub token: Option<Token>,
+    pub rva: Option<u32>,
+    pub name: String,
+}
+
+impl ClrData {
+    pub fn entry_point(&self) -> Result<Option<EntryPoint<'_>>> {
+        let value = self.clr_struct.entry_point_token_or_rva;
+        if value == 0 {
+            return Ok(None);
+        }
+        if self.flags.contains(&ClrHeaderFlags::NativeEntryPiont) {
+            return Ok(Some(EntryPoint::Native { rva: value }));
+        }
+        let token = Token::new(value as usize);
+        let rid = token.rid();
+        if rid == 0 {
+            return Err(Error::RowIndexOutOfBound(0, 0));
+        }
+        if token.table() == table_name_2_index("File")? {
+            let name = self.md_table("File")?.row::<File>(rid - 1)?.name.clone();
+            return Ok(Some(EntryPoint::File { token, name }));
+        }
+        if token.table() != table_name_2_index("MethodDef")? {
+            return Err(Error::UndefinedMetaDataTableIndex(token.table() as u32));
+        }
+        Ok(Some(EntryPoint::Method {
+            name: self.method_full_name(rid)?,
+            function: self.function_for(&token),
+            token,
+        }))
+    }
+
+    pub fn implicit_entry_points(&self) -> Result<Vec<EntryPointInfo>> {
+        let mut res = vec![];
+        let table = match self.md_table("MethodDef") {
+            Ok(t) => t,
+            Err(_) => return Ok(res),
+        };
+        let method_def = table_name_2_index("MethodDef")?;
+        for i in 0..table.row_count() {
+            if table.row::<MethodDef>(i)?.name != ".cctor" {
+                continue;
+            }
+            let kind = match self.owners.method_types.get(&(i + 1)) {
+                Some(&MODULE_TYPE_RID) => EntryPointKind::ModuleInitializer,
+                _ => EntryPointKind::StaticConstructor,
+            };
+            res.push(EntryPointInfo {
+                kind,
+                token: Some(Token::new((method_def << TABLE_SHIFT) | (i + 1))),
+                rva: None,
+                name: self.method_full_name(i + 1)?,
+            });
+        }
+        Ok(res)
+    }
+
+    fn method_full_name(&self, rid: usize) -> Result<String> {
+        if rid == 0 {
+            return Err(Error::FormatError("no entry point".to_string()));
+        }
+        let name = &self.md_table("MethodDef")?.row::<MethodDef>(rid - 1)?.name;
+        Ok(match self.owners.method_types.get(&rid) {
+            Some(type_rid) => format!(
+                "{}::{}",
+                self.type_name(&Token::new(
+                    (table_name_2_index("TypeDef")? << TABLE_SHIFT) | type_rid
+                ))?,
+                name
+            ),
+            None => name.clone(),
+        })
+    }
+}
+
+impl DnPe {
+    pub fn entry_points(&self) -> Result<Vec<EntryPointInfo>> {
+        let net = self.net()?;
+        let mut res = vec![];
+        match net.entry_point()? {
+            Some(EntryPoint::Method { token, name, .. })
+            | Some(EntryPoint::File { token, name }) => res.push(EntryPointInfo {
+                kind: EntryPointKind::Main,
+                token: Some(token),
+                rva: None,
+                name,
+            }),
+            Some(EntryPoint::Native { rva }) => res.push(EntryPointInfo {
+                kind: EntryPointKind::Native,
+                token: None,
+                rva: Some(rva),
+                name: String::new(),
+            }),
+            None => {}
+        }
+        res.extend(net.implicit_entry_points()?);
+        for export in self.managed_exports()? {
+            let token = match export.token {
+                Some(t) if t.table() == table_name_2_index("MethodDef")? => t,
+                _ => continue,
+            };
+            res.push(EntryPointInfo {
+                kind: EntryPointKind::Export,
+                rva: Some(export.rva),
+                name: net
+                    .method_full_name(token.rid())
+                    .unwrap_or_else(|_| format!("0x{:08x}", token.value)),
+                token: Some(token),
+            });
+        }
+        Ok(res)
+    }
+}
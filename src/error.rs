@@ -11,6 +11,10 @@ pub enum Error {
     #[error("{0}")]
     IoError(#[from] std::io::Error),
     #[error("{0}")]
+    ScrollError(#[from] scroll::Error),
+    #[error("{0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("{0}")]
     UnresolvedRvaError(u32),
     #[error("{0}")]
     Utf8Error(#[from] std::string::FromUtf8Error),
@@ -62,6 +66,16 @@ pub enum Error {
     ConversionError(&'static str),
     #[error("{0}")]
     MethodBodyFormatError(String),
+    #[error("{0}")]
+    MethodBodyEncodeError(String),
+    #[error("{0}")]
+    SignatureFormatError(String),
+    #[error("cyclic type reference through token {0:#010x}")]
+    CyclicTypeReference(usize),
+    #[error("{0}")]
+    PeWriteError(String),
+    #[error("{0}")]
+    MetaDataEncodeError(String),
     #[error("operand not have value")]
     OperandHasNoValue,
     #[error("incorrect cast to {0} in file {1} at line {2}")]
@@ -0,0 +1,137 @@
+use crate::{
+    lang::cil::function::Function,
+    lang::clr::{
+        signature::{parse_method_spec, SigType},
+        token::{Token, TABLE_SHIFT},
+    },
+    stream::meta_data_tables::mdtables::{
+        enums::{ClrGenericParamAttr, CorGenericParamSpecialConstraint, CorGenericParamVariance},
+        table_name_2_index, GenericParam, GenericParamConstraint, MethodSpec,
+    },
+    ClrData, Result,
+};
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct GenericParamInfo {
+    pub token: Token,
+    pub number: u32,
+    pub name: String,
+    pub owner: Token,
+    pub variance: CorGenericParamVariance,
+    pub special_constraints: Vec<CorGenericParamSpecialConstraint>,
+    pub constraints: Vec<String>,
+}
+
+/// The type and method whose generic parameters `!n` and `!!n` refer to.
+#[derive(Debug, Clone, Default)]
+pub struct GenericContext {
+    pub type_def: Option<Token>,
+    pub method: Option<Token>,
+}
+
+impl GenericContext {
+    pub fn for_function(function: &Function) -> Self {
+        Self {
+            type_def: function.declaring_type.clone(),
+            method: Some(function.method_token.clone()),
+        }
+    }
+}
+
+impl ClrData {
+    /// Context of a TypeDef or MethodDef, a method also seeing the
+    /// parameters of its declaring type.
+    pub fn generic_context(&self, owner: &Token) -> Result<GenericContext> {
+        let type_def = table_name_2_index("TypeDef")?;
+        Ok(match owner.table() {
+            t if t == type_def => GenericContext {
+                type_def: Some(owner.clone()),
+                method: None,
+            },
+            t if t == table_name_2_index("MethodDef")? => GenericContext {
+                type_def: self
+                    .method_declaring_type(owner.rid())
+                    .map(|rid| Token::new((type_def << TABLE_SHIFT) | rid)),
+                method: Some(owner.clone()),
+            },
+            _ => GenericContext::default(),
+        })
+    }
+
+    pub fn generic_params(&self, owner: &Token) -> Result<Vec<GenericParamInfo>> {
+        let mut res = vec![];
+        let rids = match self.owners.generic_params.get(&owner.value) {
+            Some(r) => r,
+            None => return Ok(res),
+        };
+        let table = self.md_table("GenericParam")?;
+        let context = self.generic_context(owner)?;
+        let param_table = table_name_2_index("GenericParam")?;
+        for rid in rids {
+            let row = table.row::<GenericParam>(rid - 1)?;
+            let mut info = GenericParamInfo {
+                token: Token::new((param_table << TABLE_SHIFT) | rid),
+                number: row.number,
+                name: row.name.clone(),
+                owner: owner.clone(),
+                variance: CorGenericParamVariance::NonVariant,
+                special_constraints: vec![],
+                constraints: self.generic_param_constraints(*rid, &context)?,
+            };
+            for flag in &row.flags {
+                match flag {
+                    ClrGenericParamAttr::Variance(v) => info.variance = v.clone(),
+                    ClrGenericParamAttr::SpecialConstraint(
+                        CorGenericParamSpecialConstraint::NoSpecialConstraint,
+                    ) => {}
+                    ClrGenericParamAttr::SpecialConstraint(c) => {
+                        info.special_constraints.push(c.clone())
+                    }
+                }
+            }
+            res.push(info);
+        }
+        Ok(res)
+    }
+
+    /// Name of generic parameter `number` of `owner`, the target of `!n`
+    /// when `owner` is a TypeDef and of `!!n` when it is a MethodDef.
+    pub fn generic_param_name(&self, owner: &Token, number: usize) -> Option<String> {
+        let table = self.md_table("GenericParam").ok()?;
+        self.owners
+            .generic_params
+            .get(&owner.value)?
+            .iter()
+            .filter_map(|rid| table.row::<GenericParam>(rid - 1).ok())
+            .find(|row| row.number as usize == number)
+            .map(|row| row.name.clone())
+    }
+
+    fn generic_param_constraints(
+        &self,
+        param_rid: usize,
+        context: &GenericContext,
+    ) -> Result<Vec<String>> {
+        let mut res = vec![];
+        if let Some(rows) = self.owners.generic_param_constraints.get(&param_rid) {
+            let table = self.md_table("GenericParamConstraint")?;
+            for i in rows {
+                let row = table.row::<GenericParamConstraint>(*i)?;
+                if row.constraint.row_index == 0 {
+                    continue;
+                }
+                let token = Token::new(
+                    (table_name_2_index(row.constraint.table)? << TABLE_SHIFT)
+                        | row.constraint.row_index,
+                );
+                res.push(self.type_name_with(&token, context)?);
+            }
+        }
+        Ok(res)
+    }
+
+    pub fn method_spec_instantiation(&self, rid: usize) -> Result<Vec<SigType>> {
+        let row = self.md_table("MethodSpec")?.row::<MethodSpec>(rid - 1)?;
+        parse_method_spec(&row.instantiation)
+    }
+}
@@ -0,0 +1,276 @@
+use crate::{
+    lang::cil::instruction::Operand,
+    stream::{meta_data_tables::mdtables::MDTableRowTraitT, Stream},
+    writer::metadata::Column,
+    DnPe, Result,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum HiddenDataKind {
+    UnreferencedEntries,
+    HeapSlack,
+    TableSlack,
+    StreamGap,
+    StreamOverlap,
+    MetadataSlack,
+    OutsideMetadata,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct HiddenData {
+    pub kind: HiddenDataKind,
+    pub stream: Option<String>,
+    pub rva: u32,
+    pub offset: Option<usize>,
+    pub size: usize,
+    pub entries: usize,
+}
+
+#[derive(Default)]
+struct References {
+    strings: std::collections::BTreeSet<usize>,
+    blobs: std::collections::BTreeSet<usize>,
+    guids: std::collections::BTreeSet<usize>,
+    user_strings: std::collections::BTreeSet<usize>,
+}
+
+fn merge(ranges: Vec<(usize, usize)>) -> Vec<(usize, usize, usize)> {
+    let mut res: Vec<(usize, usize, usize)> = vec![];
+    for (start, end) in ranges {
+        match res.last_mut() {
+            Some(last) if last.1 == start => {
+                last.1 = end;
+                last.2 += 1;
+            }
+            _ => res.push((start, end, 1)),
+        }
+    }
+    res
+}
+
+fn non_zero(data: &[u8]) -> bool {
+    data.iter().any(|b| *b != 0)
+}
+
+impl DnPe {
+    fn heap_references(&self) -> Result<References> {
+        let net = self.net()?;
+        let mut refs = References::default();
+        for i in 0..64 {
+            let table = match net.md_table_by_index(&i) {
+                Ok(t) => t,
+                Err(_) => continue,
+            };
+            for r in 0..table.row_count() {
+                let row = table.get_row(r)?;
+                Self::row_references(row, &mut refs)?;
+            }
+        }
+        for f in net.functions() {
+            for insn in &f.instructions {
+                if let Operand::StringToken(t) = &insn.operand {
+                    refs.user_strings.insert(t.rid());
+                }
+            }
+        }
+        Ok(refs)
+    }
+
+    fn row_references(row: &dyn MDTableRowTraitT, refs: &mut References) -> Result<()> {
+        let data = row.data();
+        let mut pos = 0;
+        for column in row.columns()? {
+            let size = column.size();
+            let value = match data.get(pos..pos + size) {
+                Some(bytes) => crate::utils::read_usize(bytes)?,
+                None => return Ok(()),
+            };
+            match column {
+                Column::String(_) => refs.strings.insert(value),
+                Column::Blob(_) => refs.blobs.insert(value),
+                Column::Guid(_) => refs.guids.insert(value),
+                _ => false,
+            };
+            pos += size;
+        }
+        Ok(())
+    }
+
+    fn hidden(
+        &self,
+        kind: HiddenDataKind,
+        stream: Option<&str>,
+        rva: u32,
+        size: usize,
+    ) -> HiddenData {
+        HiddenData {
+            kind,
+            stream: stream.map(|s| s.to_string()),
+            rva,
+            offset: self.offset(rva).ok(),
+            size,
+            entries: 0,
+        }
+    }
+
+    pub fn hidden_data(&self) -> Result<Vec<HiddenData>> {
+        let net = self.net()?;
+        let clr_struct = self.clr_struct()?;
+        let refs = self.heap_references()?;
+        let mut res = vec![];
+
+        let mut streams: Vec<_> = net.metadata.streams.values().collect();
+        streams.sort_by_key(|s| (s.rva, s.size));
+        for s in &streams {
+            let (unreferenced, used_end) = match &s.stream {
+                Stream::StringHeap(heap) => {
+                    let mut unreferenced = vec![];
+                    let mut end = 0;
+                    for (offset, _) in heap.iter() {
+                        let len = heap.data()[offset..]
+                            .iter()
+                            .position(|b| *b == 0)
+                            .unwrap_or(heap.data().len() - offset);
+                        end = offset + len + 1;
+                        if refs.strings.range(offset..offset + len).next().is_none() {
+                            unreferenced.push((offset, end.min(heap.data().len())));
+                        }
+                    }
+                    (unreferenced, end.min(heap.data().len()))
+                }
+                Stream::BlobHeap(heap) => {
+                    let mut unreferenced = vec![];
+                    let mut end = 0;
+                    for (offset, _) in heap.iter() {
+                        let (length, length_size) =
+                            crate::utils::read_compressed_usize(&heap.data()[offset..])?;
+                        end = offset + length_size + length;
+                        if !refs.blobs.contains(&offset) {
+                            unreferenced.push((offset, end));
+                        }
+                    }
+                    (unreferenced, end)
+                }
+                Stream::UserStringHeap(heap) => {
+                    let mut unreferenced = vec![];
+                    let mut end = 0;
+                    for (offset, _, _) in heap.iter() {
+                        let (length, length_size) =
+                            crate::utils::read_compressed_usize(&heap.data()[offset..])?;
+                        end = offset + length_size + length;
+                        if !refs.user_strings.contains(&offset) {
+                            unreferenced.push((offset, end));
+                        }
+                    }
+                    (unreferenced, end)
+                }
+                Stream::GuidHeap(heap) => {
+                    let mut unreferenced = vec![];
+                    let mut end = 0;
+                    for (index, _) in heap.iter() {
+                        end = index * 16;
+                        if !refs.guids.contains(&index) {
+                            unreferenced.push((end - 16, end));
+                        }
+                    }
+                    (unreferenced, end)
+                }
+                Stream::MetaDataTables(tables) => {
+                    let end = tables
+                        .tables
+                        .values()
+                        .filter(|t| t.num_rows > 0)
+                        .map(|t| (t.rva - s.rva) as usize + t.row_size * t.num_rows)
+                        .max()
+                        .unwrap_or(0);
+                    if let Ok(data) = self.get_vec(&s.rva, &s.size) {
+                        if end < s.size && non_zero(&data[end..]) {
+                            res.push(self.hidden(
+                                HiddenDataKind::TableSlack,
+                                Some(&s.name),
+                                s.rva + end as u32,
+                                s.size - end,
+                            ));
+                        }
+                    }
+                    continue;
+                }
+                Stream::GenericStream(_) => continue,
+            };
+            for (start, end, entries) in merge(unreferenced) {
+                let mut hidden = self.hidden(
+                    HiddenDataKind::UnreferencedEntries,
+                    Some(&s.name),
+                    s.rva + start as u32,
+                    end - start,
+                );
+                hidden.entries = entries;
+                res.push(hidden);
+            }
+            if let Ok(data) = self.get_vec(&s.rva, &s.size) {
+                if used_end < s.size && non_zero(&data[used_end..]) {
+                    res.push(self.hidden(
+                        HiddenDataKind::HeapSlack,
+                        Some(&s.name),
+                        s.rva + used_end as u32,
+                        s.size - used_end,
+                    ));
+                }
+            }
+        }
+
+        // ranges that wrap past the end of the address space are reported by
+        // anomalies() as RangeOverflow, there is nothing to measure here
+        let metadata_start = clr_struct.meta_data_rva;
+        let metadata_end = match metadata_start.checked_add(clr_struct.meta_data_size) {
+            Some(end) => end,
+            None => return Ok(res),
+        };
+        let headers_size = streams
+            .iter()
+            .map(|s| s.stream_table_entry_size as u64)
+            .sum::<u64>()
+            + net.metadata.header().version_length as u64
+            + 20;
+        let mut prev_end = match u32::try_from(metadata_start as u64 + headers_size) {
+            Ok(end) => end,
+            Err(_) => return Ok(res),
+        };
+        for s in &streams {
+            let end = match s.rva.checked_add(s.size as u32) {
+                Some(end) => end,
+                None => continue,
+            };
+            if s.rva > prev_end {
+                let size = (s.rva - prev_end) as usize;
+                if self.get_vec(&prev_end, &size).is_ok_and(|d| non_zero(&d)) {
+                    res.push(self.hidden(HiddenDataKind::StreamGap, None, prev_end, size));
+                }
+            } else if s.rva < prev_end {
+                res.push(self.hidden(
+                    HiddenDataKind::StreamOverlap,
+                    Some(&s.name),
+                    s.rva,
+                    (prev_end.min(end) - s.rva) as usize,
+                ));
+            }
+            if end > metadata_end {
+                let start = s.rva.max(metadata_end);
+                res.push(self.hidden(
+                    HiddenDataKind::OutsideMetadata,
+                    Some(&s.name),
+                    start,
+                    (end - start) as usize,
+                ));
+            }
+            prev_end = prev_end.max(end);
+        }
+        if prev_end < metadata_end {
+            let size = (metadata_end - prev_end) as usize;
+            if self.get_vec(&prev_end, &size).is_ok_and(|d| non_zero(&d)) {
+                res.push(self.hidden(HiddenDataKind::MetadataSlack, None, prev_end, size));
+            }
+        }
+        Ok(res)
+    }
+}
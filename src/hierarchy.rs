@@ -0,0 +1,215 @@
+use crate::{
+    error::Error,
+    lang::clr::{
+        signature::{parse_type_spec, SigType},
+        token::{Token, TABLE_SHIFT},
+    },
+    stream::meta_data_tables::mdtables::{
+        table_name_2_index, InterfaceImpl, MethodImpl, TypeDef, TypeRef, TypeSpec,
+    },
+    ClrData, Result,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct TypeReference {
+    pub token: Token,
+    pub name: String,
+    pub type_def: Option<usize>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MethodOverride {
+    pub body: Token,
+    pub body_name: String,
+    pub declaration: Token,
+    pub declaration_name: String,
+}
+
+fn index_token(table: &'static str, row_index: usize) -> Result<Token> {
+    Ok(Token::new(
+        (table_name_2_index(table)? << TABLE_SHIFT) | row_index,
+    ))
+}
+
+fn name_matches(reference: &TypeReference, name: &str) -> bool {
+    reference.name == name || reference.name.split('<').next() == Some(name)
+}
+
+impl ClrData {
+    pub fn type_reference(&self, token: &Token) -> Result<TypeReference> {
+        let name = self.type_name(token)?;
+        let type_def = match token.table() {
+            t if t == table_name_2_index("TypeDef")? => Some(token.rid()),
+            t if t == table_name_2_index("TypeRef")? => {
+                let row = self.md_table("TypeRef")?.row::<TypeRef>(token.rid() - 1)?;
+                if row.resolution_scope.table == "AssemblyRef" && row.resolution_scope.row_index > 0
+                {
+                    None
+                } else {
+                    self.owners.type_names.get(&name).copied()
+                }
+            }
+            // a generic instantiation such as Base<int> resolves to the
+            // definition of Base`1
+            t if t == table_name_2_index("TypeSpec")? => {
+                let row = self
+                    .md_table("TypeSpec")?
+                    .row::<TypeSpec>(token.rid() - 1)?;
+                match parse_type_spec(&row.signature)? {
+                    SigType::GenericInst(generic, _) => match *generic {
+                        SigType::Class(t) | SigType::ValueType(t)
+                            if t.table() != table_name_2_index("TypeSpec")? =>
+                        {
+                            self.type_reference(&t)?.type_def
+                        }
+                        _ => None,
+                    },
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        Ok(TypeReference {
+            token: token.clone(),
+            // type_name has already rejected rid 0
+            name: match type_def {
+                Some(rid) if token.table() == table_name_2_index("TypeDef")? => self
+                    .owners
+                    .qualified_type_names
+                    .get(rid - 1)
+                    .cloned()
+                    .unwrap_or(name),
+                _ => name,
+            },
+            type_def,
+        })
+    }
+
+    pub fn base_type(&self, type_rid: usize) -> Result<Option<TypeReference>> {
+        let table = self.md_table("TypeDef")?;
+        let row = match type_rid.checked_sub(1) {
+            Some(i) => table.row::<TypeDef>(i)?,
+            None => return Err(Error::RowIndexOutOfBound(0, table.row_count())),
+        };
+        if row.extends.row_index == 0 {
+            return Ok(None);
+        }
+        let token = index_token(row.extends.table, row.extends.row_index)?;
+        Ok(Some(self.type_reference(&token)?))
+    }
+
+    pub fn base_types(&self, type_rid: usize) -> Result<Vec<TypeReference>> {
+        let mut res = vec![];
+        let mut visited = std::collections::HashSet::new();
+        let mut current = type_rid;
+        visited.insert(current);
+        while let Some(base) = self.base_type(current)? {
+            let next = base.type_def;
+            res.push(base);
+            match next {
+                Some(rid) if visited.insert(rid) => current = rid,
+                _ => break,
+            }
+        }
+        Ok(res)
+    }
+
+    pub fn interfaces(&self, type_rid: usize) -> Result<Vec<TypeReference>> {
+        let mut res = vec![];
+        if let Ok(table) = self.md_table("InterfaceImpl") {
+            for i in 0..table.row_count() {
+                let row = table.row::<InterfaceImpl>(i)?;
+                if row.class.row_index == type_rid && row.interface.row_index > 0 {
+                    let token = index_token(row.interface.table, row.interface.row_index)?;
+                    res.push(self.type_reference(&token)?);
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    pub fn all_interfaces(&self, type_rid: usize) -> Result<Vec<TypeReference>> {
+        let mut res: Vec<TypeReference> = vec![];
+        let mut pending = vec![type_rid];
+        pending.extend(self.base_types(type_rid)?.iter().filter_map(|b| b.type_def));
+        let mut visited = std::collections::HashSet::new();
+        while let Some(rid) = pending.pop() {
+            if !visited.insert(rid) {
+                continue;
+            }
+            for interface in self.interfaces(rid)? {
+                if let Some(def) = interface.type_def {
+                    pending.push(def);
+                }
+                if !res.contains(&interface) {
+                    res.push(interface);
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    pub fn derived_types(&self, type_rid: usize) -> Result<Vec<usize>> {
+        let mut res = vec![];
+        let table = self.md_table("TypeDef")?;
+        for rid in 1..=table.row_count() {
+            if self
+                .base_type(rid)?
+                .is_some_and(|b| b.type_def == Some(type_rid))
+            {
+                res.push(rid);
+            }
+        }
+        Ok(res)
+    }
+
+    pub fn method_overrides(&self, type_rid: usize) -> Result<Vec<MethodOverride>> {
+        let mut res = vec![];
+        if let Ok(table) = self.md_table("MethodImpl") {
+            for i in 0..table.row_count() {
+                let row = table.row::<MethodImpl>(i)?;
+                if row.class.row_index != type_rid {
+                    continue;
+                }
+                let body = index_token(row.method_body.table, row.method_body.row_index)?;
+                let declaration = index_token(
+                    row.method_declaration.table,
+                    row.method_declaration.row_index,
+                )?;
+                res.push(MethodOverride {
+                    body_name: self.member_name(&body)?,
+                    declaration_name: self.member_name(&declaration)?,
+                    body,
+                    declaration,
+                });
+            }
+        }
+        Ok(res)
+    }
+
+    pub fn types_deriving_from(&self, name: &str) -> Result<Vec<usize>> {
+        let mut res = vec![];
+        let table = self.md_table("TypeDef")?;
+        for rid in 1..=table.row_count() {
+            if self.base_types(rid)?.iter().any(|b| name_matches(b, name)) {
+                res.push(rid);
+            }
+        }
+        Ok(res)
+    }
+
+    pub fn types_implementing(&self, name: &str) -> Result<Vec<usize>> {
+        let mut res = vec![];
+        let table = self.md_table("TypeDef")?;
+        for rid in 1..=table.row_count() {
+            if self
+                .all_interfaces(rid)?
+                .iter()
+                .any(|i| name_matches(i, name))
+            {
+                res.push(rid);
+            }
+        }
+        Ok(res)
+    }
+}
@@ -4,7 +4,7 @@ use serde::Serialize;
 pub const TINY_SIZE: usize = 12;
 pub const FAT_SIZE: usize = 24;
 
-#[derive(Debug, Default, Clone, Serialize)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
 pub struct ExceptionHandler {
     pub exception_type: usize,
     pub try_start: i64,
@@ -2,8 +2,12 @@ use crate::{error::Error, Result};
 
 pub mod flags;
 pub mod reader;
+pub mod writer;
 
-use super::super::clr::token::Token;
+use super::super::clr::{
+    local::{Local, LocalVariable},
+    token::Token,
+};
 use super::enums::*;
 
 use serde::Serialize;
@@ -11,6 +15,10 @@ use serde::Serialize;
 #[derive(Debug, Clone, Serialize)]
 pub struct Function {
     pub offset: usize,
+    pub method_token: Token,
+    pub name: String,
+    pub declaring_type: Option<Token>,
+    pub type_name: String,
     header_size: usize,
     flags: flags::CilMethodBodyFlags,
     max_stack: usize,
@@ -21,12 +29,40 @@ pub struct Function {
     exception_handlers_size: usize,
     pub instructions: Vec<super::instruction::Instruction>,
     exception_handlers: Vec<super::exception::ExceptionHandler>,
+    pub locals: Vec<LocalVariable>,
+    /// Why the LocalVarSig could not be decoded, leaving `locals` empty.
+    pub locals_error: Option<String>,
+}
+
+impl PartialEq for Function {
+    fn eq(&self, other: &Self) -> bool {
+        let base = self.code_base() as i64;
+        let other_base = other.code_base() as i64;
+        self.max_stack == other.max_stack
+            && self.local_var_sig_tok == other.local_var_sig_tok
+            && self.flags.init_locals() == other.flags.init_locals()
+            && self.exception_handlers == other.exception_handlers
+            && self.instructions.len() == other.instructions.len()
+            && self
+                .instructions
+                .iter()
+                .zip(other.instructions.iter())
+                .all(|(a, b)| {
+                    a.offset as i64 - base == b.offset as i64 - other_base
+                        && a.opcode.value == b.opcode.value
+                        && a.rebased_operand(base) == b.rebased_operand(other_base)
+                })
+    }
 }
 
 impl Function {
     pub fn new(reader: &mut reader::Reader) -> Result<Self> {
         let mut res = Self {
             offset: reader.tell()?,
+            method_token: Token::new(0),
+            name: String::new(),
+            declaring_type: None,
+            type_name: String::new(),
             header_size: 0,
             flags: flags::CilMethodBodyFlags::new(0),
             max_stack: 0,
@@ -37,6 +73,8 @@ impl Function {
             exception_handlers_size: 0,
             instructions: vec![],
             exception_handlers: vec![],
+            locals: vec![],
+            locals_error: None,
         };
         res.parse_header(reader)?;
         res.parse_instructions(reader)?;
@@ -44,6 +82,70 @@ impl Function {
         Ok(res)
     }
 
+    pub fn method_rid(&self) -> usize {
+        self.method_token.rid()
+    }
+
+    pub fn full_name(&self) -> String {
+        if self.type_name.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}::{}", self.type_name, self.name)
+        }
+    }
+
+    pub fn local_var_sig_tok(&self) -> Option<&Token> {
+        self.local_var_sig_tok.as_ref()
+    }
+
+    pub fn local(&self, local: &Local) -> Option<&LocalVariable> {
+        self.locals.get(local.index())
+    }
+
+    pub fn header_size(&self) -> usize {
+        self.header_size
+    }
+
+    pub fn flags(&self) -> &flags::CilMethodBodyFlags {
+        &self.flags
+    }
+
+    pub fn max_stack(&self) -> usize {
+        self.max_stack
+    }
+
+    pub fn code_size(&self) -> usize {
+        self.code_size
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn code_base(&self) -> usize {
+        self.offset + self.header_size
+    }
+
+    pub fn exception_handlers(&self) -> &Vec<super::exception::ExceptionHandler> {
+        &self.exception_handlers
+    }
+
+    pub fn method_body(&self) -> writer::MethodBody {
+        writer::MethodBody {
+            code_base: self.code_base(),
+            instructions: self.instructions.clone(),
+            max_stack: Some(self.max_stack),
+            local_var_sig_tok: self.local_var_sig_tok.clone(),
+            init_locals: self.flags.init_locals(),
+            exception_handlers: self.exception_handlers.clone(),
+            short_branches: false,
+        }
+    }
+
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        self.method_body().encode()
+    }
+
     pub fn parse_header(&mut self, reader: &mut reader::Reader) -> Result<()> {
         let header_byte = reader.read_u8()? as usize;
         if [
@@ -122,8 +224,8 @@ impl Function {
     pub fn parse_fat_exception_handlers(&mut self, reader: &mut reader::Reader) -> Result<()> {
         let pos = reader.tell()? - 1;
         reader.seek(pos)?;
-        let total_size = reader.read_u32()? >> 8;
-        let num_exceptions = total_size; // ExceptionHandler.FAT_SIZE
+        let total_size = (reader.read_u32()? >> 8) as usize;
+        let num_exceptions = total_size.saturating_sub(4) / super::exception::FAT_SIZE;
         for _ in 0..num_exceptions {
             let mut eh = super::exception::ExceptionHandler::new(reader.read_u32()? as usize);
             eh.try_start = reader.read_i32()? as i64;
@@ -144,7 +246,8 @@ impl Function {
     }
 
     pub fn parse_tiny_exception_handlers(&mut self, reader: &mut reader::Reader) -> Result<()> {
-        let num_exceptions = reader.read_u8()? as usize;
+        let num_exceptions =
+            (reader.read_u8()? as usize).saturating_sub(4) / super::exception::TINY_SIZE;
         let pos = reader.tell()? + 2;
         reader.seek(pos)?;
         for _ in 0..num_exceptions {
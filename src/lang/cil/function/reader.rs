@@ -80,9 +80,9 @@ impl Reader {
     }
 
     pub fn read_inline_br_target(&mut self, insn: &Instruction) -> Result<Operand> {
-        let branch_offset = self.read_u32()? as usize;
+        let branch_offset = self.read_i32()? as i64;
         Ok(Operand::Int(
-            (insn.offset + insn.size() + branch_offset) as i64,
+            (insn.offset + insn.size()) as i64 + branch_offset,
         ))
     }
 
@@ -92,7 +92,7 @@ impl Reader {
     }
 
     pub fn read_inline_i(&mut self, _insn: &Instruction) -> Result<Operand> {
-        let v = self.read_u32()? as i64;
+        let v = self.read_i32()? as i64;
         Ok(Operand::Int(v))
     }
 
@@ -134,8 +134,8 @@ impl Reader {
         let offset_after_insn = insn.offset + insn.opcode.size() + 4 + num_branches * 4;
         let mut branches = vec![];
         for _ in 0..num_branches {
-            let branch_offset = self.read_u32()? as usize;
-            branches.push(Operand::Int((offset_after_insn + branch_offset) as i64));
+            let branch_offset = self.read_i32()? as i64;
+            branches.push(Operand::Int(offset_after_insn as i64 + branch_offset));
         }
         Ok(Operand::Arguments(branches))
     }
@@ -160,9 +160,9 @@ impl Reader {
     }
 
     pub fn read_short_inline_br_target(&mut self, insn: &Instruction) -> Result<Operand> {
-        let branch_offset = self.read_u8()? as usize;
+        let branch_offset = self.read_i8()? as i64;
         Ok(Operand::Int(
-            (insn.offset + insn.size() + branch_offset) as i64,
+            (insn.offset + insn.size()) as i64 + branch_offset,
         ))
     }
 
@@ -0,0 +1,544 @@
+use super::super::super::clr::token::Token;
+use super::super::exception::{ExceptionHandler, FAT_SIZE, TINY_SIZE};
+use super::super::instruction::{Instruction, Operand};
+use super::super::{enums::*, opcode::*};
+use crate::{error::Error, Result};
+use byteorder::WriteBytesExt;
+
+const BRANCH_FORMS: [(OpCodeValue, OpCodeValue); 14] = [
+    (OpCodeValue::Br_S, OpCodeValue::Br),
+    (OpCodeValue::Brfalse_S, OpCodeValue::Brfalse),
+    (OpCodeValue::Brtrue_S, OpCodeValue::Brtrue),
+    (OpCodeValue::Beq_S, OpCodeValue::Beq),
+    (OpCodeValue::Bge_S, OpCodeValue::Bge),
+    (OpCodeValue::Bgt_S, OpCodeValue::Bgt),
+    (OpCodeValue::Ble_S, OpCodeValue::Ble),
+    (OpCodeValue::Blt_S, OpCodeValue::Blt),
+    (OpCodeValue::Bne_Un_S, OpCodeValue::Bne_Un),
+    (OpCodeValue::Bge_Un_S, OpCodeValue::Bge_Un),
+    (OpCodeValue::Bgt_Un_S, OpCodeValue::Bgt_Un),
+    (OpCodeValue::Ble_Un_S, OpCodeValue::Ble_Un),
+    (OpCodeValue::Blt_Un_S, OpCodeValue::Blt_Un),
+    (OpCodeValue::Leave_S, OpCodeValue::Leave),
+];
+
+fn short_form(value: OpCodeValue) -> Option<OpCodeValue> {
+    BRANCH_FORMS
+        .iter()
+        .find(|(_, l)| *l == value)
+        .map(|(s, _)| *s)
+}
+
+fn long_form(value: OpCodeValue) -> Option<OpCodeValue> {
+    BRANCH_FORMS
+        .iter()
+        .find(|(s, _)| *s == value)
+        .map(|(_, l)| *l)
+}
+
+fn stack_pop(behaviour: &StackBehaviour) -> Option<usize> {
+    match behaviour {
+        StackBehaviour::Pop0 => Some(0),
+        StackBehaviour::Pop1 | StackBehaviour::Popi | StackBehaviour::Popref => Some(1),
+        StackBehaviour::Pop1_pop1
+        | StackBehaviour::Popi_pop1
+        | StackBehaviour::Popi_popi
+        | StackBehaviour::Popi_popi8
+        | StackBehaviour::Popi_popr4
+        | StackBehaviour::Popi_popr8
+        | StackBehaviour::Popref_pop1
+        | StackBehaviour::Popref_popi => Some(2),
+        StackBehaviour::Popi_popi_popi
+        | StackBehaviour::Popref_popi_popi
+        | StackBehaviour::Popref_popi_popi8
+        | StackBehaviour::Popref_popi_popr4
+        | StackBehaviour::Popref_popi_popr8
+        | StackBehaviour::Popref_popi_popref
+        | StackBehaviour::Popref_popi_pop1 => Some(3),
+        _ => None,
+    }
+}
+
+fn stack_push(behaviour: &StackBehaviour) -> Option<usize> {
+    match behaviour {
+        StackBehaviour::Push0 => Some(0),
+        StackBehaviour::Push1
+        | StackBehaviour::Pushi
+        | StackBehaviour::Pushi8
+        | StackBehaviour::Pushr4
+        | StackBehaviour::Pushr8
+        | StackBehaviour::Pushref => Some(1),
+        StackBehaviour::Push1_push1 => Some(2),
+        _ => None,
+    }
+}
+
+pub struct Writer {
+    stream: Vec<u8>,
+}
+
+impl Default for Writer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Writer {
+    pub fn new() -> Self {
+        Self { stream: vec![] }
+    }
+
+    pub fn tell(&self) -> usize {
+        self.stream.len()
+    }
+
+    pub fn bytes(self) -> Vec<u8> {
+        self.stream
+    }
+
+    pub fn align(&mut self, alignment: usize) {
+        while !self.stream.len().is_multiple_of(alignment) {
+            self.stream.push(0);
+        }
+    }
+
+    pub fn write_u8(&mut self, v: u8) -> Result<()> {
+        Ok(self.stream.write_u8(v)?)
+    }
+
+    pub fn write_i8(&mut self, v: i8) -> Result<()> {
+        Ok(self.stream.write_i8(v)?)
+    }
+
+    pub fn write_u16(&mut self, v: u16) -> Result<()> {
+        Ok(self.stream.write_u16::<byteorder::LittleEndian>(v)?)
+    }
+
+    pub fn write_u32(&mut self, v: u32) -> Result<()> {
+        Ok(self.stream.write_u32::<byteorder::LittleEndian>(v)?)
+    }
+
+    pub fn write_i32(&mut self, v: i32) -> Result<()> {
+        Ok(self.stream.write_i32::<byteorder::LittleEndian>(v)?)
+    }
+
+    pub fn write_i64(&mut self, v: i64) -> Result<()> {
+        Ok(self.stream.write_i64::<byteorder::LittleEndian>(v)?)
+    }
+
+    pub fn write_f32(&mut self, v: f32) -> Result<()> {
+        Ok(self.stream.write_f32::<byteorder::LittleEndian>(v)?)
+    }
+
+    pub fn write_f64(&mut self, v: f64) -> Result<()> {
+        Ok(self.stream.write_f64::<byteorder::LittleEndian>(v)?)
+    }
+
+    pub fn write_token(&mut self, token: &Token) -> Result<()> {
+        self.write_u32(token.value as u32)
+    }
+
+    pub fn write_opcode(&mut self, opcode: &OpCode) -> Result<()> {
+        if [OpCodeValue::UNKNOWN1, OpCodeValue::UNKNOWN2].contains(&opcode.value) {
+            return Err(Error::MethodBodyEncodeError(format!(
+                "can't encode opcode {}",
+                opcode.name
+            )));
+        }
+        if opcode.size() == 2 {
+            self.write_u8(0xFE)?;
+        }
+        self.write_u8((opcode.value as usize & 0xFF) as u8)
+    }
+
+    fn write_var(&mut self, insn: &Instruction, short: bool) -> Result<()> {
+        let index = match &insn.operand {
+            Operand::Local(l) => l.index(),
+            Operand::Argument(a) => a.index(),
+            Operand::Int(i) => *i as usize,
+            _ => return Err(Error::MethodBodyEncodeError(bad_operand(insn))),
+        };
+        let fits = if short {
+            u8::try_from(index).map(|i| self.write_u8(i))
+        } else {
+            u16::try_from(index).map(|i| self.write_u16(i))
+        };
+        fits.map_err(|_| {
+            Error::MethodBodyEncodeError(format!(
+                "{} index {} does not fit the operand",
+                insn.opcode.name, index
+            ))
+        })?
+    }
+
+    pub fn write_instruction(
+        &mut self,
+        insn: &Instruction,
+        offset: usize,
+        targets: &[usize],
+    ) -> Result<()> {
+        self.write_opcode(&insn.opcode)?;
+        let next = (offset + insn.size()) as i64;
+        match (&insn.opcode.operand_type, &insn.operand) {
+            (OperandType::InlineBrTarget, _) => self.write_i32((targets[0] as i64 - next) as i32),
+            (OperandType::ShortInlineBrTarget, _) => {
+                self.write_i8((targets[0] as i64 - next) as i8)
+            }
+            (OperandType::InlineSwitch, _) => {
+                self.write_u32(targets.len() as u32)?;
+                for t in targets {
+                    self.write_i32((*t as i64 - next) as i32)?;
+                }
+                Ok(())
+            }
+            (
+                OperandType::InlineField
+                | OperandType::InlineMethod
+                | OperandType::InlineSig
+                | OperandType::InlineTok
+                | OperandType::InlineType,
+                Operand::Token(t),
+            ) => self.write_token(t),
+            (OperandType::InlineString, Operand::StringToken(t) | Operand::Token(t)) => {
+                self.write_token(t)
+            }
+            (OperandType::InlineI, Operand::Int(i)) => self.write_i32(*i as i32),
+            (OperandType::InlineI8, Operand::Int(i)) => self.write_i64(*i),
+            (OperandType::InlineR, Operand::Float(f)) => self.write_f64(*f),
+            (OperandType::ShortInlineR, Operand::Float(f)) => self.write_f32(*f as f32),
+            (OperandType::ShortInlineI, Operand::Int(i)) => {
+                if insn.opcode.value == OpCodeValue::Ldc_I4_S {
+                    self.write_i8(*i as i8)
+                } else {
+                    self.write_u8(*i as u8)
+                }
+            }
+            (OperandType::InlineVar, _) => self.write_var(insn, false),
+            (OperandType::ShortInlineVar, _) => self.write_var(insn, true),
+            (OperandType::InlineNone | OperandType::InlinePhi, _) => Ok(()),
+            _ => Err(Error::MethodBodyEncodeError(bad_operand(insn))),
+        }
+    }
+}
+
+fn bad_operand(insn: &Instruction) -> String {
+    format!(
+        "bad operand {:?} for {} at {:#x}",
+        insn.operand, insn.opcode.name, insn.offset
+    )
+}
+
+fn branch_targets(insn: &Instruction) -> Vec<i64> {
+    match (&insn.opcode.operand_type, &insn.operand) {
+        (OperandType::InlineBrTarget | OperandType::ShortInlineBrTarget, Operand::Int(t)) => {
+            vec![*t]
+        }
+        (OperandType::InlineSwitch, Operand::Arguments(v)) => v
+            .iter()
+            .filter_map(|a| match a {
+                Operand::Int(t) => Some(*t),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MethodBody {
+    pub code_base: usize,
+    pub instructions: Vec<Instruction>,
+    pub max_stack: Option<usize>,
+    pub local_var_sig_tok: Option<Token>,
+    pub init_locals: bool,
+    pub exception_handlers: Vec<ExceptionHandler>,
+    pub short_branches: bool,
+}
+
+impl MethodBody {
+    pub fn new(instructions: Vec<Instruction>) -> Self {
+        Self {
+            code_base: instructions.first().map(|i| i.offset).unwrap_or_default(),
+            instructions,
+            ..Default::default()
+        }
+    }
+
+    fn labels(&self) -> std::collections::HashMap<i64, usize> {
+        let mut res = std::collections::HashMap::new();
+        for (i, insn) in self.instructions.iter().enumerate() {
+            res.entry(insn.offset as i64).or_insert(i);
+        }
+        let end = match self.instructions.last() {
+            Some(last) => last.offset + last.size(),
+            None => self.code_base,
+        };
+        res.entry(end as i64).or_insert(self.instructions.len());
+        res
+    }
+
+    fn label(labels: &std::collections::HashMap<i64, usize>, offset: i64) -> Result<usize> {
+        match labels.get(&offset) {
+            Some(s) => Ok(*s),
+            None => Err(Error::MethodBodyEncodeError(format!(
+                "no instruction at offset {:#x}",
+                offset
+            ))),
+        }
+    }
+
+    fn branch_labels(
+        &self,
+        labels: &std::collections::HashMap<i64, usize>,
+    ) -> Result<Vec<Vec<usize>>> {
+        let mut res = vec![];
+        for insn in &self.instructions {
+            let mut targets = vec![];
+            for t in branch_targets(insn) {
+                targets.push(Self::label(labels, t)?);
+            }
+            res.push(targets);
+        }
+        Ok(res)
+    }
+
+    fn offsets(instructions: &[Instruction]) -> Vec<usize> {
+        let mut res = Vec::with_capacity(instructions.len() + 1);
+        let mut offset = 0;
+        for insn in instructions {
+            res.push(offset);
+            offset += insn.size();
+        }
+        res.push(offset);
+        res
+    }
+
+    fn layout(&self, targets: &[Vec<usize>]) -> Vec<Instruction> {
+        let opcodes = OpCodes::new();
+        let mut res = self.instructions.clone();
+        if self.short_branches {
+            for insn in res.iter_mut() {
+                if let Some(s) = short_form(insn.opcode.value) {
+                    insn.opcode = opcodes.get(s).clone();
+                }
+            }
+        }
+        loop {
+            let offsets = Self::offsets(&res);
+            let mut changed = false;
+            for (i, insn) in res.iter_mut().enumerate() {
+                if insn.opcode.operand_type != OperandType::ShortInlineBrTarget {
+                    continue;
+                }
+                let delta = offsets[targets[i][0]] as i64 - (offsets[i] + insn.size()) as i64;
+                if delta < i8::MIN as i64 || delta > i8::MAX as i64 {
+                    if let Some(l) = long_form(insn.opcode.value) {
+                        insn.opcode = opcodes.get(l).clone();
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                return res;
+            }
+        }
+    }
+
+    pub fn compute_max_stack(
+        &self,
+        stack_effect: &dyn Fn(&Instruction) -> Option<(usize, usize)>,
+    ) -> Result<usize> {
+        let labels = self.labels();
+        let targets = self.branch_labels(&labels)?;
+        let mut depths: Vec<Option<usize>> = vec![None; self.instructions.len() + 1];
+        let mut pending = vec![0];
+        depths[0] = Some(0);
+        for eh in &self.exception_handlers {
+            let depth = if eh.is_finally() || eh.is_fault() {
+                0
+            } else {
+                1
+            };
+            let mut starts = vec![eh.handler_start];
+            if eh.is_filter() {
+                starts.push(eh.filter_start);
+            }
+            for s in starts {
+                let i = Self::label(&labels, self.code_base as i64 + s)?;
+                if depths[i].is_none() {
+                    depths[i] = Some(depth);
+                    pending.push(i);
+                }
+            }
+        }
+        let mut max_stack = 0;
+        while let Some(i) = pending.pop() {
+            let insn = match self.instructions.get(i) {
+                Some(s) => s,
+                None => continue,
+            };
+            let depth = depths[i].unwrap_or_default();
+            let (pop, push) = match (
+                stack_pop(&insn.opcode.stack_pop),
+                stack_push(&insn.opcode.stack_push),
+            ) {
+                (Some(pop), Some(push)) => (pop, push),
+                _ if matches!(insn.opcode.stack_pop, StackBehaviour::PopAll) => (depth, 0),
+                _ => match stack_effect(insn) {
+                    Some(s) => s,
+                    None => {
+                        return Err(Error::MethodBodyEncodeError(format!(
+                            "unknown stack effect of {} at {:#x}",
+                            insn.opcode.name, insn.offset
+                        )))
+                    }
+                },
+            };
+            let depth = depth.saturating_sub(pop) + push;
+            max_stack = std::cmp::max(max_stack, depth);
+            let mut successors = targets[i].clone();
+            match insn.opcode.flow_control {
+                FlowControl::Branch | FlowControl::Return | FlowControl::Throw => {}
+                _ => successors.push(i + 1),
+            }
+            for s in successors {
+                if depths[s].is_none() {
+                    depths[s] = Some(depth);
+                    pending.push(s);
+                }
+            }
+        }
+        Ok(max_stack)
+    }
+
+    /// Encodes the body on its own. Without `max_stack`, calls and `ret`
+    /// have no fixed stack effect and fail to encode; use `encode_with` or
+    /// `ClrData::encode_method_body` to resolve them from signatures.
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        self.encode_with(&|_| None)
+    }
+
+    pub fn encode_with(
+        &self,
+        stack_effect: &dyn Fn(&Instruction) -> Option<(usize, usize)>,
+    ) -> Result<Vec<u8>> {
+        let labels = self.labels();
+        let targets = self.branch_labels(&labels)?;
+        let instructions = self.layout(&targets);
+        let offsets = Self::offsets(&instructions);
+        let mut code = Writer::new();
+        for (i, insn) in instructions.iter().enumerate() {
+            let t: Vec<usize> = targets[i].iter().map(|t| offsets[*t]).collect();
+            code.write_instruction(insn, offsets[i], &t)?;
+        }
+        let code = code.bytes();
+        // a tiny header implies max_stack 8, so a declared value only fits
+        // one when it is exactly 8
+        let (max_stack, tiny_stack) = match self.max_stack {
+            Some(s) => (s, s == 8),
+            None => {
+                let s = self.compute_max_stack(stack_effect)?;
+                (s, s <= 8)
+            }
+        };
+        let max_stack = u16::try_from(max_stack).map_err(|_| {
+            Error::MethodBodyEncodeError(format!("max_stack {} does not fit u16", max_stack))
+        })?;
+
+        let mut res = Writer::new();
+        if code.len() < 64
+            && tiny_stack
+            && self.local_var_sig_tok.is_none()
+            && !self.init_locals
+            && self.exception_handlers.is_empty()
+        {
+            res.write_u8(((code.len() << 2) | CorILMethod::TinyFormat as usize) as u8)?;
+            res.stream.extend_from_slice(&code);
+            return Ok(res.bytes());
+        }
+        let mut flags = CorILMethod::FatFormat as u16 | (3 << 12);
+        if self.init_locals {
+            flags |= CorILMethod::InitLocals as u16;
+        }
+        if !self.exception_handlers.is_empty() {
+            flags |= CorILMethod::MoreSects as u16;
+        }
+        res.write_u16(flags)?;
+        res.write_u16(max_stack)?;
+        res.write_u32(code.len() as u32)?;
+        match &self.local_var_sig_tok {
+            Some(t) => res.write_token(t)?,
+            None => res.write_u32(0)?,
+        }
+        res.stream.extend_from_slice(&code);
+        if !self.exception_handlers.is_empty() {
+            res.align(4);
+            self.write_exception_handlers(&mut res, &labels, &offsets)?;
+        }
+        Ok(res.bytes())
+    }
+
+    fn write_exception_handlers(
+        &self,
+        writer: &mut Writer,
+        labels: &std::collections::HashMap<i64, usize>,
+        offsets: &[usize],
+    ) -> Result<()> {
+        let base = self.code_base as i64;
+        let mut clauses = vec![];
+        for eh in &self.exception_handlers {
+            let try_start = offsets[Self::label(labels, base + eh.try_start)?];
+            let try_end = offsets[Self::label(labels, base + eh.try_end)?];
+            let handler_start = offsets[Self::label(labels, base + eh.handler_start)?];
+            let handler_end = offsets[Self::label(labels, base + eh.handler_end)?];
+            let extra = if eh.is_catch() {
+                eh.catch_type.as_ref().map(|t| t.value).unwrap_or_default()
+            } else if eh.is_filter() {
+                offsets[Self::label(labels, base + eh.filter_start)?]
+            } else {
+                0
+            };
+            clauses.push((
+                eh.exception_type,
+                try_start,
+                try_end.saturating_sub(try_start),
+                handler_start,
+                handler_end.saturating_sub(handler_start),
+                extra,
+            ));
+        }
+        let tiny = clauses.len() * TINY_SIZE + 4 <= 0xFF
+            && clauses.iter().all(|c| {
+                c.0 <= 0xFFFF && c.1 <= 0xFFFF && c.2 <= 0xFF && c.3 <= 0xFFFF && c.4 <= 0xFF
+            });
+        if tiny {
+            writer.write_u8(CorILMethodSect::EHTable as u8)?;
+            writer.write_u8((clauses.len() * TINY_SIZE + 4) as u8)?;
+            writer.write_u16(0)?;
+            for c in clauses {
+                writer.write_u16(c.0 as u16)?;
+                writer.write_u16(c.1 as u16)?;
+                writer.write_u8(c.2 as u8)?;
+                writer.write_u16(c.3 as u16)?;
+                writer.write_u8(c.4 as u8)?;
+                writer.write_u32(c.5 as u32)?;
+            }
+        } else {
+            let size = clauses.len() * FAT_SIZE + 4;
+            writer.write_u32(
+                ((size as u32) << 8)
+                    | CorILMethodSect::EHTable as u32
+                    | CorILMethodSect::FatFormat as u32,
+            )?;
+            for c in clauses {
+                writer.write_u32(c.0 as u32)?;
+                writer.write_u32(c.1 as u32)?;
+                writer.write_u32(c.2 as u32)?;
+                writer.write_u32(c.3 as u32)?;
+                writer.write_u32(c.4 as u32)?;
+                writer.write_u32(c.5 as u32)?;
+            }
+        }
+        Ok(())
+    }
+}
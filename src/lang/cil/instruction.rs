@@ -30,6 +30,22 @@ impl Operand {
     }
 }
 
+impl PartialEq for Operand {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Token(a), Self::Token(b)) => a == b,
+            (Self::StringToken(a), Self::StringToken(b)) => a == b,
+            (Self::Local(a), Self::Local(b)) => a == b,
+            (Self::Argument(a), Self::Argument(b)) => a == b,
+            (Self::Arguments(a), Self::Arguments(b)) => a == b,
+            (Self::Int(a), Self::Int(b)) => a == b,
+            (Self::Float(a), Self::Float(b)) => a.to_bits() == b.to_bits(),
+            (Self::None, Self::None) => true,
+            _ => false,
+        }
+    }
+}
+
 impl TryInto<f64> for Operand {
     type Error = crate::error::Error;
     fn try_into(self) -> std::result::Result<f64, Self::Error> {
@@ -155,6 +171,30 @@ impl Instruction {
         }
     }
 
+    pub fn is_branch(&self) -> bool {
+        [
+            OperandType::InlineBrTarget,
+            OperandType::ShortInlineBrTarget,
+            OperandType::InlineSwitch,
+        ]
+        .contains(&self.opcode.operand_type)
+    }
+
+    pub fn rebased_operand(&self, base: i64) -> Operand {
+        match &self.operand {
+            Operand::Int(t) if self.is_branch() => Operand::Int(t - base),
+            Operand::Arguments(v) if self.is_branch() => Operand::Arguments(
+                v.iter()
+                    .map(|a| match a {
+                        Operand::Int(t) => Operand::Int(t - base),
+                        a => a.clone(),
+                    })
+                    .collect(),
+            ),
+            o => o.clone(),
+        }
+    }
+
     pub fn is_leave(&self) -> bool {
         [OpCodeValue::Leave, OpCodeValue::Leave_S].contains(&self.opcode.value)
     }
@@ -227,10 +267,7 @@ impl Instruction {
     }
 
     pub fn get_ldc(&self) -> Option<f64> {
-        let s = match self.operand.clone().try_into() {
-            Ok(s) => Some(s),
-            _ => None,
-        };
+        let s = self.operand.clone().try_into().ok();
         match self.opcode.value {
             OpCodeValue::Ldc_I4_M1 => Some(-1.0),
             OpCodeValue::Ldc_I4_0 => Some(0.0),
@@ -266,10 +303,7 @@ impl Instruction {
     }
 
     pub fn get_ldarg(&self) -> Option<Argument> {
-        let s = match self.operand.clone().try_into() {
-            Ok(s) => Some(s),
-            _ => None,
-        };
+        let s = self.operand.clone().try_into().ok();
         match &self.opcode.value {
             OpCodeValue::Ldarg
             | OpCodeValue::Ldarga
@@ -288,10 +322,7 @@ impl Instruction {
     }
 
     pub fn get_starg(&self) -> Option<Argument> {
-        let s = match self.operand.clone().try_into() {
-            Ok(s) => Some(s),
-            _ => None,
-        };
+        let s = self.operand.clone().try_into().ok();
         if [OpCodeValue::Starg, OpCodeValue::Starg_S].contains(&self.opcode.value) {
             s
         } else {
@@ -314,10 +345,7 @@ impl Instruction {
     }
 
     pub fn get_ldoc(&self) -> Option<Local> {
-        let s = match self.operand.clone().try_into() {
-            Ok(s) => Some(s),
-            _ => None,
-        };
+        let s = self.operand.clone().try_into().ok();
         match self.opcode.value {
             OpCodeValue::Ldloc
             | OpCodeValue::Ldloc_S
@@ -344,10 +372,7 @@ impl Instruction {
     }
 
     pub fn get_stloc(&self) -> Option<Local> {
-        let s = match self.operand.clone().try_into() {
-            Ok(s) => Some(s),
-            _ => None,
-        };
+        let s = self.operand.clone().try_into().ok();
         match self.opcode.value {
             OpCodeValue::Stloc | OpCodeValue::Stloc_S => s,
             OpCodeValue::Stloc_0 => Some(Local::new(0)),
@@ -2148,6 +2148,14 @@ impl OpCodes {
         res
     }
 
+    pub fn get(&self, value: OpCodeValue) -> &OpCode {
+        if (value as usize) >> 8 == 0xFE {
+            &self.two_byte_op_codes[value as usize & 0xFF]
+        } else {
+            &self.one_byte_op_codes[value as usize & 0xFF]
+        }
+    }
+
     fn add_op_code(&mut self, op_code: OpCode) {
         let val = op_code.value;
         if (val as usize) >> 8 == 0 {
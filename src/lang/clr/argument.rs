@@ -1,6 +1,7 @@
+use super::signature::SigType;
 use serde::Serialize;
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Argument {
     index: usize,
 }
@@ -9,4 +10,16 @@ impl Argument {
     pub fn new(index: usize) -> Self {
         Self { index }
     }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArgumentInfo {
+    pub index: usize,
+    pub name: Option<String>,
+    pub arg_type: SigType,
+    pub is_this: bool,
 }
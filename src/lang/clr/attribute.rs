@@ -0,0 +1,200 @@
+use super::{
+    signature::{SigType, SignatureReader},
+    token::Token,
+};
+use crate::{error::Error, Result};
+use serde::Serialize;
+
+const SERIALIZATION_TYPE_TYPE: u8 = 0x50;
+const SERIALIZATION_TYPE_TAGGED_OBJECT: u8 = 0x51;
+const SERIALIZATION_TYPE_FIELD: u8 = 0x53;
+const SERIALIZATION_TYPE_PROPERTY: u8 = 0x54;
+const SERIALIZATION_TYPE_ENUM: u8 = 0x55;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum AttributeValue {
+    Bool(bool),
+    Char(char),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    String(Option<String>),
+    Type(Option<String>),
+    Array(Option<Vec<AttributeValue>>),
+}
+
+impl AttributeValue {
+    pub fn strings(&self) -> Vec<&str> {
+        match self {
+            Self::String(Some(s)) | Self::Type(Some(s)) => vec![s.as_str()],
+            Self::Array(Some(v)) => v.iter().flat_map(|a| a.strings()).collect(),
+            _ => vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NamedArgument {
+    pub is_field: bool,
+    pub name: String,
+    pub value: AttributeValue,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CustomAttributeValue {
+    pub fixed_args: Vec<AttributeValue>,
+    pub named_args: Vec<NamedArgument>,
+}
+
+impl CustomAttributeValue {
+    pub fn strings(&self) -> Vec<&str> {
+        self.fixed_args
+            .iter()
+            .chain(self.named_args.iter().map(|n| &n.value))
+            .flat_map(|a| a.strings())
+            .collect()
+    }
+}
+
+pub(crate) fn read_ser_string(reader: &mut SignatureReader) -> Result<Option<String>> {
+    if reader.peek_u8()? == 0xff {
+        reader.read_u8()?;
+        return Ok(None);
+    }
+    let len = reader.read_compressed_usize()?;
+    Ok(Some(String::from_utf8(reader.read_bytes(len)?.to_vec())?))
+}
+
+fn read_field_or_prop_type(reader: &mut SignatureReader) -> Result<SigType> {
+    let element_type = reader.read_u8()?;
+    Ok(match element_type {
+        SERIALIZATION_TYPE_TYPE => SigType::Class(Token::new(0)),
+        SERIALIZATION_TYPE_TAGGED_OBJECT => SigType::Object,
+        SERIALIZATION_TYPE_ENUM => {
+            read_ser_string(reader)?;
+            SigType::I4
+        }
+        super::signature::ELEMENT_TYPE_SZARRAY => {
+            SigType::SzArray(Box::new(read_field_or_prop_type(reader)?))
+        }
+        0x02..=0x0e => SignatureReader::new(&[element_type]).read_type()?,
+        _ => {
+            return Err(Error::SignatureFormatError(format!(
+                "unknown custom attribute element type {:02x}",
+                element_type
+            )))
+        }
+    })
+}
+
+fn read_int(reader: &mut SignatureReader, len: usize) -> Result<i64> {
+    let mut buf = [0u8; 8];
+    buf[..len].copy_from_slice(reader.read_bytes(len)?);
+    let shift = 64 - 8 * len as u32;
+    Ok(i64::from_le_bytes(buf) << shift >> shift)
+}
+
+fn read_value(
+    reader: &mut SignatureReader,
+    value_type: &SigType,
+    enum_type: &dyn Fn(&Token) -> SigType,
+) -> Result<AttributeValue> {
+    Ok(match value_type {
+        SigType::Boolean => AttributeValue::Bool(read_int(reader, 1)? != 0),
+        SigType::Char => AttributeValue::Char(
+            char::from_u32(read_int(reader, 2)? as u32 & 0xffff)
+                .unwrap_or(char::REPLACEMENT_CHARACTER),
+        ),
+        SigType::I1 => AttributeValue::Int(read_int(reader, 1)?),
+        SigType::I2 => AttributeValue::Int(read_int(reader, 2)?),
+        SigType::I4 => AttributeValue::Int(read_int(reader, 4)?),
+        SigType::I8 => AttributeValue::Int(read_int(reader, 8)?),
+        SigType::U1 => AttributeValue::UInt(read_int(reader, 1)? as u64 & 0xff),
+        SigType::U2 => AttributeValue::UInt(read_int(reader, 2)? as u64 & 0xffff),
+        SigType::U4 => AttributeValue::UInt(read_int(reader, 4)? as u64 & 0xffff_ffff),
+        SigType::U8 => AttributeValue::UInt(read_int(reader, 8)? as u64),
+        SigType::R4 => AttributeValue::Float(f32::from_bits(read_int(reader, 4)? as u32) as f64),
+        SigType::R8 => AttributeValue::Float(f64::from_bits(read_int(reader, 8)? as u64)),
+        SigType::String => AttributeValue::String(read_ser_string(reader)?),
+        SigType::Class(_) => AttributeValue::Type(read_ser_string(reader)?),
+        SigType::ValueType(token) => read_value(reader, &enum_type(token), enum_type)?,
+        SigType::Object => {
+            let boxed = read_field_or_prop_type(reader)?;
+            read_value(reader, &boxed, enum_type)?
+        }
+        SigType::SzArray(element) => {
+            let count = u32::from_le_bytes(reader.read_bytes(4)?.try_into()?);
+            if count == u32::MAX {
+                AttributeValue::Array(None)
+            } else {
+                let mut values = vec![];
+                for _ in 0..count {
+                    values.push(read_value(reader, element, enum_type)?);
+                }
+                AttributeValue::Array(Some(values))
+            }
+        }
+        t => {
+            return Err(Error::SignatureFormatError(format!(
+                "unsupported custom attribute argument type {:?}",
+                t
+            )))
+        }
+    })
+}
+
+pub(crate) fn read_named_args(
+    reader: &mut SignatureReader,
+    count: usize,
+    enum_type: &dyn Fn(&Token) -> SigType,
+) -> Result<Vec<NamedArgument>> {
+    let mut res = vec![];
+    for _ in 0..count {
+        let is_field = match reader.read_u8()? {
+            SERIALIZATION_TYPE_FIELD => true,
+            SERIALIZATION_TYPE_PROPERTY => false,
+            b => {
+                return Err(Error::SignatureFormatError(format!(
+                    "bad named argument kind {:02x}",
+                    b
+                )))
+            }
+        };
+        let value_type = read_field_or_prop_type(reader)?;
+        let name = read_ser_string(reader)?.unwrap_or_default();
+        res.push(NamedArgument {
+            is_field,
+            name,
+            value: read_value(reader, &value_type, enum_type)?,
+        });
+    }
+    Ok(res)
+}
+
+pub fn parse_custom_attribute(
+    data: &[u8],
+    params: &[SigType],
+    enum_type: &dyn Fn(&Token) -> SigType,
+) -> Result<CustomAttributeValue> {
+    let mut reader = SignatureReader::new(data);
+    let prolog = reader.read_bytes(2)?;
+    if prolog != [1, 0] {
+        return Err(Error::SignatureFormatError(format!(
+            "bad custom attribute prolog {:02x?}",
+            prolog
+        )));
+    }
+    let mut fixed_args = vec![];
+    for param in params {
+        fixed_args.push(read_value(&mut reader, param, enum_type)?);
+    }
+    let mut named_args = vec![];
+    if !reader.is_empty() {
+        let count = u16::from_le_bytes(reader.read_bytes(2)?.try_into()?);
+        named_args = read_named_args(&mut reader, count as usize, enum_type)?;
+    }
+    Ok(CustomAttributeValue {
+        fixed_args,
+        named_args,
+    })
+}
@@ -1,6 +1,7 @@
+use super::signature::SigType;
 use serde::Serialize;
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Local {
     index: usize,
 }
@@ -9,4 +10,27 @@ impl Local {
     pub fn new(index: usize) -> Self {
         Self { index }
     }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LocalVariable {
+    pub index: usize,
+    pub local_type: SigType,
+    pub pinned: bool,
+    pub by_ref: bool,
+}
+
+impl LocalVariable {
+    pub fn new(index: usize, local_type: SigType) -> Self {
+        Self {
+            index,
+            pinned: local_type.is_pinned(),
+            by_ref: local_type.is_by_ref(),
+            local_type,
+        }
+    }
 }
@@ -0,0 +1,289 @@
+use super::{attribute::read_ser_string, signature::SignatureReader};
+use crate::Result;
+use serde::Serialize;
+
+pub const NATIVE_TYPE_VOID: u8 = 0x01;
+pub const NATIVE_TYPE_BOOLEAN: u8 = 0x02;
+pub const NATIVE_TYPE_I1: u8 = 0x03;
+pub const NATIVE_TYPE_U1: u8 = 0x04;
+pub const NATIVE_TYPE_I2: u8 = 0x05;
+pub const NATIVE_TYPE_U2: u8 = 0x06;
+pub const NATIVE_TYPE_I4: u8 = 0x07;
+pub const NATIVE_TYPE_U4: u8 = 0x08;
+pub const NATIVE_TYPE_I8: u8 = 0x09;
+pub const NATIVE_TYPE_U8: u8 = 0x0a;
+pub const NATIVE_TYPE_R4: u8 = 0x0b;
+pub const NATIVE_TYPE_R8: u8 = 0x0c;
+pub const NATIVE_TYPE_SYSCHAR: u8 = 0x0d;
+pub const NATIVE_TYPE_VARIANT: u8 = 0x0e;
+pub const NATIVE_TYPE_CURRENCY: u8 = 0x0f;
+pub const NATIVE_TYPE_PTR: u8 = 0x10;
+pub const NATIVE_TYPE_DECIMAL: u8 = 0x11;
+pub const NATIVE_TYPE_DATE: u8 = 0x12;
+pub const NATIVE_TYPE_BSTR: u8 = 0x13;
+pub const NATIVE_TYPE_LPSTR: u8 = 0x14;
+pub const NATIVE_TYPE_LPWSTR: u8 = 0x15;
+pub const NATIVE_TYPE_LPTSTR: u8 = 0x16;
+pub const NATIVE_TYPE_FIXEDSYSSTRING: u8 = 0x17;
+pub const NATIVE_TYPE_OBJECTREF: u8 = 0x18;
+pub const NATIVE_TYPE_IUNKNOWN: u8 = 0x19;
+pub const NATIVE_TYPE_IDISPATCH: u8 = 0x1a;
+pub const NATIVE_TYPE_STRUCT: u8 = 0x1b;
+pub const NATIVE_TYPE_INTF: u8 = 0x1c;
+pub const NATIVE_TYPE_SAFEARRAY: u8 = 0x1d;
+pub const NATIVE_TYPE_FIXEDARRAY: u8 = 0x1e;
+pub const NATIVE_TYPE_INT: u8 = 0x1f;
+pub const NATIVE_TYPE_UINT: u8 = 0x20;
+pub const NATIVE_TYPE_NESTEDSTRUCT: u8 = 0x21;
+pub const NATIVE_TYPE_BYVALSTR: u8 = 0x22;
+pub const NATIVE_TYPE_ANSIBSTR: u8 = 0x23;
+pub const NATIVE_TYPE_TBSTR: u8 = 0x24;
+pub const NATIVE_TYPE_VARIANTBOOL: u8 = 0x25;
+pub const NATIVE_TYPE_FUNC: u8 = 0x26;
+pub const NATIVE_TYPE_ASANY: u8 = 0x28;
+pub const NATIVE_TYPE_ARRAY: u8 = 0x2a;
+pub const NATIVE_TYPE_LPSTRUCT: u8 = 0x2b;
+pub const NATIVE_TYPE_CUSTOMMARSHALER: u8 = 0x2c;
+pub const NATIVE_TYPE_ERROR: u8 = 0x2d;
+pub const NATIVE_TYPE_IINSPECTABLE: u8 = 0x2e;
+pub const NATIVE_TYPE_HSTRING: u8 = 0x2f;
+pub const NATIVE_TYPE_LPUTF8STR: u8 = 0x30;
+pub const NATIVE_TYPE_MAX: u8 = 0x50;
+
+const ARRAY_SIZE_PARAM_INDEX_SPECIFIED: usize = 0x0001;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum NativeType {
+    Void,
+    Boolean,
+    I1,
+    U1,
+    I2,
+    U2,
+    I4,
+    U4,
+    I8,
+    U8,
+    R4,
+    R8,
+    SysChar,
+    Variant,
+    Currency,
+    Ptr,
+    Decimal,
+    Date,
+    BStr,
+    LPStr,
+    LPWStr,
+    LPTStr,
+    ByValTStr(usize),
+    ObjectRef,
+    IUnknown(Option<usize>),
+    IDispatch(Option<usize>),
+    IInspectable(Option<usize>),
+    Interface(Option<usize>),
+    Struct,
+    SafeArray {
+        variant_type: Option<usize>,
+        user_type: Option<String>,
+    },
+    ByValArray {
+        size: usize,
+        element: Option<Box<NativeType>>,
+    },
+    SysInt,
+    SysUInt,
+    NestedStruct,
+    VBByRefStr,
+    AnsiBStr,
+    TBStr,
+    VariantBool,
+    FunctionPtr,
+    AsAny,
+    Array {
+        element: Option<Box<NativeType>>,
+        size_param_index: Option<usize>,
+        size: Option<usize>,
+    },
+    LPStruct,
+    CustomMarshaler {
+        guid: String,
+        native_type_name: String,
+        marshaler: String,
+        cookie: String,
+    },
+    Error,
+    HString,
+    LPUTF8Str,
+    Unknown(u8),
+}
+
+impl NativeType {
+    pub fn name(&self) -> String {
+        let iid = |i: &Option<usize>| i.iter().map(|i| format!("iid_is={}", i)).collect();
+        match self {
+            Self::ByValTStr(size) => format!("ByValTStr[{}]", size),
+            Self::IUnknown(i) => with_args("IUnknown", iid(i)),
+            Self::IDispatch(i) => with_args("IDispatch", iid(i)),
+            Self::IInspectable(i) => with_args("IInspectable", iid(i)),
+            Self::Interface(i) => with_args("Interface", iid(i)),
+            Self::SafeArray {
+                variant_type,
+                user_type,
+            } => with_args(
+                "SafeArray",
+                variant_type
+                    .map(|v| format!("vt={}", v))
+                    .into_iter()
+                    .chain(user_type.as_ref().map(|t| format!("type={}", t)))
+                    .collect(),
+            ),
+            Self::ByValArray { size, element } => format!(
+                "ByValArray[{}]{}",
+                size,
+                element
+                    .as_ref()
+                    .map(|e| format!(" of {}", e.name()))
+                    .unwrap_or_default()
+            ),
+            Self::Array {
+                element,
+                size_param_index,
+                size,
+            } => format!(
+                "Array[{}]{}",
+                size.filter(|s| *s > 0 || size_param_index.is_none())
+                    .map(|s| s.to_string())
+                    .into_iter()
+                    .chain(size_param_index.map(|p| format!("param {}", p)))
+                    .collect::<Vec<_>>()
+                    .join(" + "),
+                element
+                    .as_ref()
+                    .map(|e| format!(" of {}", e.name()))
+                    .unwrap_or_default()
+            ),
+            Self::CustomMarshaler {
+                marshaler, cookie, ..
+            } => with_args(
+                "CustomMarshaler",
+                std::iter::once(marshaler.clone())
+                    .chain((!cookie.is_empty()).then(|| format!("cookie={:?}", cookie)))
+                    .collect(),
+            ),
+            Self::Unknown(b) => format!("NativeType(0x{:02x})", b),
+            _ => format!("{:?}", self),
+        }
+    }
+}
+
+fn with_args(name: &str, args: Vec<String>) -> String {
+    if args.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}({})", name, args.join(", "))
+    }
+}
+
+fn read_optional(reader: &mut SignatureReader) -> Result<Option<usize>> {
+    if reader.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(reader.read_compressed_usize()?))
+}
+
+fn read_element(reader: &mut SignatureReader) -> Result<Option<Box<NativeType>>> {
+    if reader.is_empty() || reader.peek_u8()? == NATIVE_TYPE_MAX {
+        if !reader.is_empty() {
+            reader.read_u8()?;
+        }
+        return Ok(None);
+    }
+    Ok(Some(Box::new(reader.nested(read_native_type)?)))
+}
+
+fn read_native_type(reader: &mut SignatureReader) -> Result<NativeType> {
+    let native_type = reader.read_u8()?;
+    Ok(match native_type {
+        NATIVE_TYPE_VOID => NativeType::Void,
+        NATIVE_TYPE_BOOLEAN => NativeType::Boolean,
+        NATIVE_TYPE_I1 => NativeType::I1,
+        NATIVE_TYPE_U1 => NativeType::U1,
+        NATIVE_TYPE_I2 => NativeType::I2,
+        NATIVE_TYPE_U2 => NativeType::U2,
+        NATIVE_TYPE_I4 => NativeType::I4,
+        NATIVE_TYPE_U4 => NativeType::U4,
+        NATIVE_TYPE_I8 => NativeType::I8,
+        NATIVE_TYPE_U8 => NativeType::U8,
+        NATIVE_TYPE_R4 => NativeType::R4,
+        NATIVE_TYPE_R8 => NativeType::R8,
+        NATIVE_TYPE_SYSCHAR => NativeType::SysChar,
+        NATIVE_TYPE_VARIANT => NativeType::Variant,
+        NATIVE_TYPE_CURRENCY => NativeType::Currency,
+        NATIVE_TYPE_PTR => NativeType::Ptr,
+        NATIVE_TYPE_DECIMAL => NativeType::Decimal,
+        NATIVE_TYPE_DATE => NativeType::Date,
+        NATIVE_TYPE_BSTR => NativeType::BStr,
+        NATIVE_TYPE_LPSTR => NativeType::LPStr,
+        NATIVE_TYPE_LPWSTR => NativeType::LPWStr,
+        NATIVE_TYPE_LPTSTR => NativeType::LPTStr,
+        NATIVE_TYPE_FIXEDSYSSTRING => NativeType::ByValTStr(reader.read_compressed_usize()?),
+        NATIVE_TYPE_OBJECTREF => NativeType::ObjectRef,
+        NATIVE_TYPE_IUNKNOWN => NativeType::IUnknown(read_optional(reader)?),
+        NATIVE_TYPE_IDISPATCH => NativeType::IDispatch(read_optional(reader)?),
+        NATIVE_TYPE_IINSPECTABLE => NativeType::IInspectable(read_optional(reader)?),
+        NATIVE_TYPE_INTF => NativeType::Interface(read_optional(reader)?),
+        NATIVE_TYPE_STRUCT => NativeType::Struct,
+        NATIVE_TYPE_SAFEARRAY => NativeType::SafeArray {
+            variant_type: read_optional(reader)?,
+            user_type: if reader.is_empty() {
+                None
+            } else {
+                read_ser_string(reader)?
+            },
+        },
+        NATIVE_TYPE_FIXEDARRAY => NativeType::ByValArray {
+            size: read_optional(reader)?.unwrap_or(1),
+            element: read_element(reader)?,
+        },
+        NATIVE_TYPE_INT => NativeType::SysInt,
+        NATIVE_TYPE_UINT => NativeType::SysUInt,
+        NATIVE_TYPE_NESTEDSTRUCT => NativeType::NestedStruct,
+        NATIVE_TYPE_BYVALSTR => NativeType::VBByRefStr,
+        NATIVE_TYPE_ANSIBSTR => NativeType::AnsiBStr,
+        NATIVE_TYPE_TBSTR => NativeType::TBStr,
+        NATIVE_TYPE_VARIANTBOOL => NativeType::VariantBool,
+        NATIVE_TYPE_FUNC => NativeType::FunctionPtr,
+        NATIVE_TYPE_ASANY => NativeType::AsAny,
+        NATIVE_TYPE_ARRAY => {
+            let element = read_element(reader)?;
+            let mut size_param_index = read_optional(reader)?;
+            let size = read_optional(reader)?;
+            if let Some(flags) = read_optional(reader)? {
+                if flags & ARRAY_SIZE_PARAM_INDEX_SPECIFIED == 0 {
+                    size_param_index = None;
+                }
+            }
+            NativeType::Array {
+                element,
+                size_param_index,
+                size,
+            }
+        }
+        NATIVE_TYPE_LPSTRUCT => NativeType::LPStruct,
+        NATIVE_TYPE_CUSTOMMARSHALER => NativeType::CustomMarshaler {
+            guid: read_ser_string(reader)?.unwrap_or_default(),
+            native_type_name: read_ser_string(reader)?.unwrap_or_default(),
+            marshaler: read_ser_string(reader)?.unwrap_or_default(),
+            cookie: read_ser_string(reader)?.unwrap_or_default(),
+        },
+        NATIVE_TYPE_ERROR => NativeType::Error,
+        NATIVE_TYPE_HSTRING => NativeType::HString,
+        NATIVE_TYPE_LPUTF8STR => NativeType::LPUTF8Str,
+        b => NativeType::Unknown(b),
+    })
+}
+
+pub fn parse_marshal_spec(data: &[u8]) -> Result<NativeType> {
+    read_native_type(&mut SignatureReader::new(data))
+}
@@ -1,3 +1,7 @@
 pub mod argument;
+pub mod attribute;
 pub mod local;
+pub mod marshal;
+pub mod security;
+pub mod signature;
 pub mod token;
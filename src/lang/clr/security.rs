@@ -0,0 +1,58 @@
+use super::{
+    attribute::{read_named_args, read_ser_string, NamedArgument},
+    signature::{SigType, SignatureReader},
+};
+use crate::{error::Error, Result};
+use serde::Serialize;
+
+const PERMISSION_SET_BINARY: u8 = b'.';
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SecurityAttribute {
+    pub type_name: String,
+    pub named_args: Vec<NamedArgument>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum PermissionSet {
+    Binary(Vec<SecurityAttribute>),
+    Xml(String),
+}
+
+fn parse_binary(data: &[u8]) -> Result<Vec<SecurityAttribute>> {
+    let mut reader = SignatureReader::new(data);
+    reader.read_u8()?;
+    let mut res = vec![];
+    for _ in 0..reader.read_compressed_usize()? {
+        let type_name = read_ser_string(&mut reader)?.unwrap_or_default();
+        let size = reader.read_compressed_usize()?;
+        let mut properties = SignatureReader::new(reader.read_bytes(size)?);
+        let count = properties.read_compressed_usize()?;
+        res.push(SecurityAttribute {
+            type_name,
+            named_args: read_named_args(&mut properties, count, &|_| SigType::I4)?,
+        });
+    }
+    Ok(res)
+}
+
+pub fn parse_permission_set(data: &[u8]) -> Result<PermissionSet> {
+    match data.first() {
+        Some(&PERMISSION_SET_BINARY) => Ok(PermissionSet::Binary(parse_binary(data)?)),
+        Some(_) if data.len().is_multiple_of(2) => {
+            let units: Vec<u16> = data
+                .chunks_exact(2)
+                .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                .collect();
+            Ok(PermissionSet::Xml(
+                String::from_utf16_lossy(&units)
+                    .trim_end_matches('\0')
+                    .to_string(),
+            ))
+        }
+        _ => Err(Error::SignatureFormatError(format!(
+            "bad permission set of {} bytes",
+            data.len()
+        ))),
+    }
+}
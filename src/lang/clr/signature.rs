@@ -0,0 +1,466 @@
+use super::token::{Token, TABLE_SHIFT};
+use crate::{error::Error, Result};
+use serde::Serialize;
+
+pub const ELEMENT_TYPE_END: u8 = 0x00;
+pub const ELEMENT_TYPE_VOID: u8 = 0x01;
+pub const ELEMENT_TYPE_BOOLEAN: u8 = 0x02;
+pub const ELEMENT_TYPE_CHAR: u8 = 0x03;
+pub const ELEMENT_TYPE_I1: u8 = 0x04;
+pub const ELEMENT_TYPE_U1: u8 = 0x05;
+pub const ELEMENT_TYPE_I2: u8 = 0x06;
+pub const ELEMENT_TYPE_U2: u8 = 0x07;
+pub const ELEMENT_TYPE_I4: u8 = 0x08;
+pub const ELEMENT_TYPE_U4: u8 = 0x09;
+pub const ELEMENT_TYPE_I8: u8 = 0x0a;
+pub const ELEMENT_TYPE_U8: u8 = 0x0b;
+pub const ELEMENT_TYPE_R4: u8 = 0x0c;
+pub const ELEMENT_TYPE_R8: u8 = 0x0d;
+pub const ELEMENT_TYPE_STRING: u8 = 0x0e;
+pub const ELEMENT_TYPE_PTR: u8 = 0x0f;
+pub const ELEMENT_TYPE_BYREF: u8 = 0x10;
+pub const ELEMENT_TYPE_VALUETYPE: u8 = 0x11;
+pub const ELEMENT_TYPE_CLASS: u8 = 0x12;
+pub const ELEMENT_TYPE_VAR: u8 = 0x13;
+pub const ELEMENT_TYPE_ARRAY: u8 = 0x14;
+pub const ELEMENT_TYPE_GENERICINST: u8 = 0x15;
+pub const ELEMENT_TYPE_TYPEDBYREF: u8 = 0x16;
+pub const ELEMENT_TYPE_I: u8 = 0x18;
+pub const ELEMENT_TYPE_U: u8 = 0x19;
+pub const ELEMENT_TYPE_FNPTR: u8 = 0x1b;
+pub const ELEMENT_TYPE_OBJECT: u8 = 0x1c;
+pub const ELEMENT_TYPE_SZARRAY: u8 = 0x1d;
+pub const ELEMENT_TYPE_MVAR: u8 = 0x1e;
+pub const ELEMENT_TYPE_CMOD_REQD: u8 = 0x1f;
+pub const ELEMENT_TYPE_CMOD_OPT: u8 = 0x20;
+pub const ELEMENT_TYPE_INTERNAL: u8 = 0x21;
+pub const ELEMENT_TYPE_SENTINEL: u8 = 0x41;
+pub const ELEMENT_TYPE_PINNED: u8 = 0x45;
+
+pub const SIG_DEFAULT: u8 = 0x00;
+pub const SIG_VARARG: u8 = 0x05;
+pub const SIG_FIELD: u8 = 0x06;
+pub const SIG_LOCAL_SIG: u8 = 0x07;
+pub const SIG_PROPERTY: u8 = 0x08;
+pub const SIG_GENERIC_INST: u8 = 0x0a;
+pub const SIG_CALLCONV_MASK: u8 = 0x0f;
+pub const SIG_GENERIC: u8 = 0x10;
+pub const SIG_HAS_THIS: u8 = 0x20;
+pub const SIG_EXPLICIT_THIS: u8 = 0x40;
+
+/// Deepest type nesting accepted before a signature is rejected as malformed.
+pub const MAX_SIGNATURE_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ArrayShape {
+    pub rank: usize,
+    pub sizes: Vec<usize>,
+    pub lo_bounds: Vec<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum SigType {
+    Void,
+    Boolean,
+    Char,
+    I1,
+    U1,
+    I2,
+    U2,
+    I4,
+    U4,
+    I8,
+    U8,
+    R4,
+    R8,
+    String,
+    Object,
+    TypedByRef,
+    I,
+    U,
+    Ptr(Box<SigType>),
+    ByRef(Box<SigType>),
+    ValueType(Token),
+    Class(Token),
+    Var(usize),
+    MVar(usize),
+    Array(Box<SigType>, ArrayShape),
+    SzArray(Box<SigType>),
+    GenericInst(Box<SigType>, Vec<SigType>),
+    FnPtr(Box<MethodSig>),
+    Pinned(Box<SigType>),
+    CModReqd(Token, Box<SigType>),
+    CModOpt(Token, Box<SigType>),
+    Sentinel,
+}
+
+impl SigType {
+    pub fn base(&self) -> &SigType {
+        match self {
+            Self::Pinned(t) | Self::ByRef(t) | Self::CModReqd(_, t) | Self::CModOpt(_, t) => {
+                t.base()
+            }
+            _ => self,
+        }
+    }
+
+    pub fn is_pinned(&self) -> bool {
+        match self {
+            Self::Pinned(_) => true,
+            Self::ByRef(t) | Self::CModReqd(_, t) | Self::CModOpt(_, t) => t.is_pinned(),
+            _ => false,
+        }
+    }
+
+    pub fn is_by_ref(&self) -> bool {
+        match self {
+            Self::ByRef(_) => true,
+            Self::Pinned(t) | Self::CModReqd(_, t) | Self::CModOpt(_, t) => t.is_by_ref(),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MethodSig {
+    pub calling_convention: u8,
+    pub has_this: bool,
+    pub explicit_this: bool,
+    pub generic_param_count: usize,
+    pub ret_type: SigType,
+    pub params: Vec<SigType>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldSig {
+    pub field_type: SigType,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PropertySig {
+    pub has_this: bool,
+    pub property_type: SigType,
+    pub params: Vec<SigType>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LocalVarSig {
+    pub locals: Vec<SigType>,
+}
+
+pub struct SignatureReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> SignatureReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            depth: 0,
+        }
+    }
+
+    pub fn tell(&self) -> usize {
+        self.pos
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8> {
+        match self.data.get(self.pos) {
+            Some(b) => {
+                self.pos += 1;
+                Ok(*b)
+            }
+            None => Err(Error::SignatureFormatError(format!(
+                "unexpected end of signature at {}",
+                self.pos
+            ))),
+        }
+    }
+
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        match self
+            .pos
+            .checked_add(len)
+            .and_then(|end| self.data.get(self.pos..end))
+        {
+            Some(b) => {
+                self.pos += len;
+                Ok(b)
+            }
+            None => Err(Error::SignatureFormatError(format!(
+                "unexpected end of signature at {}",
+                self.pos
+            ))),
+        }
+    }
+
+    pub fn peek_u8(&self) -> Result<u8> {
+        self.data.get(self.pos).copied().ok_or_else(|| {
+            Error::SignatureFormatError(format!("unexpected end of signature at {}", self.pos))
+        })
+    }
+
+    pub fn read_compressed_usize(&mut self) -> Result<usize> {
+        let rest = self.data.get(self.pos..).unwrap_or_default();
+        if rest.is_empty() {
+            return Err(Error::SignatureFormatError(format!(
+                "unexpected end of signature at {}",
+                self.pos
+            )));
+        }
+        let len = match rest[0] {
+            b if b & 0x80 == 0 => 1,
+            b if b & 0x40 == 0 => 2,
+            _ => 4,
+        };
+        if rest.len() < len {
+            return Err(Error::SignatureFormatError(format!(
+                "truncated compressed integer at {}",
+                self.pos
+            )));
+        }
+        let (value, size) = crate::utils::read_compressed_usize(rest)?;
+        self.pos += size;
+        Ok(value)
+    }
+
+    pub fn read_compressed_i64(&mut self) -> Result<i64> {
+        let start = self.pos;
+        let raw = self.read_compressed_usize()? as i64;
+        let bits = match self.pos - start {
+            1 => 7,
+            2 => 14,
+            _ => 29,
+        };
+        let value = raw >> 1;
+        if raw & 1 == 0 {
+            Ok(value)
+        } else {
+            Ok(value - (1 << (bits - 1)))
+        }
+    }
+
+    pub fn read_type_def_or_ref(&mut self) -> Result<Token> {
+        let value = self.read_compressed_usize()?;
+        let table = match value & 3 {
+            0 => 0x02,
+            1 => 0x01,
+            2 => 0x1b,
+            _ => {
+                return Err(Error::SignatureFormatError(format!(
+                    "bad TypeDefOrRef tag in {:x}",
+                    value
+                )))
+            }
+        };
+        Ok(Token::new((table << TABLE_SHIFT) | (value >> 2)))
+    }
+
+    /// Runs `read` one nesting level deeper, failing once the signature nests
+    /// more than [`MAX_SIGNATURE_DEPTH`] types.
+    pub fn nested<T>(&mut self, read: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        if self.depth >= MAX_SIGNATURE_DEPTH {
+            return Err(Error::SignatureFormatError(format!(
+                "type nested deeper than {} at {}",
+                MAX_SIGNATURE_DEPTH, self.pos
+            )));
+        }
+        self.depth += 1;
+        let res = read(self);
+        self.depth -= 1;
+        res
+    }
+
+    pub fn read_type(&mut self) -> Result<SigType> {
+        self.nested(Self::read_element_type)
+    }
+
+    fn read_element_type(&mut self) -> Result<SigType> {
+        let element_type = self.read_u8()?;
+        Ok(match element_type {
+            ELEMENT_TYPE_VOID => SigType::Void,
+            ELEMENT_TYPE_BOOLEAN => SigType::Boolean,
+            ELEMENT_TYPE_CHAR => SigType::Char,
+            ELEMENT_TYPE_I1 => SigType::I1,
+            ELEMENT_TYPE_U1 => SigType::U1,
+            ELEMENT_TYPE_I2 => SigType::I2,
+            ELEMENT_TYPE_U2 => SigType::U2,
+            ELEMENT_TYPE_I4 => SigType::I4,
+            ELEMENT_TYPE_U4 => SigType::U4,
+            ELEMENT_TYPE_I8 => SigType::I8,
+            ELEMENT_TYPE_U8 => SigType::U8,
+            ELEMENT_TYPE_R4 => SigType::R4,
+            ELEMENT_TYPE_R8 => SigType::R8,
+            ELEMENT_TYPE_STRING => SigType::String,
+            ELEMENT_TYPE_OBJECT => SigType::Object,
+            ELEMENT_TYPE_TYPEDBYREF => SigType::TypedByRef,
+            ELEMENT_TYPE_I => SigType::I,
+            ELEMENT_TYPE_U => SigType::U,
+            ELEMENT_TYPE_PTR => SigType::Ptr(Box::new(self.read_type()?)),
+            ELEMENT_TYPE_BYREF => SigType::ByRef(Box::new(self.read_type()?)),
+            ELEMENT_TYPE_PINNED => SigType::Pinned(Box::new(self.read_type()?)),
+            ELEMENT_TYPE_SZARRAY => SigType::SzArray(Box::new(self.read_type()?)),
+            ELEMENT_TYPE_VALUETYPE => SigType::ValueType(self.read_type_def_or_ref()?),
+            ELEMENT_TYPE_CLASS => SigType::Class(self.read_type_def_or_ref()?),
+            ELEMENT_TYPE_VAR => SigType::Var(self.read_compressed_usize()?),
+            ELEMENT_TYPE_MVAR => SigType::MVar(self.read_compressed_usize()?),
+            ELEMENT_TYPE_CMOD_REQD => {
+                let token = self.read_type_def_or_ref()?;
+                SigType::CModReqd(token, Box::new(self.read_type()?))
+            }
+            ELEMENT_TYPE_CMOD_OPT => {
+                let token = self.read_type_def_or_ref()?;
+                SigType::CModOpt(token, Box::new(self.read_type()?))
+            }
+            ELEMENT_TYPE_ARRAY => {
+                let element = self.read_type()?;
+                let rank = self.read_compressed_usize()?;
+                let mut sizes = vec![];
+                for _ in 0..self.read_compressed_usize()? {
+                    sizes.push(self.read_compressed_usize()?);
+                }
+                let mut lo_bounds = vec![];
+                for _ in 0..self.read_compressed_usize()? {
+                    lo_bounds.push(self.read_compressed_i64()?);
+                }
+                SigType::Array(
+                    Box::new(element),
+                    ArrayShape {
+                        rank,
+                        sizes,
+                        lo_bounds,
+                    },
+                )
+            }
+            ELEMENT_TYPE_GENERICINST => {
+                let generic = self.read_type()?;
+                let mut args = vec![];
+                for _ in 0..self.read_compressed_usize()? {
+                    args.push(self.read_type()?);
+                }
+                SigType::GenericInst(Box::new(generic), args)
+            }
+            ELEMENT_TYPE_FNPTR => SigType::FnPtr(Box::new(self.read_method_sig()?)),
+            ELEMENT_TYPE_SENTINEL => SigType::Sentinel,
+            _ => {
+                return Err(Error::SignatureFormatError(format!(
+                    "unknown element type {:02x} at {}",
+                    element_type,
+                    self.pos - 1
+                )))
+            }
+        })
+    }
+
+    pub fn read_method_sig(&mut self) -> Result<MethodSig> {
+        let flags = self.read_u8()?;
+        let generic_param_count = if flags & SIG_GENERIC != 0 {
+            self.read_compressed_usize()?
+        } else {
+            0
+        };
+        let param_count = self.read_compressed_usize()?;
+        let ret_type = self.read_type()?;
+        let mut params = vec![];
+        for _ in 0..param_count {
+            let mut param = self.read_type()?;
+            if param == SigType::Sentinel {
+                param = self.read_type()?;
+            }
+            params.push(param);
+        }
+        Ok(MethodSig {
+            calling_convention: flags & SIG_CALLCONV_MASK,
+            has_this: flags & SIG_HAS_THIS != 0,
+            explicit_this: flags & SIG_EXPLICIT_THIS != 0,
+            generic_param_count,
+            ret_type,
+            params,
+        })
+    }
+}
+
+pub fn parse_method_sig(data: &[u8]) -> Result<MethodSig> {
+    SignatureReader::new(data).read_method_sig()
+}
+
+pub fn parse_field_sig(data: &[u8]) -> Result<FieldSig> {
+    let mut reader = SignatureReader::new(data);
+    let flags = reader.read_u8()?;
+    if flags & SIG_CALLCONV_MASK != SIG_FIELD {
+        return Err(Error::SignatureFormatError(format!(
+            "bad field signature {:02x}",
+            flags
+        )));
+    }
+    Ok(FieldSig {
+        field_type: reader.read_type()?,
+    })
+}
+
+pub fn parse_property_sig(data: &[u8]) -> Result<PropertySig> {
+    let mut reader = SignatureReader::new(data);
+    let flags = reader.read_u8()?;
+    if flags & SIG_CALLCONV_MASK != SIG_PROPERTY {
+        return Err(Error::SignatureFormatError(format!(
+            "bad property signature {:02x}",
+            flags
+        )));
+    }
+    let param_count = reader.read_compressed_usize()?;
+    let property_type = reader.read_type()?;
+    let mut params = vec![];
+    for _ in 0..param_count {
+        params.push(reader.read_type()?);
+    }
+    Ok(PropertySig {
+        has_this: flags & SIG_HAS_THIS != 0,
+        property_type,
+        params,
+    })
+}
+
+pub fn parse_local_var_sig(data: &[u8]) -> Result<LocalVarSig> {
+    let mut reader = SignatureReader::new(data);
+    let flags = reader.read_u8()?;
+    if flags != SIG_LOCAL_SIG {
+        return Err(Error::SignatureFormatError(format!(
+            "bad local variable signature {:02x}",
+            flags
+        )));
+    }
+    let count = reader.read_compressed_usize()?;
+    let mut locals = vec![];
+    for _ in 0..count {
+        locals.push(reader.read_type()?);
+    }
+    Ok(LocalVarSig { locals })
+}
+
+pub fn parse_method_spec(data: &[u8]) -> Result<Vec<SigType>> {
+    let mut reader = SignatureReader::new(data);
+    let flags = reader.read_u8()?;
+    if flags != SIG_GENERIC_INST {
+        return Err(Error::SignatureFormatError(format!(
+            "bad method instantiation signature {:02x}",
+            flags
+        )));
+    }
+    let count = reader.read_compressed_usize()?;
+    let mut args = vec![];
+    for _ in 0..count {
+        args.push(reader.read_type()?);
+    }
+    Ok(args)
+}
+
+pub fn parse_type_spec(data: &[u8]) -> Result<SigType> {
+    SignatureReader::new(data).read_type()
+}
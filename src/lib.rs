@@ -1,9 +1,25 @@
 use serde::{Deserialize, Serialize};
 
+pub mod anomalies;
+pub mod authenticode;
+pub mod batch;
+pub mod debug_info;
+pub mod decl_security;
+pub mod entry_point;
 pub mod error;
+pub mod generics;
+pub mod hidden;
+pub mod hierarchy;
 pub mod lang;
+pub mod members;
+pub mod mixed_mode;
+pub mod ready_to_run;
+pub mod resources;
+pub mod search;
 pub mod stream;
+pub mod strong_name;
 pub mod utils;
+pub mod writer;
 
 use crate::{
     error::Error,
@@ -28,7 +44,14 @@ impl DnPe {
         }
     }
 
-    pub fn pe(&self) -> Result<goblin::pe::PE> {
+    pub fn net_mut(&mut self) -> Result<&mut ClrData> {
+        match &mut self.net {
+            Some(s) => Ok(s),
+            None => Err(Error::NotImplementedError),
+        }
+    }
+
+    pub fn pe(&self) -> Result<goblin::pe::PE<'_>> {
         match goblin::Object::parse(&self.data)? {
             goblin::Object::PE(pe) => Ok(pe),
             _ => Err(Error::UnsupportedBinaryFormat("main")),
@@ -36,27 +59,32 @@ impl DnPe {
     }
 
     pub fn new(name: &str) -> Result<DnPe> {
+        Self::from_bytes(name, std::fs::read(name)?)
+    }
+
+    pub fn from_bytes(name: &str, data: Vec<u8>) -> Result<DnPe> {
         let mut res = DnPe {
             name: name.to_string(),
-            data: std::fs::read(name)?,
+            data,
             net: None,
         };
-        let opt_header = match res.pe()?.header.optional_header {
-            Some(oh) => oh,
-            None => return Err(Error::UnsupportedBinaryFormat("optional header absence")),
-        };
-        let clr_directory = match opt_header.data_directories.get_clr_runtime_header() {
-            Some(oh) => oh,
-            None => return Err(Error::UnsupportedBinaryFormat("ClR runtime header absence")),
-        };
-        let clr_struct: ClrStruct = res.get_data(
-            &clr_directory.virtual_address,
-            &(clr_directory.size as usize),
-        )?;
+        let clr_struct = res.clr_struct()?;
         res.net = Some(res.new_clrdata(clr_struct)?);
         Ok(res)
     }
 
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn writer(&self) -> writer::PeWriter<'_> {
+        writer::PeWriter::new(self)
+    }
+
     fn offset(&self, rva: u32) -> Result<usize> {
         match goblin::pe::utils::find_offset(
             rva as usize,
@@ -97,24 +125,53 @@ impl DnPe {
     fn get_nullterminated_string(&self, rva: &u32) -> Result<String> {
         let mut res_buf = vec![];
         let mut rrva = *rva;
-        let mut c = self.data[self.offset(rrva)?];
+        let byte_at = |rva: u32| -> Result<u8> {
+            let offset = self.offset(rva)?;
+            self.data
+                .get(offset)
+                .copied()
+                .ok_or(Error::NotEnoughData(self.data.len(), offset + 1))
+        };
+        let mut c = byte_at(rrva)?;
         while c != 0 {
             res_buf.push(c);
-            rrva += 1;
-            c = self.data[self.offset(rrva)?];
+            rrva = rrva.checked_add(1).ok_or(Error::UnresolvedRvaError(rrva))?;
+            c = byte_at(rrva)?;
         }
         Ok(String::from_utf8(res_buf)?)
     }
 
     fn get_vec(&self, rva: &u32, size: &usize) -> Result<Vec<u8>> {
         let offset = self.offset(*rva)?;
-        Ok(self.data[offset..offset + size].to_vec())
+        offset
+            .checked_add(*size)
+            .and_then(|end| self.data.get(offset..end))
+            .map(|d| d.to_vec())
+            .ok_or(Error::NotEnoughData(
+                self.data.len(),
+                offset.saturating_add(*size),
+            ))
     }
 
     fn get_dword_at_rva(&self, rva: &u32) -> Result<u32> {
         self.get_data(rva, &4)
     }
 
+    fn clr_struct(&self) -> Result<ClrStruct> {
+        let opt_header = match self.pe()?.header.optional_header {
+            Some(oh) => oh,
+            None => return Err(Error::UnsupportedBinaryFormat("optional header absence")),
+        };
+        let clr_directory = match opt_header.data_directories.get_clr_runtime_header() {
+            Some(oh) => oh,
+            None => return Err(Error::UnsupportedBinaryFormat("ClR runtime header absence")),
+        };
+        self.get_data(
+            &clr_directory.virtual_address,
+            &(clr_directory.size as usize),
+        )
+    }
+
     fn new_clrdata(&self, clr_struct: ClrStruct) -> Result<ClrData> {
         let metadata_struct: MetaDataStruct = self.get_data(
             &clr_struct.meta_data_rva,
@@ -122,17 +179,31 @@ impl DnPe {
         )?;
         let metadata = self.new_metadata(&clr_struct.meta_data_rva, metadata_struct)?;
         let flags = ClrHeaderFlags::new(clr_struct.flags as usize);
-        let functions = self.parse_functions(&metadata)?;
+        let owners = Owners::new(&metadata)?;
+        let (functions, method_body_errors) = self.parse_functions(&metadata, &owners)?;
+        let function_rids = functions
+            .iter()
+            .enumerate()
+            .map(|(i, f)| (f.method_rid(), i))
+            .collect();
         Ok(ClrData {
-            //clr_struct,
+            clr_struct,
             metadata,
             flags,
             functions,
+            method_body_errors,
+            function_rids,
+            owners,
         })
     }
 
-    fn parse_functions(&self, metadata: &MetaData) -> Result<Vec<lang::cil::function::Function>> {
+    fn parse_functions(
+        &self,
+        metadata: &MetaData,
+        owners: &Owners,
+    ) -> Result<(Vec<lang::cil::function::Function>, Vec<MethodBodyError>)> {
         let mut res = vec![];
+        let mut errors = vec![];
         let method_def_table = metadata.md_table("MethodDef")?;
         for i in 0..method_def_table.row_count() {
             let row = method_def_table.row::<MethodDef>(i)?;
@@ -148,10 +219,60 @@ impl DnPe {
             {
                 continue;
             }
-            res.push(self.parse_function(row)?);
+            let method_token = lang::clr::token::Token::new(
+                (table_name_2_index("MethodDef")? << lang::clr::token::TABLE_SHIFT) | (i + 1),
+            );
+            let mut function = match self.parse_function(row) {
+                Ok(f) => f,
+                Err(e) => {
+                    errors.push(MethodBodyError {
+                        method_token,
+                        name: row.name.clone(),
+                        rva: row.rva,
+                        error: e.to_string(),
+                    });
+                    continue;
+                }
+            };
+            function.method_token = method_token;
+            function.name = row.name.clone();
+            if let Some(type_rid) = owners.method_types.get(&(i + 1)) {
+                let type_row = metadata.md_table("TypeDef")?.row::<TypeDef>(type_rid - 1)?;
+                function.declaring_type = Some(lang::clr::token::Token::new(
+                    (table_name_2_index("TypeDef")? << lang::clr::token::TABLE_SHIFT) | type_rid,
+                ));
+                function.type_name = full_type_name(&type_row.type_namespace, &type_row.type_name);
+            }
+            if let Some(token) = function.local_var_sig_tok() {
+                match Self::parse_locals(metadata, token) {
+                    Ok(locals) => function.locals = locals,
+                    Err(e) => function.locals_error = Some(e.to_string()),
+                }
+            }
+            res.push(function);
         }
-        Ok(res)
+        Ok((res, errors))
     }
+
+    fn parse_locals(
+        metadata: &MetaData,
+        token: &lang::clr::token::Token,
+    ) -> Result<Vec<lang::clr::local::LocalVariable>> {
+        // a zero LocalVarSigTok means the method declares no locals
+        if token.rid() == 0 {
+            return Ok(vec![]);
+        }
+        let row = metadata
+            .md_table("StandAloneSig")?
+            .row::<StandAloneSig>(token.rid() - 1)?;
+        Ok(lang::clr::signature::parse_local_var_sig(&row.signature)?
+            .locals
+            .into_iter()
+            .enumerate()
+            .map(|(i, t)| lang::clr::local::LocalVariable::new(i, t))
+            .collect())
+    }
+
     fn parse_function(&self, row: &MethodDef) -> Result<lang::cil::function::Function> {
         let mut reader = lang::cil::function::reader::Reader::new(&self.data);
         reader.seek(self.offset(row.rva)?)?;
@@ -175,17 +296,20 @@ impl DnPe {
         )?;
         let struct_size = 16 + metadata_struct.version_length + 2 + 2;
         let mut streams = std::collections::HashMap::new();
+        let mut stream_headers = vec![];
         if number_of_streams > 0 {
             let streams_table_rva = metadata_rva + struct_size;
-            streams = self.new_streams(
+            (streams, stream_headers) = self.new_streams(
                 metadata_rva,
                 &streams_table_rva,
                 &(number_of_streams as usize),
             )?;
         }
         Ok(MetaData {
+            header: metadata_struct,
             _version: String::from_utf8(version)?,
             flags,
+            stream_headers,
             streams,
         })
     }
@@ -195,19 +319,28 @@ impl DnPe {
         metadata_rva: &u32,
         streams_table_rva: &u32,
         number_of_streams: &usize,
-    ) -> Result<std::collections::HashMap<String, stream::ClrStream>> {
+    ) -> Result<(
+        std::collections::HashMap<String, stream::ClrStream>,
+        Vec<stream::StreamHeader>,
+    )> {
         let mut res = std::collections::HashMap::new();
+        let mut headers = vec![];
         let mut stream_entry_rva = *streams_table_rva;
         for _i in 0..*number_of_streams {
             let stream = self.new_clr_stream(&stream_entry_rva, metadata_rva)?;
             stream_entry_rva += &(stream.stream_table_entry_size as u32);
+            headers.push(stream::StreamHeader {
+                name: stream.name().to_string(),
+                offset: stream.rva - metadata_rva,
+                size: stream.size as u32,
+            });
             res.insert(stream.name().to_string(), stream);
         }
         let mut rres = std::collections::HashMap::new();
         for (n, s) in &res {
             rres.insert(n.to_string(), self.parse_clr_stream(s, &res)?);
         }
-        Ok(rres)
+        Ok((rres, headers))
     }
 
     fn new_clr_stream(
@@ -218,7 +351,10 @@ impl DnPe {
         let stream_offset: u32 = self.get_data(stream_table_entry_rva, &4)?;
         let stream_size: u32 = self.get_data(&(stream_table_entry_rva + 4), &4)?;
         let stream_name = self.get_nullterminated_string(&(stream_table_entry_rva + 8))?;
-        let stream_data = self.get_vec(&(metadata_rva + stream_offset), &(stream_size as usize))?;
+        let stream_rva = metadata_rva
+            .checked_add(stream_offset)
+            .ok_or(Error::UnresolvedRvaError(stream_offset))?;
+        let stream_data = self.get_vec(&stream_rva, &(stream_size as usize))?;
         self.nnew_clr_stream(
             metadata_rva,
             &stream_offset,
@@ -230,27 +366,27 @@ impl DnPe {
 }
 
 #[repr(C)]
-#[derive(Deserialize, Debug, scroll::Pread)]
+#[derive(Deserialize, Serialize, Debug, Clone, scroll::Pread)]
 pub struct ClrStruct {
-    cb: u32,
-    majorr_runtime_version: u16,
-    minor_runtime_version: u16,
-    meta_data_rva: u32,
-    meta_data_size: u32,
-    flags: u32,
-    entry_point_token_or_rva: u32,
-    resources_rva: u32,
-    resources_size: u32,
-    strong_name_signature_rva: u32,
-    strong_name_signature_size: u32,
-    code_manager_table_rva: u32,
-    code_manager_table_size: u32,
-    v_table_fixups_rva: u32,
-    v_table_fixups_size: u32,
-    export_address_table_jumps_rva: u32,
-    export_address_table_jumps_size: u32,
-    managed_native_header_rva: u32,
-    managed_native_header_size: u32,
+    pub cb: u32,
+    pub major_runtime_version: u16,
+    pub minor_runtime_version: u16,
+    pub meta_data_rva: u32,
+    pub meta_data_size: u32,
+    pub flags: u32,
+    pub entry_point_token_or_rva: u32,
+    pub resources_rva: u32,
+    pub resources_size: u32,
+    pub strong_name_signature_rva: u32,
+    pub strong_name_signature_size: u32,
+    pub code_manager_table_rva: u32,
+    pub code_manager_table_size: u32,
+    pub v_table_fixups_rva: u32,
+    pub v_table_fixups_size: u32,
+    pub export_address_table_jumps_rva: u32,
+    pub export_address_table_jumps_size: u32,
+    pub managed_native_header_rva: u32,
+    pub managed_native_header_size: u32,
 }
 
 #[derive(Debug, Serialize, PartialOrd, Ord, PartialEq, Eq)]
@@ -294,8 +430,7 @@ impl ClrHeaderFlags {
 
 #[derive(Debug, Serialize)]
 pub struct ClrData {
-    #[serde(skip_serializing)]
-    //    clr_struct: ClrStruct,
+    clr_struct: ClrStruct,
     pub metadata: MetaData,
     //    strings: Option<StringsHeap>,
     //    user_strings: Option<UserStringHeap>,
@@ -304,6 +439,119 @@ pub struct ClrData {
     //    mdtables: Option<MetaDataTables>,
     pub flags: std::collections::BTreeSet<ClrHeaderFlags>,
     pub functions: Vec<lang::cil::function::Function>,
+    /// IL methods whose body could not be read, and so have no entry in `functions`.
+    pub method_body_errors: Vec<MethodBodyError>,
+    #[serde(skip_serializing)]
+    function_rids: std::collections::HashMap<usize, usize>,
+    #[serde(skip_serializing)]
+    owners: Owners,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MethodBodyError {
+    pub method_token: lang::clr::token::Token,
+    pub name: String,
+    pub rva: u32,
+    pub error: String,
+}
+
+#[derive(Debug, Default)]
+struct Owners {
+    method_types: std::collections::HashMap<usize, usize>,
+    field_types: std::collections::HashMap<usize, usize>,
+    param_methods: std::collections::HashMap<usize, usize>,
+    event_types: std::collections::HashMap<usize, usize>,
+    property_types: std::collections::HashMap<usize, usize>,
+    enclosing_types: std::collections::HashMap<usize, usize>,
+    qualified_type_names: Vec<String>,
+    type_names: std::collections::HashMap<String, usize>,
+    generic_params: std::collections::HashMap<usize, Vec<usize>>,
+    generic_param_constraints: std::collections::HashMap<usize, Vec<usize>>,
+}
+
+impl Owners {
+    fn new(metadata: &MetaData) -> Result<Self> {
+        let mut res = Self::default();
+        if let Ok(table) = metadata.md_table("TypeDef") {
+            for i in 0..table.row_count() {
+                let row = table.row::<TypeDef>(i)?;
+                for m in &row.method_list {
+                    res.method_types.insert(m.row_index, i + 1);
+                }
+                for f in &row.field_list {
+                    res.field_types.insert(f.row_index, i + 1);
+                }
+            }
+        }
+        if let Ok(table) = metadata.md_table("MethodDef") {
+            for i in 0..table.row_count() {
+                for p in &table.row::<MethodDef>(i)?.param_list {
+                    res.param_methods.insert(p.row_index, i + 1);
+                }
+            }
+        }
+        if let Ok(table) = metadata.md_table("EventMap") {
+            for i in 0..table.row_count() {
+                let row = table.row::<EventMap>(i)?;
+                for e in &row.event_list {
+                    res.event_types.insert(e.row_index, row.parent.row_index);
+                }
+            }
+        }
+        if let Ok(table) = metadata.md_table("PropertyMap") {
+            for i in 0..table.row_count() {
+                let row = table.row::<PropertyMap>(i)?;
+                for p in &row.property_list {
+                    res.property_types.insert(p.row_index, row.parent.row_index);
+                }
+            }
+        }
+        if let Ok(table) = metadata.md_table("NestedClass") {
+            for i in 0..table.row_count() {
+                let row = table.row::<NestedClass>(i)?;
+                res.enclosing_types
+                    .insert(row.nested_class.row_index, row.enclosing_class.row_index);
+            }
+        }
+        if let Ok(table) = metadata.md_table("TypeDef") {
+            let mut names = vec![];
+            for i in 0..table.row_count() {
+                let row = table.row::<TypeDef>(i)?;
+                names.push((
+                    full_type_name(&row.type_namespace, &row.type_name),
+                    row.type_name.clone(),
+                ));
+            }
+            for rid in 1..=names.len() {
+                let name = qualified_type_name(rid, &names, &res.enclosing_types, 0);
+                res.type_names.entry(name.clone()).or_insert(rid);
+                res.qualified_type_names.push(name);
+            }
+        }
+        if let Ok(table) = metadata.md_table("GenericParam") {
+            let mut params = vec![];
+            for i in 0..table.row_count() {
+                let row = table.row::<GenericParam>(i)?;
+                let owner = (table_name_2_index(row.owner.table)? << lang::clr::token::TABLE_SHIFT)
+                    | row.owner.row_index;
+                params.push((owner, row.number, i + 1));
+            }
+            params.sort();
+            for (owner, _, rid) in params {
+                res.generic_params.entry(owner).or_default().push(rid);
+            }
+        }
+        if let Ok(table) = metadata.md_table("GenericParamConstraint") {
+            for i in 0..table.row_count() {
+                let row = table.row::<GenericParamConstraint>(i)?;
+                res.generic_param_constraints
+                    .entry(row.owner.row_index)
+                    .or_default()
+                    .push(i);
+            }
+        }
+        Ok(res)
+    }
 }
 
 impl ClrData {
@@ -314,6 +562,13 @@ impl ClrData {
         self.metadata.md_table(name)
     }
 
+    pub fn md_table_mut(
+        &mut self,
+        name: &'static str,
+    ) -> Result<&mut stream::meta_data_tables::mdtables::MetaDataTable> {
+        self.metadata.md_table_mut(name)
+    }
+
     pub fn md_table_by_index(
         &self,
         index: &usize,
@@ -332,23 +587,657 @@ impl ClrData {
         table.row(index.row_index() - 1)
     }
 
+    pub fn clr_header(&self) -> &ClrStruct {
+        &self.clr_struct
+    }
+
     pub fn functions(&self) -> &Vec<lang::cil::function::Function> {
         &self.functions
     }
 
+    pub fn method_body_errors(&self) -> &Vec<MethodBodyError> {
+        &self.method_body_errors
+    }
+
+    pub fn function_for(
+        &self,
+        method_token: &lang::clr::token::Token,
+    ) -> Option<&lang::cil::function::Function> {
+        if method_token.table() != table_name_2_index("MethodDef").ok()? {
+            return None;
+        }
+        self.function_rids
+            .get(&method_token.rid())
+            .and_then(|i| self.functions.get(*i))
+    }
+
+    pub fn method_for_function(
+        &self,
+        function: &lang::cil::function::Function,
+    ) -> Result<&MethodDef> {
+        let table = self.md_table("MethodDef")?;
+        match function.method_rid().checked_sub(1) {
+            Some(i) => table.row::<MethodDef>(i),
+            None => Err(Error::RowIndexOutOfBound(0, table.row_count())),
+        }
+    }
+
     pub fn get_us(&self, rid: usize) -> Result<String> {
         self.metadata.get_us(rid)
     }
+
+    pub fn method_signature(&self, method: &MethodDef) -> Result<lang::clr::signature::MethodSig> {
+        lang::clr::signature::parse_method_sig(&method.signature)
+    }
+
+    pub fn method_params(&self, method: &MethodDef) -> Result<Vec<&Param>> {
+        let mut res = vec![];
+        if method.param_list.is_empty() {
+            return Ok(res);
+        }
+        let table = self.md_table("Param")?;
+        for p in &method.param_list {
+            res.push(table.row::<Param>(p.row_index - 1)?);
+        }
+        Ok(res)
+    }
+
+    pub fn field_marshal(
+        &self,
+        field_rid: usize,
+    ) -> Result<Option<lang::clr::marshal::NativeType>> {
+        self.marshal("Field", field_rid)
+    }
+
+    pub fn param_marshal(
+        &self,
+        param_rid: usize,
+    ) -> Result<Option<lang::clr::marshal::NativeType>> {
+        self.marshal("Param", param_rid)
+    }
+
+    fn marshal(
+        &self,
+        parent: &'static str,
+        rid: usize,
+    ) -> Result<Option<lang::clr::marshal::NativeType>> {
+        if let Ok(table) = self.md_table("FieldMarshal") {
+            for i in 0..table.row_count() {
+                let row = table.row::<FieldMarshal>(i)?;
+                if row.parent.table == parent && row.parent.row_index == rid {
+                    return Ok(Some(lang::clr::marshal::parse_marshal_spec(
+                        &row.native_type,
+                    )?));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn method_declaring_type(&self, method_rid: usize) -> Option<usize> {
+        self.owners.method_types.get(&method_rid).copied()
+    }
+
+    pub fn field_declaring_type(&self, field_rid: usize) -> Option<usize> {
+        self.owners.field_types.get(&field_rid).copied()
+    }
+
+    pub fn param_method(&self, param_rid: usize) -> Option<usize> {
+        self.owners.param_methods.get(&param_rid).copied()
+    }
+
+    pub fn event_declaring_type(&self, event_rid: usize) -> Option<usize> {
+        self.owners.event_types.get(&event_rid).copied()
+    }
+
+    pub fn property_declaring_type(&self, property_rid: usize) -> Option<usize> {
+        self.owners.property_types.get(&property_rid).copied()
+    }
+
+    pub fn enclosing_type(&self, type_rid: usize) -> Option<usize> {
+        self.owners.enclosing_types.get(&type_rid).copied()
+    }
+
+    pub fn type_methods(&self, type_def: &TypeDef) -> Result<Vec<&MethodDef>> {
+        let mut res = vec![];
+        if type_def.method_list.is_empty() {
+            return Ok(res);
+        }
+        let table = self.md_table("MethodDef")?;
+        for m in &type_def.method_list {
+            res.push(table.row::<MethodDef>(m.row_index - 1)?);
+        }
+        Ok(res)
+    }
+
+    pub fn type_fields(&self, type_def: &TypeDef) -> Result<Vec<&Field>> {
+        let mut res = vec![];
+        if type_def.field_list.is_empty() {
+            return Ok(res);
+        }
+        let table = self.md_table("Field")?;
+        for f in &type_def.field_list {
+            res.push(table.row::<Field>(f.row_index - 1)?);
+        }
+        Ok(res)
+    }
+
+    pub fn type_properties(&self, type_rid: usize) -> Result<Vec<&Property>> {
+        let mut rids: Vec<usize> = self
+            .owners
+            .property_types
+            .iter()
+            .filter(|(_, t)| **t == type_rid)
+            .map(|(p, _)| *p)
+            .collect();
+        rids.sort();
+        let mut res = vec![];
+        if rids.is_empty() {
+            return Ok(res);
+        }
+        let table = self.md_table("Property")?;
+        for rid in rids {
+            res.push(table.row::<Property>(rid - 1)?);
+        }
+        Ok(res)
+    }
+
+    pub fn type_events(&self, type_rid: usize) -> Result<Vec<&Event>> {
+        let mut rids: Vec<usize> = self
+            .owners
+            .event_types
+            .iter()
+            .filter(|(_, t)| **t == type_rid)
+            .map(|(e, _)| *e)
+            .collect();
+        rids.sort();
+        let mut res = vec![];
+        if rids.is_empty() {
+            return Ok(res);
+        }
+        let table = self.md_table("Event")?;
+        for rid in rids {
+            res.push(table.row::<Event>(rid - 1)?);
+        }
+        Ok(res)
+    }
+
+    pub fn nested_types(&self, type_rid: usize) -> Vec<usize> {
+        let mut res: Vec<usize> = self
+            .owners
+            .enclosing_types
+            .iter()
+            .filter(|(_, t)| **t == type_rid)
+            .map(|(n, _)| *n)
+            .collect();
+        res.sort();
+        res
+    }
+
+    pub fn call_signature(
+        &self,
+        token: &lang::clr::token::Token,
+    ) -> Result<lang::clr::signature::MethodSig> {
+        let rid = token.rid();
+        if rid == 0 {
+            return Err(Error::RowIndexOutOfBound(0, 0));
+        }
+        match token.table() {
+            t if t == table_name_2_index("MethodDef")? => {
+                self.method_signature(self.md_table("MethodDef")?.row::<MethodDef>(rid - 1)?)
+            }
+            t if t == table_name_2_index("MemberRef")? => lang::clr::signature::parse_method_sig(
+                &self
+                    .md_table("MemberRef")?
+                    .row::<MemberRef>(rid - 1)?
+                    .signature,
+            ),
+            t if t == table_name_2_index("StandAloneSig")? => {
+                lang::clr::signature::parse_method_sig(
+                    &self
+                        .md_table("StandAloneSig")?
+                        .row::<StandAloneSig>(rid - 1)?
+                        .signature,
+                )
+            }
+            t if t == table_name_2_index("MethodSpec")? => {
+                let row = self.md_table("MethodSpec")?.row::<MethodSpec>(rid - 1)?;
+                self.call_signature(&lang::clr::token::Token::new(
+                    (table_name_2_index(row.method.table)? << lang::clr::token::TABLE_SHIFT)
+                        | row.method.row_index,
+                ))
+            }
+            t => Err(Error::UndefinedMetaDataTableIndex(t as u32)),
+        }
+    }
+
+    pub fn stack_effect(
+        &self,
+        function: &lang::cil::function::Function,
+        insn: &lang::cil::instruction::Instruction,
+    ) -> Option<(usize, usize)> {
+        self.method_stack_effect(self.method_for_function(function).ok()?, insn)
+    }
+
+    fn method_stack_effect(
+        &self,
+        method: &MethodDef,
+        insn: &lang::cil::instruction::Instruction,
+    ) -> Option<(usize, usize)> {
+        use lang::cil::{enums::OpCodeValue, instruction::Operand};
+        use lang::clr::signature::SigType;
+        let returns = |sig: &lang::clr::signature::MethodSig| -> usize {
+            !matches!(sig.ret_type.base(), SigType::Void) as usize
+        };
+        if insn.opcode.value == OpCodeValue::Ret {
+            let sig = self.method_signature(method).ok()?;
+            return Some((returns(&sig), 0));
+        }
+        let sig = match &insn.operand {
+            Operand::Token(t) => self.call_signature(t).ok()?,
+            _ => return None,
+        };
+        let this = (sig.has_this && !sig.explicit_this) as usize;
+        match insn.opcode.value {
+            OpCodeValue::Call | OpCodeValue::Callvirt => {
+                Some((sig.params.len() + this, returns(&sig)))
+            }
+            OpCodeValue::Newobj => Some((sig.params.len(), 1)),
+            OpCodeValue::Calli => Some((sig.params.len() + this + 1, returns(&sig))),
+            _ => None,
+        }
+    }
+
+    pub fn compute_max_stack(
+        &self,
+        function: &lang::cil::function::Function,
+        body: &lang::cil::function::writer::MethodBody,
+    ) -> Result<usize> {
+        body.compute_max_stack(&|insn| self.stack_effect(function, insn))
+    }
+
+    /// Encodes `body` as the body of `method`, computing max_stack from the
+    /// call signatures in this assembly when the body does not set one.
+    pub fn encode_method_body(
+        &self,
+        method: &lang::clr::token::Token,
+        body: &lang::cil::function::writer::MethodBody,
+    ) -> Result<Vec<u8>> {
+        if body.max_stack.is_some() {
+            return body.encode();
+        }
+        if method.table() != table_name_2_index("MethodDef")? {
+            return Err(Error::IncorrectTableRequested(
+                "MethodDef",
+                file!(),
+                line!(),
+            ));
+        }
+        let table = self.md_table("MethodDef")?;
+        let row = match method.rid().checked_sub(1) {
+            Some(i) => table.row::<MethodDef>(i)?,
+            None => return Err(Error::RowIndexOutOfBound(0, table.row_count())),
+        };
+        body.encode_with(&|insn| self.method_stack_effect(row, insn))
+    }
+
+    pub fn argument(
+        &self,
+        function: &lang::cil::function::Function,
+        argument: &lang::clr::argument::Argument,
+    ) -> Result<lang::clr::argument::ArgumentInfo> {
+        let method = self.method_for_function(function)?;
+        let sig = self.method_signature(method)?;
+        let mut index = argument.index();
+        if sig.has_this && !sig.explicit_this {
+            if index == 0 {
+                return Ok(lang::clr::argument::ArgumentInfo {
+                    index: 0,
+                    name: Some("this".to_string()),
+                    arg_type: match &function.declaring_type {
+                        Some(t) => lang::clr::signature::SigType::Class(t.clone()),
+                        None => lang::clr::signature::SigType::Object,
+                    },
+                    is_this: true,
+                });
+            }
+            index -= 1;
+        }
+        let arg_type = sig.params.get(index).cloned().ok_or_else(|| {
+            Error::SignatureFormatError(format!(
+                "argument {} out of range for {}",
+                argument.index(),
+                function.full_name()
+            ))
+        })?;
+        let name = self
+            .method_params(method)?
+            .iter()
+            .find(|p| p.sequence == index + 1)
+            .map(|p| p.name.clone());
+        Ok(lang::clr::argument::ArgumentInfo {
+            index: argument.index(),
+            name,
+            arg_type,
+            is_this: false,
+        })
+    }
+
+    pub fn type_name(&self, token: &lang::clr::token::Token) -> Result<String> {
+        self.type_name_with(token, &generics::GenericContext::default())
+    }
+
+    pub fn type_name_with(
+        &self,
+        token: &lang::clr::token::Token,
+        context: &generics::GenericContext,
+    ) -> Result<String> {
+        self.type_name_in(token, context, &mut vec![])
+    }
+
+    /// `path` holds the tokens currently being resolved, so a TypeRef whose
+    /// resolution scope or a TypeSpec whose signature leads back to itself
+    /// is reported instead of recursing forever.
+    fn type_name_in(
+        &self,
+        token: &lang::clr::token::Token,
+        context: &generics::GenericContext,
+        path: &mut Vec<usize>,
+    ) -> Result<String> {
+        let rid = token.rid();
+        if rid == 0 {
+            return Err(Error::RowIndexOutOfBound(0, 0));
+        }
+        if path.contains(&token.value) {
+            return Err(Error::CyclicTypeReference(token.value));
+        }
+        path.push(token.value);
+        let res = match token.table() {
+            t if t == table_name_2_index("TypeDef")? => {
+                let row = self.md_table("TypeDef")?.row::<TypeDef>(rid - 1)?;
+                Ok(full_type_name(&row.type_namespace, &row.type_name))
+            }
+            t if t == table_name_2_index("TypeRef")? => {
+                let row = self.md_table("TypeRef")?.row::<TypeRef>(rid - 1)?;
+                if row.resolution_scope.table == "TypeRef" && row.resolution_scope.row_index > 0 {
+                    self.type_name_in(
+                        &lang::clr::token::Token::new(
+                            (t << lang::clr::token::TABLE_SHIFT) | row.resolution_scope.row_index,
+                        ),
+                        context,
+                        path,
+                    )
+                    .map(|parent| format!("{}/{}", parent, row.type_name))
+                } else {
+                    Ok(full_type_name(&row.type_namespace, &row.type_name))
+                }
+            }
+            t if t == table_name_2_index("TypeSpec")? => {
+                let row = self.md_table("TypeSpec")?.row::<TypeSpec>(rid - 1)?;
+                lang::clr::signature::parse_type_spec(&row.signature)
+                    .and_then(|sig_type| self.sig_type_name_in(&sig_type, context, path))
+            }
+            t => Err(Error::UndefinedMetaDataTableIndex(t as u32)),
+        };
+        path.pop();
+        res
+    }
+
+    pub fn member_name(&self, token: &lang::clr::token::Token) -> Result<String> {
+        self.member_name_with(token, &generics::GenericContext::default())
+    }
+
+    pub fn member_name_with(
+        &self,
+        token: &lang::clr::token::Token,
+        context: &generics::GenericContext,
+    ) -> Result<String> {
+        let rid = token.rid();
+        if rid == 0 {
+            return Err(Error::RowIndexOutOfBound(0, 0));
+        }
+        match token.table() {
+            t if t == table_name_2_index("Field")? => {
+                Ok(self.md_table("Field")?.row::<Field>(rid - 1)?.name.clone())
+            }
+            t if t == table_name_2_index("MethodDef")? => match self.function_for(token) {
+                Some(f) => Ok(f.full_name()),
+                None => Ok(self
+                    .md_table("MethodDef")?
+                    .row::<MethodDef>(rid - 1)?
+                    .name
+                    .clone()),
+            },
+            t if t == table_name_2_index("MemberRef")? => {
+                let row = self.md_table("MemberRef")?.row::<MemberRef>(rid - 1)?;
+                let parent = match row.class.table {
+                    "TypeDef" | "TypeRef" | "TypeSpec" => self.type_name_with(
+                        &lang::clr::token::Token::new(
+                            (table_name_2_index(row.class.table)? << lang::clr::token::TABLE_SHIFT)
+                                | row.class.row_index,
+                        ),
+                        context,
+                    )?,
+                    "ModuleRef" => {
+                        let table = self.md_table("ModuleRef")?;
+                        match row.class.row_index.checked_sub(1) {
+                            Some(i) => table.row::<ModuleRef>(i)?.name.clone(),
+                            None => return Err(Error::RowIndexOutOfBound(0, table.row_count())),
+                        }
+                    }
+                    _ => return Ok(row.name.clone()),
+                };
+                Ok(format!("{}::{}", parent, row.name))
+            }
+            t if t == table_name_2_index("MethodSpec")? => {
+                let row = self.md_table("MethodSpec")?.row::<MethodSpec>(rid - 1)?;
+                let name = self.member_name_with(
+                    &lang::clr::token::Token::new(
+                        (table_name_2_index(row.method.table)? << lang::clr::token::TABLE_SHIFT)
+                            | row.method.row_index,
+                    ),
+                    context,
+                )?;
+                match self.method_spec_instantiation(rid) {
+                    Ok(args) => Ok(format!(
+                        "{}<{}>",
+                        name,
+                        args.iter()
+                            .map(|a| self.sig_type_name_with(a, context))
+                            .collect::<Vec<String>>()
+                            .join(",")
+                    )),
+                    Err(_) => Ok(name),
+                }
+            }
+            _ => self.type_name_with(token, context),
+        }
+    }
+
+    pub fn custom_attribute_value(
+        &self,
+        attribute: &CustomAttribute,
+    ) -> Result<lang::clr::attribute::CustomAttributeValue> {
+        let rid = attribute._type.row_index;
+        if rid == 0 {
+            return Err(Error::RowIndexOutOfBound(0, 0));
+        }
+        let signature = match attribute._type.table {
+            "MethodDef" => {
+                &self
+                    .md_table("MethodDef")?
+                    .row::<MethodDef>(rid - 1)?
+                    .signature
+            }
+            "MemberRef" => {
+                &self
+                    .md_table("MemberRef")?
+                    .row::<MemberRef>(rid - 1)?
+                    .signature
+            }
+            _ => return Err(Error::UndefinedMetaDataTableName(attribute._type.table)),
+        };
+        let ctor = lang::clr::signature::parse_method_sig(signature)?;
+        lang::clr::attribute::parse_custom_attribute(&attribute.value, &ctor.params, &|token| {
+            self.enum_underlying_type(token)
+                .unwrap_or(lang::clr::signature::SigType::I4)
+        })
+    }
+
+    fn enum_underlying_type(
+        &self,
+        token: &lang::clr::token::Token,
+    ) -> Result<lang::clr::signature::SigType> {
+        if token.table() != table_name_2_index("TypeDef")? || token.rid() == 0 {
+            return Err(Error::UndefinedMetaDataTableIndex(token.table() as u32));
+        }
+        let type_def = self.md_table("TypeDef")?.row::<TypeDef>(token.rid() - 1)?;
+        let fields = self.md_table("Field")?;
+        for index in &type_def.field_list {
+            let field = fields.row::<Field>(index.row_index - 1)?;
+            if field.name == "value__" {
+                return Ok(lang::clr::signature::parse_field_sig(&field.signature)?.field_type);
+            }
+        }
+        Err(Error::FormatError(format!(
+            "type 0x{:08x} is not an enum",
+            token.value
+        )))
+    }
+
+    pub fn sig_type_name(&self, sig_type: &lang::clr::signature::SigType) -> String {
+        self.sig_type_name_with(sig_type, &generics::GenericContext::default())
+    }
+
+    pub fn sig_type_name_with(
+        &self,
+        sig_type: &lang::clr::signature::SigType,
+        context: &generics::GenericContext,
+    ) -> String {
+        match self.sig_type_name_in(sig_type, context, &mut vec![]) {
+            Ok(name) => name,
+            Err(Error::CyclicTypeReference(value)) => format!("{:08x}", value),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    fn sig_type_name_in(
+        &self,
+        sig_type: &lang::clr::signature::SigType,
+        context: &generics::GenericContext,
+        path: &mut Vec<usize>,
+    ) -> Result<String> {
+        use lang::clr::signature::SigType;
+        let token_name = |token: &lang::clr::token::Token, path: &mut Vec<usize>| match self
+            .type_name_in(token, context, path)
+        {
+            Err(Error::CyclicTypeReference(value)) => Err(Error::CyclicTypeReference(value)),
+            Err(_) => Ok(format!("{:08x}", token.value)),
+            name => name,
+        };
+        Ok(match sig_type {
+            SigType::Void => "void".to_string(),
+            SigType::Boolean => "bool".to_string(),
+            SigType::Char => "char".to_string(),
+            SigType::I1 => "int8".to_string(),
+            SigType::U1 => "uint8".to_string(),
+            SigType::I2 => "int16".to_string(),
+            SigType::U2 => "uint16".to_string(),
+            SigType::I4 => "int32".to_string(),
+            SigType::U4 => "uint32".to_string(),
+            SigType::I8 => "int64".to_string(),
+            SigType::U8 => "uint64".to_string(),
+            SigType::R4 => "float32".to_string(),
+            SigType::R8 => "float64".to_string(),
+            SigType::String => "string".to_string(),
+            SigType::Object => "object".to_string(),
+            SigType::TypedByRef => "typedref".to_string(),
+            SigType::I => "native int".to_string(),
+            SigType::U => "native uint".to_string(),
+            SigType::Ptr(t) => format!("{}*", self.sig_type_name_in(t, context, path)?),
+            SigType::ByRef(t) => format!("{}&", self.sig_type_name_in(t, context, path)?),
+            SigType::Pinned(t) => format!("{} pinned", self.sig_type_name_in(t, context, path)?),
+            SigType::SzArray(t) => format!("{}[]", self.sig_type_name_in(t, context, path)?),
+            SigType::Array(t, shape) => format!(
+                "{}[{}]",
+                self.sig_type_name_in(t, context, path)?,
+                ",".repeat(shape.rank.saturating_sub(1))
+            ),
+            SigType::ValueType(t) | SigType::Class(t) => token_name(t, path)?,
+            SigType::Var(i) => context
+                .type_def
+                .as_ref()
+                .and_then(|t| self.generic_param_name(t, *i))
+                .unwrap_or_else(|| format!("!{}", i)),
+            SigType::MVar(i) => context
+                .method
+                .as_ref()
+                .and_then(|m| self.generic_param_name(m, *i))
+                .unwrap_or_else(|| format!("!!{}", i)),
+            SigType::GenericInst(t, args) => format!(
+                "{}<{}>",
+                self.sig_type_name_in(t, context, path)?,
+                args.iter()
+                    .map(|a| self.sig_type_name_in(a, context, path))
+                    .collect::<Result<Vec<String>>>()?
+                    .join(",")
+            ),
+            SigType::FnPtr(sig) => format!(
+                "method {} *({})",
+                self.sig_type_name_in(&sig.ret_type, context, path)?,
+                sig.params
+                    .iter()
+                    .map(|a| self.sig_type_name_in(a, context, path))
+                    .collect::<Result<Vec<String>>>()?
+                    .join(",")
+            ),
+            SigType::CModReqd(m, t) => format!(
+                "{} modreq({})",
+                self.sig_type_name_in(t, context, path)?,
+                token_name(m, path)?
+            ),
+            SigType::CModOpt(m, t) => format!(
+                "{} modopt({})",
+                self.sig_type_name_in(t, context, path)?,
+                token_name(m, path)?
+            ),
+            SigType::Sentinel => "...".to_string(),
+        })
+    }
+}
+
+fn qualified_type_name(
+    rid: usize,
+    names: &[(String, String)],
+    enclosing_types: &std::collections::HashMap<usize, usize>,
+    depth: usize,
+) -> String {
+    match enclosing_types.get(&rid) {
+        Some(e) if *e > 0 && *e <= names.len() && *e != rid && depth < names.len() => format!(
+            "{}/{}",
+            qualified_type_name(*e, names, enclosing_types, depth + 1),
+            names[rid - 1].1
+        ),
+        _ => names[rid - 1].0.clone(),
+    }
+}
+
+fn full_type_name(namespace: &str, name: &str) -> String {
+    if namespace.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", namespace, name)
+    }
 }
 
 #[repr(C)]
-#[derive(Deserialize, Debug, Clone, scroll::Pread)]
+#[derive(Deserialize, Serialize, Debug, Clone, scroll::Pread)]
 pub struct MetaDataStruct {
-    signature: u32,
-    major_version: u16,
-    minor_version: u16,
-    reserved: u32,
-    version_length: u32,
+    pub signature: u32,
+    pub major_version: u16,
+    pub minor_version: u16,
+    pub reserved: u32,
+    pub version_length: u32,
     //    version: u32,
     //    flags: u32,
     //    number_of_streams: u32
@@ -356,12 +1245,21 @@ pub struct MetaDataStruct {
 
 #[derive(Debug, Serialize)]
 pub struct MetaData {
-    #[serde(skip_serializing)]
+    header: MetaDataStruct,
+    #[serde(rename = "version", serialize_with = "serialize_version")]
     _version: String,
     flags: u16,
+    stream_headers: Vec<stream::StreamHeader>,
     pub streams: std::collections::HashMap<String, stream::ClrStream>,
 }
 
+fn serialize_version<S: serde::Serializer>(
+    version: &str,
+    s: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    s.serialize_str(version.trim_end_matches('\0'))
+}
+
 impl MetaData {
     pub fn md_table(
         &self,
@@ -382,6 +1280,25 @@ impl MetaData {
         Err(Error::UndefinedMetaDataTableName(name))
     }
 
+    pub fn md_table_mut(
+        &mut self,
+        name: &'static str,
+    ) -> Result<&mut stream::meta_data_tables::mdtables::MetaDataTable> {
+        for s in self.streams.values_mut() {
+            if let stream::Stream::MetaDataTables(mt) = &mut s.stream {
+                match mt
+                    .tables
+                    .get_mut(&stream::meta_data_tables::mdtables::table_name_2_index(
+                        name,
+                    )?) {
+                    Some(s) => return Ok(s),
+                    None => return Err(Error::UndefinedMetaDataTableName(name)),
+                }
+            }
+        }
+        Err(Error::UndefinedMetaDataTableName(name))
+    }
+
     pub fn md_table_by_index(
         &self,
         index: &usize,
@@ -397,6 +1314,29 @@ impl MetaData {
         Err(Error::UndefinedMetaDataTableIndex(*index as u32))
     }
 
+    pub fn version(&self) -> &str {
+        self._version.trim_end_matches('\0')
+    }
+
+    pub fn header(&self) -> &MetaDataStruct {
+        &self.header
+    }
+
+    pub fn tables_header(&self) -> Option<&stream::meta_data_tables::MDTablesStruct> {
+        self.streams.values().find_map(|s| match &s.stream {
+            stream::Stream::MetaDataTables(mt) => mt.header(),
+            _ => None,
+        })
+    }
+
+    pub fn flags(&self) -> u16 {
+        self.flags
+    }
+
+    pub fn stream_headers(&self) -> &[stream::StreamHeader] {
+        &self.stream_headers
+    }
+
     pub fn get_us(&self, rid: usize) -> Result<String> {
         for s in self.streams.values() {
             if let stream::Stream::UserStringHeap(us) = &s.stream {
@@ -0,0 +1,151 @@
+use crate::{
+    generics::GenericContext,
+    lang::clr::{
+        signature::parse_property_sig,
+        token::{Token, TABLE_SHIFT},
+    },
+    stream::meta_data_tables::mdtables::{
+        enums::ClrMethodSemanticsAttr, table_name_2_index, Event, MethodSemantics, Property,
+    },
+    ClrData, Result,
+};
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PropertyInfo {
+    pub token: Token,
+    pub name: String,
+    pub declaring_type: Option<usize>,
+    pub property_type: Option<String>,
+    pub params: Vec<String>,
+    pub getter: Option<Token>,
+    pub setter: Option<Token>,
+    pub others: Vec<Token>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct EventInfo {
+    pub token: Token,
+    pub name: String,
+    pub declaring_type: Option<usize>,
+    pub event_type: Option<String>,
+    pub add_on: Option<Token>,
+    pub remove_on: Option<Token>,
+    pub fire: Option<Token>,
+    pub others: Vec<Token>,
+}
+
+type Semantics = std::collections::HashMap<usize, Vec<(ClrMethodSemanticsAttr, Token)>>;
+
+impl ClrData {
+    fn semantics(&self, association: &'static str) -> Result<Semantics> {
+        let mut res = Semantics::new();
+        let table = match self.md_table("MethodSemantics") {
+            Ok(t) => t,
+            Err(_) => return Ok(res),
+        };
+        let method_table = table_name_2_index("MethodDef")?;
+        for i in 0..table.row_count() {
+            let row = table.row::<MethodSemantics>(i)?;
+            if row.association.table != association {
+                continue;
+            }
+            let method = Token::new((method_table << TABLE_SHIFT) | row.method.row_index);
+            for s in &row.semantics {
+                res.entry(row.association.row_index)
+                    .or_default()
+                    .push((s.clone(), method.clone()));
+            }
+        }
+        Ok(res)
+    }
+
+    pub fn properties(&self) -> Result<Vec<PropertyInfo>> {
+        let mut res = vec![];
+        let table = match self.md_table("Property") {
+            Ok(t) => t,
+            Err(_) => return Ok(res),
+        };
+        let semantics = self.semantics("Property")?;
+        let property_table = table_name_2_index("Property")?;
+        let type_def_table = table_name_2_index("TypeDef")?;
+        for i in 0..table.row_count() {
+            let row = table.row::<Property>(i)?;
+            let signature = parse_property_sig(&row._type).ok();
+            let context = GenericContext {
+                type_def: self
+                    .property_declaring_type(i + 1)
+                    .map(|rid| Token::new((type_def_table << TABLE_SHIFT) | rid)),
+                method: None,
+            };
+            let mut info = PropertyInfo {
+                token: Token::new((property_table << TABLE_SHIFT) | (i + 1)),
+                name: row.name.clone(),
+                declaring_type: self.property_declaring_type(i + 1),
+                property_type: signature
+                    .as_ref()
+                    .map(|s| self.sig_type_name_with(&s.property_type, &context)),
+                params: signature
+                    .iter()
+                    .flat_map(|s| {
+                        s.params
+                            .iter()
+                            .map(|p| self.sig_type_name_with(p, &context))
+                    })
+                    .collect(),
+                getter: None,
+                setter: None,
+                others: vec![],
+            };
+            for (kind, method) in semantics.get(&(i + 1)).into_iter().flatten() {
+                match kind {
+                    ClrMethodSemanticsAttr::Getter => info.getter = Some(method.clone()),
+                    ClrMethodSemanticsAttr::Setter => info.setter = Some(method.clone()),
+                    _ => info.others.push(method.clone()),
+                }
+            }
+            res.push(info);
+        }
+        Ok(res)
+    }
+
+    pub fn events(&self) -> Result<Vec<EventInfo>> {
+        let mut res = vec![];
+        let table = match self.md_table("Event") {
+            Ok(t) => t,
+            Err(_) => return Ok(res),
+        };
+        let semantics = self.semantics("Event")?;
+        let event_table = table_name_2_index("Event")?;
+        for i in 0..table.row_count() {
+            let row = table.row::<Event>(i)?;
+            let event_type = match row.event_type.row_index {
+                0 => None,
+                rid => self
+                    .type_name(&Token::new(
+                        (table_name_2_index(row.event_type.table)? << TABLE_SHIFT) | rid,
+                    ))
+                    .ok(),
+            };
+            let mut info = EventInfo {
+                token: Token::new((event_table << TABLE_SHIFT) | (i + 1)),
+                name: row.name.clone(),
+                declaring_type: self.event_declaring_type(i + 1),
+                event_type,
+                add_on: None,
+                remove_on: None,
+                fire: None,
+                others: vec![],
+            };
+            for (kind, method) in semantics.get(&(i + 1)).into_iter().flatten() {
+                match kind {
+                    ClrMethodSemanticsAttr::AddOn => info.add_on = Some(method.clone()),
+                    ClrMethodSemanticsAttr::RemoveOn => info.remove_on = Some(method.clone()),
+                    ClrMethodSemanticsAttr::Fire => info.fire = Some(method.clone()),
+                    _ => info.others.push(method.clone()),
+                }
+            }
+            res.push(info);
+        }
+        Ok(res)
+    }
+}
@@ -0,0 +1,183 @@
+use crate::{lang::clr::token::Token, ClrHeaderFlags, DnPe, Result};
+use goblin::pe::export::ExportAddressTableEntry;
+
+const VTABLE_FIXUP_SIZE: usize = 8;
+
+#[derive(Debug, Clone, Copy, serde::Serialize, PartialOrd, Ord, PartialEq, Eq)]
+pub enum VTableFixupFlags {
+    Bit32,
+    Bit64,
+    FromUnmanaged,
+    FromUnmanagedRetainAppDomain,
+    CallMostDerived,
+}
+
+impl VTableFixupFlags {
+    pub fn new(value: usize) -> std::collections::BTreeSet<Self> {
+        let mut res = std::collections::BTreeSet::new();
+        if value & 1 != 0 {
+            res.insert(Self::Bit32);
+        }
+        if value & 2 != 0 {
+            res.insert(Self::Bit64);
+        }
+        if value & 4 != 0 {
+            res.insert(Self::FromUnmanaged);
+        }
+        if value & 8 != 0 {
+            res.insert(Self::FromUnmanagedRetainAppDomain);
+        }
+        if value & 0x10 != 0 {
+            res.insert(Self::CallMostDerived);
+        }
+        res
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct VTableFixup {
+    pub rva: u32,
+    pub count: u16,
+    pub raw_flags: u16,
+    pub flags: std::collections::BTreeSet<VTableFixupFlags>,
+    pub slots: Vec<Token>,
+    /// Why the slots could not be read, leaving `slots` empty.
+    pub error: Option<String>,
+}
+
+impl VTableFixup {
+    pub fn slot_size(&self) -> u32 {
+        if self.flags.contains(&VTableFixupFlags::Bit64) {
+            8
+        } else {
+            4
+        }
+    }
+
+    pub fn slot_at(&self, rva: u32) -> Option<&Token> {
+        let index = rva.checked_sub(self.rva)?;
+        if index % self.slot_size() != 0 {
+            return None;
+        }
+        self.slots.get((index / self.slot_size()) as usize)
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ManagedExport {
+    pub ordinal: u32,
+    pub name: Option<String>,
+    pub rva: u32,
+    pub slot_rva: Option<u32>,
+    pub token: Option<Token>,
+}
+
+impl DnPe {
+    pub fn vtable_fixups(&self) -> Result<Vec<VTableFixup>> {
+        let clr_struct = self.clr_struct()?;
+        let mut res = vec![];
+        if clr_struct.v_table_fixups_rva == 0 {
+            return Ok(res);
+        }
+        let table = self.get_vec(
+            &clr_struct.v_table_fixups_rva,
+            &(clr_struct.v_table_fixups_size as usize),
+        )?;
+        for entry in table.chunks_exact(VTABLE_FIXUP_SIZE) {
+            let mut fixup = VTableFixup {
+                rva: u32::from_le_bytes(entry[0..4].try_into()?),
+                count: u16::from_le_bytes(entry[4..6].try_into()?),
+                raw_flags: u16::from_le_bytes(entry[6..8].try_into()?),
+                flags: Default::default(),
+                slots: vec![],
+                error: None,
+            };
+            fixup.flags = VTableFixupFlags::new(fixup.raw_flags as usize);
+            let slot_size = fixup.slot_size() as usize;
+            match self.get_vec(&fixup.rva, &(fixup.count as usize * slot_size)) {
+                Ok(slots) => {
+                    fixup.slots = slots
+                        .chunks_exact(slot_size)
+                        .map(|s| Ok(Token::new(u32::from_le_bytes(s[0..4].try_into()?) as usize)))
+                        .collect::<Result<_>>()?
+                }
+                Err(e) => fixup.error = Some(e.to_string()),
+            }
+            res.push(fixup);
+        }
+        Ok(res)
+    }
+
+    pub fn managed_exports(&self) -> Result<Vec<ManagedExport>> {
+        // exports are still listed, without tokens, when the fixup table is unreadable
+        let fixups = self.vtable_fixups().unwrap_or_default();
+        let pe = self.pe()?;
+        let export_data = match &pe.export_data {
+            Some(e) => e,
+            None => return Ok(vec![]),
+        };
+        let ordinal_base = export_data.export_directory_table.ordinal_base;
+        let mut res = vec![];
+        for (index, entry) in export_data.export_address_table.iter().enumerate() {
+            let rva = match entry {
+                ExportAddressTableEntry::ExportRVA(rva) if *rva != 0 => *rva,
+                _ => continue,
+            };
+            let name = match export_data
+                .export_ordinal_table
+                .iter()
+                .position(|o| *o as usize == index)
+            {
+                Some(i) => export_data
+                    .export_name_pointer_table
+                    .get(i)
+                    .and_then(|rva| self.get_nullterminated_string(rva).ok()),
+                None => None,
+            };
+            let slot_rva = if fixups.iter().any(|f| f.slot_at(rva).is_some()) {
+                Some(rva)
+            } else {
+                self.export_stub_target(rva, pe.is_64, pe.image_base)
+            };
+            res.push(ManagedExport {
+                ordinal: ordinal_base + index as u32,
+                name,
+                rva,
+                slot_rva,
+                token: slot_rva.and_then(|s| fixups.iter().find_map(|f| f.slot_at(s)).cloned()),
+            });
+        }
+        Ok(res)
+    }
+
+    fn export_stub_target(&self, rva: u32, is_64: bool, image_base: u64) -> Option<u32> {
+        let offset = self.offset(rva).ok()?;
+        let stub = self.data.get(offset..)?;
+        match stub {
+            // jmp [slot]; absolute on x86, rip-relative on x64
+            [0xff, 0x25, a, b, c, d, ..] => {
+                let operand = u32::from_le_bytes([*a, *b, *c, *d]);
+                if is_64 {
+                    Some((rva as i64 + 6 + operand as i32 as i64) as u32)
+                } else {
+                    (operand as u64).checked_sub(image_base).map(|r| r as u32)
+                }
+            }
+            // mov rax, [slot]; jmp rax
+            [0x48, 0xa1, rest @ ..] if rest.get(8..10) == Some(&[0xff, 0xe0]) => {
+                u64::from_le_bytes(rest[..8].try_into().ok()?)
+                    .checked_sub(image_base)
+                    .map(|r| r as u32)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn native_entry_point(&self) -> Result<Option<u32>> {
+        let clr_struct = self.clr_struct()?;
+        let flags = ClrHeaderFlags::new(clr_struct.flags as usize);
+        Ok(flags
+            .contains(&ClrHeaderFlags::NativeEntryPiont)
+            .then_some(clr_struct.entry_point_token_or_rva))
+    }
+}
@@ -0,0 +1,403 @@
+use crate::{
+    error::Error,
+    lang::clr::token::{Token, TABLE_SHIFT},
+    stream::meta_data_tables::mdtables::{table_name_2_index, AssemblyRef},
+    DnPe, MetaDataStruct, Result,
+};
+
+const READYTORUN_SIGNATURE: u32 = 0x0052_5452;
+const NATIVE_ARRAY_BLOCK_SIZE: usize = 16;
+const IMAGE_FILE_MACHINE_AMD64: u16 = 0x8664;
+const MACHINE_OS_OVERRIDES: &[u16] = &[0x0000, 0x4644, 0x7b79, 0xadc4, 0x1993];
+
+const SECTION_COMPILER_IDENTIFIER: u32 = 100;
+const SECTION_IMPORT_SECTIONS: u32 = 101;
+const SECTION_RUNTIME_FUNCTIONS: u32 = 102;
+const SECTION_METHOD_DEF_ENTRY_POINTS: u32 = 103;
+const SECTION_AVAILABLE_TYPES: u32 = 108;
+const SECTION_MANIFEST_METADATA: u32 = 112;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+pub enum ReadyToRunFlags {
+    PlatformNeutralSource,
+    SkipTypeValidation,
+    Partial,
+    NonSharedPInvokeStubs,
+    EmbeddedMsil,
+    Component,
+    MultiModuleVersionBubble,
+    UnrelatedR2RCode,
+}
+
+impl ReadyToRunFlags {
+    pub fn new(value: usize) -> std::collections::BTreeSet<Self> {
+        let mut res = std::collections::BTreeSet::new();
+        for (bit, flag) in [
+            (1, Self::PlatformNeutralSource),
+            (2, Self::SkipTypeValidation),
+            (4, Self::Partial),
+            (8, Self::NonSharedPInvokeStubs),
+            (0x10, Self::EmbeddedMsil),
+            (0x20, Self::Component),
+            (0x40, Self::MultiModuleVersionBubble),
+            (0x80, Self::UnrelatedR2RCode),
+        ] {
+            if value & bit != 0 {
+                res.insert(flag);
+            }
+        }
+        res
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ReadyToRunSection {
+    pub section_type: u32,
+    pub name: &'static str,
+    pub rva: u32,
+    pub size: u32,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ReadyToRunImportSection {
+    pub rva: u32,
+    pub size: u32,
+    pub flags: u16,
+    pub section_type: u8,
+    pub entry_size: u8,
+    pub signatures_rva: u32,
+    pub auxiliary_data_rva: u32,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ReadyToRunMethod {
+    pub token: Token,
+    pub runtime_function: usize,
+    pub native_rva: u32,
+    pub has_fixups: bool,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ReadyToRun {
+    pub major_version: u16,
+    pub minor_version: u16,
+    pub flags: std::collections::BTreeSet<ReadyToRunFlags>,
+    pub sections: Vec<ReadyToRunSection>,
+    pub compiler_identifier: Option<String>,
+    pub import_sections: Vec<ReadyToRunImportSection>,
+    pub available_types: Vec<Token>,
+    pub manifest_assembly_refs: Vec<String>,
+    pub methods: Vec<ReadyToRunMethod>,
+}
+
+impl ReadyToRun {
+    pub fn native_code_rva(&self, method: &Token) -> Option<u32> {
+        self.methods
+            .iter()
+            .find(|m| m.token == *method)
+            .map(|m| m.native_rva)
+    }
+
+    fn section(&self, section_type: u32) -> Option<&ReadyToRunSection> {
+        self.sections
+            .iter()
+            .find(|s| s.section_type == section_type)
+    }
+}
+
+fn section_name(section_type: u32) -> &'static str {
+    match section_type {
+        100 => "CompilerIdentifier",
+        101 => "ImportSections",
+        102 => "RuntimeFunctions",
+        103 => "MethodDefEntryPoints",
+        104 => "ExceptionInfo",
+        105 => "DebugInfo",
+        106 => "DelayLoadMethodCallThunks",
+        107 => "AvailableTypesOld",
+        108 => "AvailableTypes",
+        109 => "InstanceMethodEntryPoints",
+        110 => "InliningInfo",
+        111 => "ProfileDataInfo",
+        112 => "ManifestMetadata",
+        113 => "AttributePresence",
+        114 => "InliningInfo2",
+        115 => "ComponentAssemblies",
+        116 => "OwnerCompositeExecutable",
+        117 => "PgoInstrumentationData",
+        118 => "ManifestAssemblyMvids",
+        119 => "CrossModuleInlineInfo",
+        120 => "HotColdMap",
+        121 => "MethodIsGenericMap",
+        122 => "EnclosingTypeMap",
+        123 => "TypeGenericInfoMap",
+        _ => "Unknown",
+    }
+}
+
+struct NativeReader<'a> {
+    data: &'a [u8],
+}
+
+impl NativeReader<'_> {
+    fn byte(&self, offset: usize) -> Result<u32> {
+        self.data
+            .get(offset)
+            .map(|b| *b as u32)
+            .ok_or(Error::NotEnoughData(self.data.len(), offset + 1))
+    }
+
+    fn u16(&self, offset: usize) -> Result<u32> {
+        Ok(self.byte(offset)? | (self.byte(offset + 1)? << 8))
+    }
+
+    fn u32(&self, offset: usize) -> Result<u32> {
+        Ok(self.u16(offset)? | (self.u16(offset + 2)? << 16))
+    }
+
+    fn decode_unsigned(&self, offset: usize) -> Result<(u32, usize)> {
+        let val = self.byte(offset)?;
+        Ok(if val & 1 == 0 {
+            (val >> 1, offset + 1)
+        } else if val & 2 == 0 {
+            ((val >> 2) | (self.byte(offset + 1)? << 6), offset + 2)
+        } else if val & 4 == 0 {
+            (
+                (val >> 3) | (self.byte(offset + 1)? << 5) | (self.byte(offset + 2)? << 13),
+                offset + 3,
+            )
+        } else if val & 8 == 0 {
+            (
+                (val >> 4)
+                    | (self.byte(offset + 1)? << 4)
+                    | (self.byte(offset + 2)? << 12)
+                    | (self.byte(offset + 3)? << 20),
+                offset + 4,
+            )
+        } else if val & 16 == 0 {
+            (self.u32(offset + 1)?, offset + 5)
+        } else {
+            return Err(Error::FormatError(format!(
+                "bad native format integer at 0x{:x}",
+                offset
+            )));
+        })
+    }
+
+    fn decode_signed(&self, offset: usize) -> Result<(i64, usize)> {
+        let (value, next) = self.decode_unsigned(offset)?;
+        let bits = match next - offset {
+            1 => 7,
+            2 => 14,
+            3 => 21,
+            4 => 28,
+            _ => return Ok((value as i32 as i64, next)),
+        };
+        let shift = 64 - bits;
+        Ok((((value as i64) << shift) >> shift, next))
+    }
+
+    fn relative_offset(&self, offset: usize) -> Result<(usize, usize)> {
+        let (delta, next) = self.decode_signed(offset)?;
+        Ok(((offset as i64 + delta) as usize, next))
+    }
+
+    fn array_element(&self, array: usize, index: usize) -> Result<Option<usize>> {
+        let (header, base) = self.decode_unsigned(array)?;
+        if index >= (header >> 2) as usize {
+            return Ok(None);
+        }
+        let block = index / NATIVE_ARRAY_BLOCK_SIZE;
+        let mut offset = base
+            + match header & 3 {
+                0 => self.byte(base + block)?,
+                1 => self.u16(base + 2 * block)?,
+                _ => self.u32(base + 4 * block)?,
+            } as usize;
+        let mut bit = NATIVE_ARRAY_BLOCK_SIZE >> 1;
+        while bit > 0 {
+            let (val, next) = self.decode_unsigned(offset)?;
+            if index & bit != 0 && val & 2 != 0 {
+                offset += (val >> 2) as usize;
+            } else if index & bit == 0 && val & 1 != 0 {
+                offset = next;
+            } else if val & 3 == 0 && (val >> 2) as usize == index & (NATIVE_ARRAY_BLOCK_SIZE - 1) {
+                return Ok(Some(next));
+            } else {
+                return Ok(None);
+            }
+            bit >>= 1;
+        }
+        Ok(Some(offset))
+    }
+
+    fn hashtable_entries(&self, table: usize) -> Result<Vec<usize>> {
+        let header = self.byte(table)?;
+        let base = table + 1;
+        let buckets = 1usize << (header >> 2);
+        let bucket_offset = |bucket: usize| -> Result<usize> {
+            Ok(base
+                + match header & 3 {
+                    0 => self.byte(base + bucket)?,
+                    1 => self.u16(base + 2 * bucket)?,
+                    _ => self.u32(base + 4 * bucket)?,
+                } as usize)
+        };
+        let mut res = vec![];
+        for bucket in 0..buckets {
+            let mut offset = bucket_offset(bucket)?;
+            let end = bucket_offset(bucket + 1)?;
+            while offset < end {
+                let (entry, next) = self.relative_offset(offset + 1)?;
+                res.push(entry);
+                offset = next;
+            }
+        }
+        Ok(res)
+    }
+}
+
+impl DnPe {
+    pub fn ready_to_run(&self) -> Result<Option<ReadyToRun>> {
+        let clr_struct = self.clr_struct()?;
+        if clr_struct.managed_native_header_rva == 0 || clr_struct.managed_native_header_size == 0 {
+            return Ok(None);
+        }
+        let header = self.offset(clr_struct.managed_native_header_rva)?;
+        let reader = NativeReader { data: &self.data };
+        if reader.u32(header)? != READYTORUN_SIGNATURE {
+            return Ok(None);
+        }
+        let mut res = ReadyToRun {
+            major_version: reader.u16(header + 4)? as u16,
+            minor_version: reader.u16(header + 6)? as u16,
+            flags: ReadyToRunFlags::new(reader.u32(header + 8)? as usize),
+            sections: vec![],
+            compiler_identifier: None,
+            import_sections: vec![],
+            available_types: vec![],
+            manifest_assembly_refs: vec![],
+            methods: vec![],
+        };
+        for i in 0..reader.u32(header + 12)? as usize {
+            let entry = header + 16 + i * 12;
+            let section_type = reader.u32(entry)?;
+            res.sections.push(ReadyToRunSection {
+                section_type,
+                name: section_name(section_type),
+                rva: reader.u32(entry + 4)?,
+                size: reader.u32(entry + 8)?,
+            });
+        }
+
+        if let Some(s) = res.section(SECTION_COMPILER_IDENTIFIER) {
+            let start = self.offset(s.rva)?;
+            let bytes =
+                self.data
+                    .get(start..start + s.size as usize)
+                    .ok_or(Error::NotEnoughData(
+                        self.data.len(),
+                        start + s.size as usize,
+                    ))?;
+            let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+            res.compiler_identifier = Some(String::from_utf8_lossy(&bytes[..end]).to_string());
+        }
+        if let Some(s) = res.section(SECTION_IMPORT_SECTIONS) {
+            let start = self.offset(s.rva)?;
+            let mut sections = vec![];
+            for entry in (start..start + s.size as usize).step_by(20) {
+                sections.push(ReadyToRunImportSection {
+                    rva: reader.u32(entry)?,
+                    size: reader.u32(entry + 4)?,
+                    flags: reader.u16(entry + 8)? as u16,
+                    section_type: reader.byte(entry + 10)? as u8,
+                    entry_size: reader.byte(entry + 11)? as u8,
+                    signatures_rva: reader.u32(entry + 12)?,
+                    auxiliary_data_rva: reader.u32(entry + 16)?,
+                });
+            }
+            res.import_sections = sections;
+        }
+        if let Some(s) = res.section(SECTION_AVAILABLE_TYPES) {
+            let type_def = table_name_2_index("TypeDef")?;
+            let exported_type = table_name_2_index("ExportedType")?;
+            let mut types = vec![];
+            for entry in reader.hashtable_entries(self.offset(s.rva)?)? {
+                let (rid, _) = reader.decode_unsigned(entry)?;
+                let table = if rid & 1 != 0 {
+                    exported_type
+                } else {
+                    type_def
+                };
+                types.push(Token::new((table << TABLE_SHIFT) | (rid >> 1) as usize));
+            }
+            res.available_types = types;
+        }
+        if let Some(s) = res.section(SECTION_MANIFEST_METADATA) {
+            let metadata_struct: MetaDataStruct = self.get_data(&s.rva, &(s.size as usize))?;
+            let metadata = self.new_metadata(&s.rva, metadata_struct)?;
+            if let Ok(table) = metadata.md_table("AssemblyRef") {
+                for i in 0..table.row_count() {
+                    res.manifest_assembly_refs
+                        .push(table.row::<AssemblyRef>(i)?.name.clone());
+                }
+            }
+        }
+        res.methods = self.ready_to_run_methods(&res, &reader)?;
+        Ok(Some(res))
+    }
+
+    fn ready_to_run_methods(
+        &self,
+        r2r: &ReadyToRun,
+        reader: &NativeReader,
+    ) -> Result<Vec<ReadyToRunMethod>> {
+        let mut res = vec![];
+        let (entry_points, runtime_functions) = match (
+            r2r.section(SECTION_METHOD_DEF_ENTRY_POINTS),
+            r2r.section(SECTION_RUNTIME_FUNCTIONS),
+        ) {
+            (Some(e), Some(r)) => (self.offset(e.rva)?, r),
+            _ => return Ok(res),
+        };
+        let machine = self.pe()?.header.coff_header.machine;
+        let runtime_function_size = if MACHINE_OS_OVERRIDES
+            .iter()
+            .any(|os| machine ^ os == IMAGE_FILE_MACHINE_AMD64)
+        {
+            12
+        } else {
+            8
+        };
+        let functions = self.offset(runtime_functions.rva)?;
+        let function_count = runtime_functions.size as usize / runtime_function_size;
+        let method_def = table_name_2_index("MethodDef")?;
+        let method_count = self
+            .net()?
+            .md_table("MethodDef")
+            .map(|t| t.row_count())
+            .unwrap_or_default();
+        for rid in 1..=method_count {
+            let offset = match reader.array_element(entry_points, rid - 1)? {
+                Some(o) => o,
+                None => continue,
+            };
+            let (id, _) = reader.decode_unsigned(offset)?;
+            let (runtime_function, has_fixups) = if id & 1 != 0 {
+                ((id >> 2) as usize, true)
+            } else {
+                ((id >> 1) as usize, false)
+            };
+            if runtime_function >= function_count {
+                continue;
+            }
+            res.push(ReadyToRunMethod {
+                token: Token::new((method_def << TABLE_SHIFT) | rid),
+                runtime_function,
+                native_rva: reader.u32(functions + runtime_function * runtime_function_size)?,
+                has_fixups,
+            });
+        }
+        Ok(res)
+    }
+}
@@ -0,0 +1,373 @@
+use crate::{
+    error::Error,
+    stream::meta_data_tables::mdtables::{AssemblyRef, File, ManifestResource},
+    Result,
+};
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Resource {
+    pub name: String,
+    pub offset: u32,
+    pub public: bool,
+    pub implementation: Option<String>,
+    pub size: usize,
+    #[serde(skip_serializing)]
+    pub data: Vec<u8>,
+    /// Why the resource could not be resolved or read, leaving `data` empty.
+    pub error: Option<String>,
+}
+
+impl crate::DnPe {
+    pub fn resources(&self) -> Result<Vec<Resource>> {
+        let net = self.net()?;
+        let table = match net.md_table("ManifestResource") {
+            Ok(t) => t,
+            Err(_) => return Ok(vec![]),
+        };
+        let clr_struct = self.clr_struct()?;
+        let mut res = vec![];
+        for i in 0..table.row_count() {
+            let row = table.row::<ManifestResource>(i)?;
+            let (table_name, rid) = (row.implementation.table, row.implementation.row_index);
+            let implementation = match (table_name, rid) {
+                (_, 0) => Ok(None),
+                ("File", rid) => net
+                    .md_table("File")
+                    .and_then(|t| Ok(Some(t.row::<File>(rid - 1)?.name.clone()))),
+                ("AssemblyRef", rid) => net
+                    .md_table("AssemblyRef")
+                    .and_then(|t| Ok(Some(t.row::<AssemblyRef>(rid - 1)?.name.clone()))),
+                (table, rid) => Ok(Some(format!("{}#{}", table, rid))),
+            };
+            let (implementation, data) = match implementation {
+                Ok(None) => (None, self.embedded_resource(&clr_struct, row.offset)),
+                Ok(Some(name)) => (Some(name), Ok(vec![])),
+                Err(e) => (Some(format!("{}#{}", table_name, rid)), Err(e)),
+            };
+            let (data, error) = match data {
+                Ok(data) => (data, None),
+                Err(e) => (vec![], Some(e.to_string())),
+            };
+            res.push(Resource {
+                name: row.name.clone(),
+                offset: row.offset,
+                public: row.raw_flags & 7 == 1,
+                implementation,
+                size: data.len(),
+                data,
+                error,
+            });
+        }
+        Ok(res)
+    }
+
+    fn embedded_resource(&self, clr_struct: &crate::ClrStruct, offset: u32) -> Result<Vec<u8>> {
+        if offset >= clr_struct.resources_size {
+            return Err(Error::FormatError(format!(
+                "resource offset 0x{:x} outside of resources directory",
+                offset
+            )));
+        }
+        let exceeds = || {
+            Error::FormatError(format!(
+                "resource at 0x{:x} exceeds resources directory",
+                offset
+            ))
+        };
+        let rva = clr_struct
+            .resources_rva
+            .checked_add(offset)
+            .ok_or_else(exceeds)?;
+        let size = self.get_dword_at_rva(&rva)?;
+        if offset as u64 + 4 + size as u64 > clr_struct.resources_size as u64 {
+            return Err(exceeds());
+        }
+        self.get_vec(&(rva.checked_add(4).ok_or_else(exceeds)?), &(size as usize))
+    }
+}
+
+const RESOURCE_MANAGER_MAGIC: u32 = 0xbeef_cace;
+const USER_TYPES: usize = 0x40;
+
+/// A value of a `.resources` entry. Primitive values are kept in their
+/// printed form.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub enum ResourceValue {
+    Null,
+    String(String),
+    Primitive(String),
+    Bytes(Vec<u8>),
+    /// An object of a user type, left in its serialized form.
+    Serialized(Vec<u8>),
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ResourceEntry {
+    pub name: String,
+    pub type_name: String,
+    /// Offset of the value, starting with its type code, in the resource data.
+    pub offset: usize,
+    pub value: Option<ResourceValue>,
+    /// Why the value could not be read, leaving `value` empty.
+    pub error: Option<String>,
+}
+
+struct ResourceReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ResourceReader<'a> {
+    fn bytes(&mut self, size: usize) -> Result<&'a [u8]> {
+        let res = self
+            .pos
+            .checked_add(size)
+            .and_then(|end| self.data.get(self.pos..end))
+            .ok_or(Error::NotEnoughData(
+                self.data.len(),
+                self.pos.saturating_add(size),
+            ))?;
+        self.pos += size;
+        Ok(res)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+
+    fn usize(&mut self) -> Result<usize> {
+        Ok(self.u32()? as usize)
+    }
+
+    /// BinaryWriter's 7-bit encoded length.
+    fn encoded_usize(&mut self) -> Result<usize> {
+        let mut res = 0;
+        for shift in (0..35).step_by(7) {
+            let b = self.u8()?;
+            res |= ((b & 0x7f) as usize) << shift;
+            if b & 0x80 == 0 {
+                return Ok(res);
+            }
+        }
+        Err(Error::FormatError(format!(
+            "bad 7-bit encoded integer before 0x{:x}",
+            self.pos
+        )))
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let len = self.encoded_usize()?;
+        Ok(String::from_utf8(self.bytes(len)?.to_vec())?)
+    }
+
+    fn utf16_string(&mut self) -> Result<String> {
+        let len = self.encoded_usize()?;
+        let units: Vec<u16> = self
+            .bytes(len)?
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        Ok(String::from_utf16_lossy(&units))
+    }
+
+    fn value(&mut self, type_name: &str) -> Result<ResourceValue> {
+        Ok(match type_name {
+            "" => ResourceValue::Null,
+            "System.String" => ResourceValue::String(self.string()?),
+            "System.Boolean" => ResourceValue::Primitive((self.u8()? != 0).to_string()),
+            "System.Char" => {
+                ResourceValue::Primitive(String::from_utf16_lossy(&[self.u16()?]).to_string())
+            }
+            "System.Byte" => ResourceValue::Primitive(self.u8()?.to_string()),
+            "System.SByte" => ResourceValue::Primitive((self.u8()? as i8).to_string()),
+            "System.Int16" => ResourceValue::Primitive((self.u16()? as i16).to_string()),
+            "System.UInt16" => ResourceValue::Primitive(self.u16()?.to_string()),
+            "System.Int32" => ResourceValue::Primitive((self.u32()? as i32).to_string()),
+            "System.UInt32" => ResourceValue::Primitive(self.u32()?.to_string()),
+            "System.Int64" | "System.DateTime" | "System.TimeSpan" => {
+                ResourceValue::Primitive((self.u64()? as i64).to_string())
+            }
+            "System.UInt64" => ResourceValue::Primitive(self.u64()?.to_string()),
+            "System.Single" => ResourceValue::Primitive(f32::from_bits(self.u32()?).to_string()),
+            "System.Double" => ResourceValue::Primitive(f64::from_bits(self.u64()?).to_string()),
+            "System.Decimal" => ResourceValue::Bytes(self.bytes(16)?.to_vec()),
+            "System.Byte[]" | "System.IO.Stream" => {
+                let size = self.usize()?;
+                ResourceValue::Bytes(self.bytes(size)?.to_vec())
+            }
+            _ => {
+                return Err(Error::FormatError(format!(
+                    "{} is not a primitive resource type",
+                    type_name
+                )))
+            }
+        })
+    }
+}
+
+/// Names of the predefined type codes of a version 2 resource set.
+fn type_code_name(code: usize) -> Option<&'static str> {
+    Some(match code {
+        0x00 => "",
+        0x01 => "System.String",
+        0x02 => "System.Boolean",
+        0x03 => "System.Char",
+        0x04 => "System.Byte",
+        0x05 => "System.SByte",
+        0x06 => "System.Int16",
+        0x07 => "System.UInt16",
+        0x08 => "System.Int32",
+        0x09 => "System.UInt32",
+        0x0a => "System.Int64",
+        0x0b => "System.UInt64",
+        0x0c => "System.Single",
+        0x0d => "System.Double",
+        0x0e => "System.Decimal",
+        0x0f => "System.DateTime",
+        0x10 => "System.TimeSpan",
+        0x20 => "System.Byte[]",
+        0x21 => "System.IO.Stream",
+        _ => return None,
+    })
+}
+
+/// Types a version 1 resource set stores natively, any other is serialized.
+const V1_NATIVE_TYPES: &[&str] = &[
+    "System.String",
+    "System.Int32",
+    "System.Byte",
+    "System.SByte",
+    "System.Int16",
+    "System.Int64",
+    "System.UInt16",
+    "System.UInt32",
+    "System.UInt64",
+    "System.Single",
+    "System.Double",
+    "System.DateTime",
+    "System.TimeSpan",
+    "System.Decimal",
+];
+
+/// Reads the type of the value at the reader's position, returning its name
+/// and whether it is stored natively rather than serialized.
+fn entry_type(
+    reader: &mut ResourceReader,
+    version: u32,
+    types: &[String],
+) -> Result<(String, bool)> {
+    let code = reader.encoded_usize()?;
+    let unknown = || Error::FormatError(format!("unknown resource type code 0x{:x}", code));
+    if version == 1 {
+        // a version 1 set indexes the type table directly, -1 is null
+        if code == u32::MAX as usize {
+            return Ok((String::new(), true));
+        }
+        let type_name = types.get(code).ok_or_else(unknown)?;
+        let type_name = type_name.split(',').next().unwrap_or_default().to_string();
+        let native = V1_NATIVE_TYPES.contains(&type_name.as_str());
+        Ok((type_name, native))
+    } else if code >= USER_TYPES {
+        let type_name = types.get(code - USER_TYPES).ok_or_else(unknown)?;
+        Ok((type_name.clone(), false))
+    } else {
+        Ok((type_code_name(code).ok_or_else(unknown)?.to_string(), true))
+    }
+}
+
+/// Decodes a `.resources` file as written by ResourceWriter: a resource
+/// manager header followed by a runtime resource set of version 1 or 2.
+pub fn parse_resource_set(data: &[u8]) -> Result<Vec<ResourceEntry>> {
+    let mut reader = ResourceReader { data, pos: 0 };
+    let magic = reader.u32()?;
+    if magic != RESOURCE_MANAGER_MAGIC {
+        return Err(Error::FormatError(format!(
+            "bad resource manager magic 0x{:08x}",
+            magic
+        )));
+    }
+    // the reader and resource set class names of the header are skipped
+    reader.u32()?;
+    let header_size = reader.usize()?;
+    reader.bytes(header_size)?;
+    let version = reader.u32()?;
+    if version != 1 && version != 2 {
+        return Err(Error::FormatError(format!(
+            "unsupported resource set version {}",
+            version
+        )));
+    }
+    let count = reader.usize()?;
+    let type_count = reader.usize()?;
+    let mut types = vec![];
+    for _ in 0..type_count {
+        types.push(reader.string()?);
+    }
+    reader.bytes((8 - reader.pos % 8) % 8)?;
+    // name hashes
+    reader.bytes(count.saturating_mul(4))?;
+    let mut name_positions = vec![];
+    for _ in 0..count {
+        name_positions.push(reader.usize()?);
+    }
+    let data_section = reader.usize()?;
+    let name_section = reader.pos;
+
+    let mut entries = vec![];
+    for position in name_positions {
+        reader.pos = name_section.saturating_add(position);
+        let name = reader.utf16_string()?;
+        let offset = data_section.saturating_add(reader.usize()?);
+        entries.push((name, offset));
+    }
+    let mut ends: Vec<usize> = entries.iter().map(|(_, offset)| *offset).collect();
+    ends.push(data.len());
+    ends.sort();
+
+    let mut res = vec![];
+    for (name, offset) in entries {
+        reader.pos = offset;
+        let end = ends
+            .iter()
+            .find(|e| **e > offset)
+            .copied()
+            .unwrap_or(data.len());
+        let (type_name, value) = match entry_type(&mut reader, version, &types) {
+            Ok((type_name, native)) => {
+                let value = if native {
+                    reader.value(&type_name)
+                } else {
+                    reader
+                        .bytes(end.saturating_sub(reader.pos))
+                        .map(|b| ResourceValue::Serialized(b.to_vec()))
+                };
+                (type_name, value)
+            }
+            Err(e) => (String::new(), Err(e)),
+        };
+        let (value, error) = match value {
+            Ok(v) => (Some(v), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        res.push(ResourceEntry {
+            name,
+            type_name,
+            offset,
+            value,
+            error,
+        });
+    }
+    Ok(res)
+}
@@ -0,0 +1,242 @@
+use crate::{
+    lang::cil::instruction::Operand,
+    resources::{parse_resource_set, ResourceValue},
+    stream::{meta_data_tables::mdtables::CustomAttribute, Stream},
+    DnPe, Result,
+};
+
+pub const IOC_PATTERNS: &[(&str, &str)] = &[
+    ("url", r#"(?i)\b(?:https?|ftp)://[^\s"'<>]+"#),
+    (
+        "ipv4",
+        r"\b(?:(?:25[0-5]|2[0-4]\d|1?\d?\d)\.){3}(?:25[0-5]|2[0-4]\d|1?\d?\d)\b",
+    ),
+    (
+        "registry",
+        r#"(?i)\b(?:HKEY_[A-Z_]+|HK(?:LM|CU|CR|U|CC))\\[^\s"']+"#,
+    ),
+];
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Location {
+    UserString {
+        offset: usize,
+    },
+    Ldstr {
+        offset: usize,
+        token: usize,
+        method: String,
+        il_offset: usize,
+    },
+    String {
+        offset: usize,
+    },
+    CustomAttribute {
+        row: usize,
+        parent: String,
+    },
+    Resource {
+        name: String,
+        offset: usize,
+    },
+    ResourceEntry {
+        resource: String,
+        entry: String,
+        offset: usize,
+    },
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SearchMatch {
+    pub pattern: String,
+    pub value: String,
+    pub matched: String,
+    pub location: Location,
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    pub user_strings: bool,
+    pub strings: bool,
+    pub attributes: bool,
+    pub resources: bool,
+    /// Shortest run of printable characters picked out of raw resource data:
+    /// resources that are not `.resources` sets, and byte or serialized
+    /// values inside those that are.
+    pub min_resource_string: usize,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            user_strings: true,
+            strings: true,
+            attributes: true,
+            resources: true,
+            min_resource_string: 4,
+        }
+    }
+}
+
+fn printable(b: u8) -> bool {
+    (0x20..0x7f).contains(&b) || b == b'\t'
+}
+
+fn resource_strings(data: &[u8], min_len: usize) -> Vec<(usize, String)> {
+    let mut res = vec![];
+    let mut start = 0;
+    for (i, b) in data.iter().chain(std::iter::once(&0)).enumerate() {
+        if !printable(*b) {
+            if i - start >= min_len {
+                res.push((start, String::from_utf8_lossy(&data[start..i]).to_string()));
+            }
+            start = i + 1;
+        }
+    }
+    for parity in 0..2 {
+        let mut start = parity;
+        let mut i = parity;
+        while i < data.len() + 2 {
+            let wide = data
+                .get(i..i + 2)
+                .is_some_and(|c| printable(c[0]) && c[1] == 0);
+            if !wide {
+                if (i - start) / 2 >= min_len {
+                    let text: String = data[start..i]
+                        .iter()
+                        .step_by(2)
+                        .map(|b| *b as char)
+                        .collect();
+                    res.push((start, text));
+                }
+                start = i + 2;
+            }
+            i += 2;
+        }
+    }
+    res.sort_by_key(|(offset, _)| *offset);
+    res
+}
+
+impl DnPe {
+    pub fn search(
+        &self,
+        patterns: &[regex::Regex],
+        options: &SearchOptions,
+    ) -> Result<Vec<SearchMatch>> {
+        let net = self.net()?;
+        let mut candidates: Vec<(String, Location)> = vec![];
+        for s in net.metadata.streams.values() {
+            match &s.stream {
+                Stream::UserStringHeap(heap) if options.user_strings => {
+                    let mut sites: std::collections::HashMap<usize, Vec<Location>> =
+                        std::collections::HashMap::new();
+                    for f in net.functions() {
+                        for insn in &f.instructions {
+                            if let Operand::StringToken(t) = &insn.operand {
+                                sites.entry(t.rid()).or_default().push(Location::Ldstr {
+                                    offset: t.rid(),
+                                    token: f.method_token.value,
+                                    method: f.full_name(),
+                                    il_offset: insn.offset - f.code_base(),
+                                });
+                            }
+                        }
+                    }
+                    for (offset, value, _) in heap.iter() {
+                        match sites.remove(&offset) {
+                            Some(locations) => {
+                                candidates.extend(locations.into_iter().map(|l| (value.clone(), l)))
+                            }
+                            None => candidates.push((value, Location::UserString { offset })),
+                        }
+                    }
+                }
+                Stream::StringHeap(heap) if options.strings => {
+                    for (offset, value) in heap.iter() {
+                        candidates.push((value, Location::String { offset }));
+                    }
+                }
+                _ => {}
+            }
+        }
+        if options.attributes {
+            if let Ok(table) = net.md_table("CustomAttribute") {
+                for i in 0..table.row_count() {
+                    let row = table.row::<CustomAttribute>(i)?;
+                    let value = match net.custom_attribute_value(row) {
+                        Ok(v) => v,
+                        Err(_) => continue,
+                    };
+                    let parent = format!("{}#{}", row.parent.table, row.parent.row_index);
+                    for s in value.strings() {
+                        candidates.push((
+                            s.to_string(),
+                            Location::CustomAttribute {
+                                row: i + 1,
+                                parent: parent.clone(),
+                            },
+                        ));
+                    }
+                }
+            }
+        }
+        if options.resources {
+            for r in self.resources()? {
+                let entries = match parse_resource_set(&r.data) {
+                    Ok(entries) => entries,
+                    Err(_) => {
+                        for (offset, value) in
+                            resource_strings(&r.data, options.min_resource_string)
+                        {
+                            candidates.push((
+                                value,
+                                Location::Resource {
+                                    name: r.name.clone(),
+                                    offset,
+                                },
+                            ));
+                        }
+                        continue;
+                    }
+                };
+                for e in entries {
+                    let location = Location::ResourceEntry {
+                        resource: r.name.clone(),
+                        entry: e.name.clone(),
+                        offset: e.offset,
+                    };
+                    let values = match e.value {
+                        Some(ResourceValue::String(value)) => vec![value],
+                        Some(ResourceValue::Bytes(data))
+                        | Some(ResourceValue::Serialized(data)) => {
+                            resource_strings(&data, options.min_resource_string)
+                                .into_iter()
+                                .map(|(_, value)| value)
+                                .collect()
+                        }
+                        _ => vec![],
+                    };
+                    candidates.push((e.name, location.clone()));
+                    candidates.extend(values.into_iter().map(|v| (v, location.clone())));
+                }
+            }
+        }
+
+        let mut res = vec![];
+        for (value, location) in candidates {
+            for pattern in patterns {
+                for m in pattern.find_iter(&value) {
+                    res.push(SearchMatch {
+                        pattern: pattern.as_str().to_string(),
+                        value: value.clone(),
+                        matched: m.as_str().to_string(),
+                        location: location.clone(),
+                    });
+                }
+            }
+        }
+        Ok(res)
+    }
+}
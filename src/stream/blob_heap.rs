@@ -12,8 +12,8 @@ impl BlobHeap {
             return Err(Error::BlobHeapReadOutOfBound(index, self.data.len()));
         }
         let (data_length, length_size) =
-            crate::utils::read_compressed_usize(&self.data[index..index + 4])?;
-        if index + length_size + data_length >= self.data.len() + 1 {
+            crate::utils::read_compressed_usize(&self.data[index..self.data.len().min(index + 4)])?;
+        if index + length_size + data_length > self.data.len() {
             return Err(Error::BlobHeapReadOutOfBound(
                 index + data_length + length_size,
                 self.data.len(),
@@ -21,6 +21,44 @@ impl BlobHeap {
         }
         Ok(self.data[index + length_size..index + length_size + data_length].to_vec())
     }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn iter(&self) -> BlobHeapIter<'_> {
+        BlobHeapIter {
+            data: &self.data,
+            offset: 0,
+        }
+    }
+}
+
+pub struct BlobHeapIter<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Iterator for BlobHeapIter<'a> {
+    type Item = (usize, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.offset < self.data.len() {
+            let start = self.offset;
+            let (data_length, length_size) =
+                crate::utils::read_compressed_usize(&self.data[start..]).ok()?;
+            let end = start + length_size + data_length;
+            if end > self.data.len() {
+                self.offset = self.data.len();
+                return None;
+            }
+            self.offset = end;
+            if data_length > 0 {
+                return Some((start, &self.data[start + length_size..end]));
+            }
+        }
+        None
+    }
 }
 
 impl crate::DnPe {
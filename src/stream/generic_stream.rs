@@ -2,8 +2,14 @@ use crate::Result;
 
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct GenericStream {
-    //#[serde(skip_serializing)]
-    //data: Vec<u8>
+    #[serde(skip_serializing)]
+    data: Vec<u8>,
+}
+
+impl GenericStream {
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
 }
 
 impl crate::DnPe {
@@ -13,10 +19,10 @@ impl crate::DnPe {
         _stream_offset: &u32,
         _stream_size: &usize,
         _stream_name: &str,
-        _stream_data: Vec<u8>,
+        stream_data: Vec<u8>,
     ) -> Result<super::Stream> {
-        Ok(super::Stream::GenericStream(GenericStream{
-            //data: stream_data
+        Ok(super::Stream::GenericStream(GenericStream {
+            data: stream_data,
         }))
     }
 }
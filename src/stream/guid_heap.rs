@@ -19,6 +19,17 @@ impl GuidHeap {
         let guid_buf = &self.data[offset..offset + size];
         Ok(uuid::Uuid::from_slice(guid_buf)?)
     }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, uuid::Uuid)> + '_ {
+        self.data
+            .chunks_exact(16)
+            .enumerate()
+            .filter_map(|(i, c)| Some((i + 1, uuid::Uuid::from_slice(c).ok()?)))
+    }
 }
 
 impl crate::DnPe {
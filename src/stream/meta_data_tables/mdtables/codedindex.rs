@@ -7,15 +7,11 @@ pub fn clr_coded_index_struct_size(
 ) -> usize {
     let mut max_index = 0;
     for name in table_names {
-        let table_index = if let Ok(s) = super::table_name_2_index(name) {
-            s
-        } else {
-            0
-        };
+        let table_index = super::table_name_2_index(name).unwrap_or_default();
         let table_rowcnt = tables_row_counts[table_index];
         max_index = std::cmp::max(max_index, table_rowcnt);
     }
-    if max_index <= 1 << (16 - tag_bits) {
+    if max_index < 1 << (16 - tag_bits) {
         2
     } else {
         4
@@ -27,24 +23,37 @@ pub trait CodedIndex {
     fn set_table(&mut self, value: &'static str);
     fn get_table_name(&self, index: usize) -> Result<&'static str>;
     fn get_tag_bits(&self) -> usize;
+    fn get_table_names(&self) -> &[&'static str];
     fn table(&self) -> &'static str;
     fn row_index(&self) -> usize;
     fn set(
         &mut self,
         value: &[u8],
-        tables: &std::collections::BTreeMap<usize, super::MetaDataTable>,
+        _tables: &std::collections::BTreeMap<usize, super::MetaDataTable>,
     ) -> Result<()> {
         let value = crate::utils::read_usize(value)?;
-        let table_name = self.get_table_name(value & ((1 << self.get_tag_bits()) - 1))?;
+        let table_name = self.get_table_name(value & ((1 << self.get_tag_bits()) - 1));
         self.set_row_index(value >> self.get_tag_bits());
-        for t in tables.values() {
-            if t.table.name() != table_name {
-                continue;
-            }
-            self.set_table(table_name);
-            return Ok(());
+        // the tag of a null index carries no meaning, and an index into a
+        // table the stream does not define is kept as is so that anomalies()
+        // can report it instead of the load failing
+        if self.row_index() == 0 {
+            self.set_table(table_name.unwrap_or_default());
+        } else {
+            self.set_table(table_name?);
         }
-        Err(Error::CodedIndexWithUndefinedTable(table_name.to_string()))
+        Ok(())
+    }
+    fn value(&self) -> Result<usize> {
+        let tag = if self.table().is_empty() {
+            0
+        } else {
+            self.get_table_names()
+                .iter()
+                .position(|n| *n == self.table())
+                .ok_or_else(|| Error::CodedIndexWithUndefinedTable(self.table().to_string()))?
+        };
+        Ok((self.row_index() << self.get_tag_bits()) | tag)
     }
 }
 
@@ -72,6 +81,15 @@ impl SimpleCodedIndex {
         res.set(value, tables)?;
         Ok(res)
     }
+
+    pub fn list_start(table: &'static str, value: &[u8]) -> Result<SimpleCodedIndex> {
+        Ok(SimpleCodedIndex {
+            tag_bits: 0,
+            table_names: vec![table],
+            row_index: crate::utils::read_usize(value)?,
+            table,
+        })
+    }
 }
 
 impl CodedIndex for SimpleCodedIndex {
@@ -88,11 +106,18 @@ impl CodedIndex for SimpleCodedIndex {
         self.table = value;
     }
     fn get_table_name(&self, index: usize) -> Result<&'static str> {
-        Ok(self.table_names[index])
+        self.table_names
+            .get(index)
+            .copied()
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| Error::CodedIndexWithUndefinedTable(format!("tag {}", index)))
     }
     fn get_tag_bits(&self) -> usize {
         self.tag_bits
     }
+    fn get_table_names(&self) -> &[&'static str] {
+        &self.table_names
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -117,11 +142,18 @@ impl CodedIndex for ResolutionScope {
         self.table = value;
     }
     fn get_table_name(&self, index: usize) -> Result<&'static str> {
-        Ok(self.table_names[index])
+        self.table_names
+            .get(index)
+            .copied()
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| Error::CodedIndexWithUndefinedTable(format!("tag {}", index)))
     }
     fn get_tag_bits(&self) -> usize {
         self.tag_bits
     }
+    fn get_table_names(&self) -> &[&'static str] {
+        &self.table_names
+    }
 }
 
 impl Default for ResolutionScope {
@@ -157,11 +189,18 @@ impl CodedIndex for TypeDefOrRef {
         self.table = value;
     }
     fn get_table_name(&self, index: usize) -> Result<&'static str> {
-        Ok(self.table_names[index])
+        self.table_names
+            .get(index)
+            .copied()
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| Error::CodedIndexWithUndefinedTable(format!("tag {}", index)))
     }
     fn get_tag_bits(&self) -> usize {
         self.tag_bits
     }
+    fn get_table_names(&self) -> &[&'static str] {
+        &self.table_names
+    }
 }
 
 impl Default for TypeDefOrRef {
@@ -197,11 +236,18 @@ impl CodedIndex for MemberRefParent {
         self.table = value;
     }
     fn get_table_name(&self, index: usize) -> Result<&'static str> {
-        Ok(self.table_names[index])
+        self.table_names
+            .get(index)
+            .copied()
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| Error::CodedIndexWithUndefinedTable(format!("tag {}", index)))
     }
     fn get_tag_bits(&self) -> usize {
         self.tag_bits
     }
+    fn get_table_names(&self) -> &[&'static str] {
+        &self.table_names
+    }
 }
 
 impl Default for MemberRefParent {
@@ -237,11 +283,18 @@ impl CodedIndex for HasConstant {
         self.table = value;
     }
     fn get_table_name(&self, index: usize) -> Result<&'static str> {
-        Ok(self.table_names[index])
+        self.table_names
+            .get(index)
+            .copied()
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| Error::CodedIndexWithUndefinedTable(format!("tag {}", index)))
     }
     fn get_tag_bits(&self) -> usize {
         self.tag_bits
     }
+    fn get_table_names(&self) -> &[&'static str] {
+        &self.table_names
+    }
 }
 
 impl Default for HasConstant {
@@ -277,11 +330,18 @@ impl CodedIndex for HasCustomAttribute {
         self.table = value;
     }
     fn get_table_name(&self, index: usize) -> Result<&'static str> {
-        Ok(self.table_names[index])
+        self.table_names
+            .get(index)
+            .copied()
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| Error::CodedIndexWithUndefinedTable(format!("tag {}", index)))
     }
     fn get_tag_bits(&self) -> usize {
         self.tag_bits
     }
+    fn get_table_names(&self) -> &[&'static str] {
+        &self.table_names
+    }
 }
 
 impl Default for HasCustomAttribute {
@@ -310,6 +370,7 @@ impl Default for HasCustomAttribute {
                 "ManifestResource",
                 "GenericParam",
                 "GenericParamConstraint",
+                "MethodSpec",
             ],
             row_index: 0,
             table: "",
@@ -339,11 +400,18 @@ impl CodedIndex for CustomAttributeType {
         self.table = value;
     }
     fn get_table_name(&self, index: usize) -> Result<&'static str> {
-        Ok(self.table_names[index])
+        self.table_names
+            .get(index)
+            .copied()
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| Error::CodedIndexWithUndefinedTable(format!("tag {}", index)))
     }
     fn get_tag_bits(&self) -> usize {
         self.tag_bits
     }
+    fn get_table_names(&self) -> &[&'static str] {
+        &self.table_names
+    }
 }
 
 impl Default for CustomAttributeType {
@@ -379,11 +447,18 @@ impl CodedIndex for HasFieldMarshall {
         self.table = value;
     }
     fn get_table_name(&self, index: usize) -> Result<&'static str> {
-        Ok(self.table_names[index])
+        self.table_names
+            .get(index)
+            .copied()
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| Error::CodedIndexWithUndefinedTable(format!("tag {}", index)))
     }
     fn get_tag_bits(&self) -> usize {
         self.tag_bits
     }
+    fn get_table_names(&self) -> &[&'static str] {
+        &self.table_names
+    }
 }
 
 impl Default for HasFieldMarshall {
@@ -419,11 +494,18 @@ impl CodedIndex for HasDeclSecurity {
         self.table = value;
     }
     fn get_table_name(&self, index: usize) -> Result<&'static str> {
-        Ok(self.table_names[index])
+        self.table_names
+            .get(index)
+            .copied()
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| Error::CodedIndexWithUndefinedTable(format!("tag {}", index)))
     }
     fn get_tag_bits(&self) -> usize {
         self.tag_bits
     }
+    fn get_table_names(&self) -> &[&'static str] {
+        &self.table_names
+    }
 }
 
 impl Default for HasDeclSecurity {
@@ -459,11 +541,18 @@ impl CodedIndex for HasSemantics {
         self.table = value;
     }
     fn get_table_name(&self, index: usize) -> Result<&'static str> {
-        Ok(self.table_names[index])
+        self.table_names
+            .get(index)
+            .copied()
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| Error::CodedIndexWithUndefinedTable(format!("tag {}", index)))
     }
     fn get_tag_bits(&self) -> usize {
         self.tag_bits
     }
+    fn get_table_names(&self) -> &[&'static str] {
+        &self.table_names
+    }
 }
 
 impl Default for HasSemantics {
@@ -499,11 +588,18 @@ impl CodedIndex for MethodDefOrRef {
         self.table = value;
     }
     fn get_table_name(&self, index: usize) -> Result<&'static str> {
-        Ok(self.table_names[index])
+        self.table_names
+            .get(index)
+            .copied()
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| Error::CodedIndexWithUndefinedTable(format!("tag {}", index)))
     }
     fn get_tag_bits(&self) -> usize {
         self.tag_bits
     }
+    fn get_table_names(&self) -> &[&'static str] {
+        &self.table_names
+    }
 }
 
 impl Default for MethodDefOrRef {
@@ -539,11 +635,18 @@ impl CodedIndex for MemberForwarded {
         self.table = value;
     }
     fn get_table_name(&self, index: usize) -> Result<&'static str> {
-        Ok(self.table_names[index])
+        self.table_names
+            .get(index)
+            .copied()
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| Error::CodedIndexWithUndefinedTable(format!("tag {}", index)))
     }
     fn get_tag_bits(&self) -> usize {
         self.tag_bits
     }
+    fn get_table_names(&self) -> &[&'static str] {
+        &self.table_names
+    }
 }
 
 impl Default for MemberForwarded {
@@ -579,11 +682,18 @@ impl CodedIndex for Implementation {
         self.table = value;
     }
     fn get_table_name(&self, index: usize) -> Result<&'static str> {
-        Ok(self.table_names[index])
+        self.table_names
+            .get(index)
+            .copied()
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| Error::CodedIndexWithUndefinedTable(format!("tag {}", index)))
     }
     fn get_tag_bits(&self) -> usize {
         self.tag_bits
     }
+    fn get_table_names(&self) -> &[&'static str] {
+        &self.table_names
+    }
 }
 
 impl Default for Implementation {
@@ -619,11 +729,18 @@ impl CodedIndex for TypeOrMethodDef {
         self.table = value;
     }
     fn get_table_name(&self, index: usize) -> Result<&'static str> {
-        Ok(self.table_names[index])
+        self.table_names
+            .get(index)
+            .copied()
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| Error::CodedIndexWithUndefinedTable(format!("tag {}", index)))
     }
     fn get_tag_bits(&self) -> usize {
         self.tag_bits
     }
+    fn get_table_names(&self) -> &[&'static str] {
+        &self.table_names
+    }
 }
 
 impl Default for TypeOrMethodDef {
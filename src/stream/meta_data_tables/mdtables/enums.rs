@@ -1,7 +1,8 @@
 use crate::{error::Error, Result};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub enum CorTypeVisibility {
+    #[default]
     NotPublic,
     Public,
     NestedPublic,
@@ -27,14 +28,9 @@ impl CorTypeVisibility {
     }
 }
 
-impl Default for CorTypeVisibility {
-    fn default() -> Self {
-        Self::NotPublic
-    }
-}
-
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub enum CorTypeLayout {
+    #[default]
     AutoLayout,
     SequentialLayout,
     ExplicitLayout,
@@ -50,14 +46,9 @@ impl CorTypeLayout {
     }
 }
 
-impl Default for CorTypeLayout {
-    fn default() -> Self {
-        Self::AutoLayout
-    }
-}
-
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub enum CorTypeSemantics {
+    #[default]
     Class,
     Interface,
 }
@@ -71,12 +62,6 @@ impl CorTypeSemantics {
     }
 }
 
-impl Default for CorTypeSemantics {
-    fn default() -> Self {
-        Self::Class
-    }
-}
-
 #[derive(Debug, Clone)]
 pub enum CorTypeAttrFlags {
     Abstract,
@@ -128,8 +113,9 @@ impl CorTypeAttrFlags {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub enum CorTypeStringFormat {
+    #[default]
     AnsiClass,
     UnicodeClass,
     AutoClass,
@@ -147,12 +133,6 @@ impl CorTypeStringFormat {
     }
 }
 
-impl Default for CorTypeStringFormat {
-    fn default() -> Self {
-        Self::AnsiClass
-    }
-}
-
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CorMethodCodeType {
     IL,
@@ -667,8 +647,9 @@ impl ClrPinvokeMap {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
 pub enum AssemblyHashAlgorithm {
+    #[default]
     None,
     Md5,
     Sha1,
@@ -690,12 +671,6 @@ impl AssemblyHashAlgorithm {
     }
 }
 
-impl Default for AssemblyHashAlgorithm {
-    fn default() -> Self {
-        Self::None
-    }
-}
-
 #[derive(Debug, Clone)]
 pub enum CorAssemblyFlagsPA {
     PaNone,
@@ -814,7 +789,7 @@ impl ClrManifestResourceFlags {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
 pub enum CorGenericParamVariance {
     NonVariant,
     Covariant,
@@ -833,7 +808,7 @@ impl CorGenericParamVariance {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
 pub enum CorGenericParamSpecialConstraint {
     NoSpecialConstraint,
     ReferenceTypeConstraint,
@@ -860,9 +835,62 @@ pub enum ClrGenericParamAttr {
 
 impl ClrGenericParamAttr {
     pub fn new(value: usize) -> Vec<Self> {
-        vec![
-            Self::Variance(CorGenericParamVariance::new(value)),
-            Self::SpecialConstraint(CorGenericParamSpecialConstraint::new(value)),
-        ]
+        let mut res = vec![Self::Variance(CorGenericParamVariance::new(value))];
+        for bit in [4, 8, 0x10] {
+            if value & bit != 0 {
+                res.push(Self::SpecialConstraint(
+                    CorGenericParamSpecialConstraint::new(bit),
+                ));
+            }
+        }
+        if res.len() == 1 {
+            res.push(Self::SpecialConstraint(
+                CorGenericParamSpecialConstraint::NoSpecialConstraint,
+            ));
+        }
+        res
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub enum CorDeclSecurity {
+    Request,
+    Demand,
+    Assert,
+    Deny,
+    PermitOnly,
+    LinkDemand,
+    InheritanceDemand,
+    RequestMinimum,
+    RequestOptional,
+    RequestRefuse,
+    PrejitGrant,
+    PrejitDenied,
+    NonCasDemand,
+    NonCasLinkDemand,
+    NonCasInheritance,
+    Unknown(usize),
+}
+
+impl CorDeclSecurity {
+    pub fn new(value: usize) -> Self {
+        match value {
+            1 => Self::Request,
+            2 => Self::Demand,
+            3 => Self::Assert,
+            4 => Self::Deny,
+            5 => Self::PermitOnly,
+            6 => Self::LinkDemand,
+            7 => Self::InheritanceDemand,
+            8 => Self::RequestMinimum,
+            9 => Self::RequestOptional,
+            10 => Self::RequestRefuse,
+            11 => Self::PrejitGrant,
+            12 => Self::PrejitDenied,
+            13 => Self::NonCasDemand,
+            14 => Self::NonCasLinkDemand,
+            15 => Self::NonCasInheritance,
+            v => Self::Unknown(v),
+        }
     }
 }
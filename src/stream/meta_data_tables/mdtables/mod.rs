@@ -6,6 +6,8 @@ pub mod codedindex;
 use codedindex::CodedIndex;
 pub mod enums;
 
+use crate::writer::rows::{IndexColumn, RowIndexes, RowWriter};
+
 pub trait MDTableTrait: std::fmt::Debug + MDTableTraitClone {
     fn set_data(&mut self, data: &[u8]) -> Result<()>;
     fn row_size(&self) -> usize;
@@ -13,6 +15,10 @@ pub trait MDTableTrait: std::fmt::Debug + MDTableTraitClone {
     fn get_mut_row(&mut self, i: usize) -> Result<&mut dyn MDTableRowTraitT>;
     fn row_count(&self) -> usize;
     fn name(&self) -> &str;
+    fn push_row(&mut self, row: &dyn std::any::Any) -> Result<()>;
+    fn remove_row(&mut self, i: usize) -> Result<()>;
+    fn index_columns(&mut self, i: usize) -> Result<Vec<IndexColumn<'_>>>;
+    fn write(&self, w: &mut crate::writer::metadata::TableWriter) -> Result<()>;
 }
 
 impl Serialize for dyn MDTableTrait {
@@ -51,6 +57,10 @@ where
     T: MDTableRowTrait + std::fmt::Debug + Default + Clone,
 {
     name: String,
+    str_offset_size: usize,
+    guids_offset_size: usize,
+    blobs_offset_size: usize,
+    tables_row_counts: Vec<usize>,
     table: Vec<MDTableRow<T>>,
 }
 
@@ -68,6 +78,10 @@ where
     ) -> Result<MDTable<T>> {
         Ok(MDTable::<T> {
             name: name.to_string(),
+            str_offset_size: strings_offset_size,
+            guids_offset_size,
+            blobs_offset_size,
+            tables_row_counts: tables_row_counts.to_vec(),
             table: vec![
                 MDTableRow::<T>::new(
                     strings_offset_size,
@@ -83,7 +97,7 @@ where
 
 impl<T> MDTableTrait for MDTable<T>
 where
-    T: 'static + MDTableRowTrait + std::fmt::Debug + Default + Clone,
+    T: 'static + MDTableRowTrait + RowWriter + RowIndexes + std::fmt::Debug + Default + Clone,
 {
     fn set_data(&mut self, data: &[u8]) -> Result<()> {
         if data.len() < self.table.len() * self.row_size() {
@@ -135,6 +149,82 @@ where
     fn name(&self) -> &str {
         &self.name
     }
+
+    fn push_row(&mut self, row: &dyn std::any::Any) -> Result<()> {
+        let row =
+            row.downcast_ref::<T>()
+                .ok_or(Error::IncorrectCastTo("table row", file!(), line!()))?;
+        let mut new_row = MDTableRow::<T>::new(
+            self.str_offset_size,
+            self.guids_offset_size,
+            self.blobs_offset_size,
+            &self.tables_row_counts,
+        );
+        new_row.row = row.clone();
+        self.table.push(new_row);
+        Ok(())
+    }
+
+    fn remove_row(&mut self, i: usize) -> Result<()> {
+        if i < self.row_count() {
+            self.table.remove(i);
+            Ok(())
+        } else {
+            Err(Error::RowIndexOutOfBound(i, self.row_count()))
+        }
+    }
+
+    fn index_columns(&mut self, i: usize) -> Result<Vec<IndexColumn<'_>>> {
+        let row_count = self.row_count();
+        match self.table.get_mut(i) {
+            Some(r) => Ok(r.row.index_columns()),
+            None => Err(Error::RowIndexOutOfBound(i, row_count)),
+        }
+    }
+
+    fn write(&self, w: &mut crate::writer::metadata::TableWriter) -> Result<()> {
+        for r in &self.table {
+            r.row.write(w)?;
+        }
+        w.finish_table()
+    }
+}
+
+fn list_range(
+    table: &'static str,
+    ptr_table: &'static str,
+    first: usize,
+    next: Option<usize>,
+    tables: &std::collections::BTreeMap<usize, MetaDataTable>,
+) -> Result<Vec<codedindex::SimpleCodedIndex>> {
+    let ptr = tables
+        .get(&table_name_2_index(ptr_table)?)
+        .filter(|t| t.row_count() > 0);
+    let row_count = match ptr {
+        Some(t) => t.row_count(),
+        None => match tables.get(&table_name_2_index(table)?) {
+            Some(t) => t.row_count(),
+            None => 0,
+        },
+    };
+    let end = std::cmp::min(row_count + 1, next.unwrap_or(row_count + 1));
+    let mut res = vec![];
+    if first == 0 {
+        return Ok(res);
+    }
+    for i in first..end {
+        let row_index = match ptr {
+            Some(t) => crate::utils::read_usize(t.get_row(i - 1)?.data())?,
+            None => i,
+        };
+        res.push(codedindex::SimpleCodedIndex::new(
+            vec![table],
+            0,
+            &row_index.to_le_bytes(),
+            tables,
+        )?);
+    }
+    Ok(res)
 }
 
 pub trait MDTableRowTrait {
@@ -174,6 +264,7 @@ pub trait MDTableRowTrait {
         Ok(())
     }
     fn as_any(&self) -> &dyn std::any::Any;
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any;
 }
 
 pub trait MDTableRowTraitT {
@@ -196,6 +287,8 @@ pub trait MDTableRowTraitT {
     ) -> Result<()>;
     fn get_row(&self) -> &dyn MDTableRowTrait;
     fn get_mut_row(&mut self) -> &mut dyn MDTableRowTrait;
+    fn data(&self) -> &[u8];
+    fn columns(&self) -> Result<Vec<crate::writer::metadata::Column>>;
 }
 
 #[derive(Debug, Clone)]
@@ -213,7 +306,7 @@ where
 
 impl<T> MDTableRowTraitT for MDTableRow<T>
 where
-    T: MDTableRowTrait,
+    T: MDTableRowTrait + RowWriter,
 {
     fn size(&self) -> usize {
         self.row.size(
@@ -275,6 +368,19 @@ where
     fn get_mut_row(&mut self) -> &mut dyn MDTableRowTrait {
         &mut self.row
     }
+    fn data(&self) -> &[u8] {
+        &self.data
+    }
+    fn columns(&self) -> Result<Vec<crate::writer::metadata::Column>> {
+        let mut w = crate::writer::metadata::TableWriter::layout(
+            self.str_offset_size,
+            self.guids_offset_size,
+            self.blobs_offset_size,
+            &self.tables_row_counts,
+        );
+        self.row.write(&mut w)?;
+        Ok(w.into_columns())
+    }
 }
 
 impl<T> MDTableRow<T>
@@ -317,6 +423,10 @@ impl MDTableRowTrait for Module {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         str_offset_size: usize,
@@ -376,6 +486,10 @@ impl MDTableRowTrait for TypeRef {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         str_offset_size: usize,
@@ -425,9 +539,10 @@ impl MDTableRowTrait for TypeRef {
 #[derive(Debug, Clone, Default)]
 pub struct TypeDef {
     flags: enums::ClrTypeAttr,
+    pub raw_flags: u32,
     pub type_name: String,
     pub type_namespace: String,
-    extends: codedindex::TypeDefOrRef,
+    pub extends: codedindex::TypeDefOrRef,
     pub field_list: Vec<codedindex::SimpleCodedIndex>, //Field
     pub method_list: Vec<codedindex::SimpleCodedIndex>, //MethodDef
 }
@@ -437,6 +552,10 @@ impl MDTableRowTrait for TypeDef {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         str_offset_size: usize,
@@ -484,21 +603,18 @@ impl MDTableRowTrait for TypeDef {
         } else {
             return Err(Error::RefToUndefinedHeap("string"));
         };
+        self.raw_flags = crate::utils::read_usize(&data[0..s1])? as u32;
         self.flags.set(&data[0..s1])?;
         self.type_name = strings_heap.get_string(&data[s1..s2])?;
         self.type_namespace = strings_heap.get_string(&data[s2..s3])?;
         self.extends.set(&data[s3..s4], tables)?;
-        self.field_list = vec![codedindex::SimpleCodedIndex::new(
-            vec!["Field"],
-            0,
+        self.field_list = vec![codedindex::SimpleCodedIndex::list_start(
+            "Field",
             &data[s4..s5],
-            tables,
         )?];
-        self.method_list = vec![codedindex::SimpleCodedIndex::new(
-            vec!["MethodDef"],
-            0,
+        self.method_list = vec![codedindex::SimpleCodedIndex::list_start(
+            "MethodDef",
             &data[s5..s6],
-            tables,
         )?];
         Ok(())
     }
@@ -516,70 +632,35 @@ impl MDTableRowTrait for TypeDef {
         _blobs_heap: &Option<&crate::stream::ClrStream>,
         _guids_heap: &Option<&crate::stream::ClrStream>,
     ) -> Result<()> {
-        let field_row_count = tables
-            .get(&table_name_2_index("Field")?)
-            .ok_or(Error::IncorrectTableRequested("Field", file!(), line!()))?
-            .row_count();
-        let method_def_row_count = tables
-            .get(&table_name_2_index("MethodDef")?)
-            .ok_or(Error::IncorrectTableRequested(
-                "MethodDef",
-                file!(),
-                line!(),
-            ))?
-            .row_count();
-        let (first_field_index, first_method_index) = (
+        let next_row = match next_row {
+            Some(nr) => Some(
+                nr.as_any()
+                    .downcast_ref::<TypeDef>()
+                    .ok_or(Error::IncorrectCastTo("TypeDef", file!(), line!()))?,
+            ),
+            None => None,
+        };
+        self.field_list = list_range(
+            "Field",
+            "FieldPtr",
             self.field_list[0].row_index(),
+            next_row.map(|nr| nr.field_list[0].row_index()),
+            tables,
+        )?;
+        self.method_list = list_range(
+            "MethodDef",
+            "MethodPtr",
             self.method_list[0].row_index(),
-        );
-        let (last_field_index, last_method_index) = if let Some(nr) = next_row {
-            let nnr = nr
-                .as_any()
-                .downcast_ref::<TypeDef>()
-                .ok_or(Error::IncorrectCastTo("TypeDef", file!(), line!()))?;
-            (
-                std::cmp::min(field_row_count, nnr.field_list[0].row_index()),
-                std::cmp::min(method_def_row_count, nnr.method_list[0].row_index()),
-            )
-        } else {
-            (field_row_count, method_def_row_count)
-        };
-        if first_field_index < last_field_index
-            || (first_field_index == last_field_index && last_field_index == field_row_count)
-        {
-            for i in self.field_list[0].row_index() + 1..last_field_index {
-                self.field_list.push(codedindex::SimpleCodedIndex::new(
-                    vec!["Field"],
-                    0,
-                    &i.to_le_bytes(),
-                    tables,
-                )?);
-            }
-        } else {
-            self.field_list.clear();
-        }
-        if first_method_index < last_method_index
-            || (first_method_index == last_method_index
-                && last_method_index == method_def_row_count)
-        {
-            for i in self.method_list[0].row_index() + 1..last_method_index {
-                self.method_list.push(codedindex::SimpleCodedIndex::new(
-                    vec!["MethodDef"],
-                    0,
-                    &i.to_le_bytes(),
-                    tables,
-                )?);
-            }
-        } else {
-            self.method_list.clear();
-        }
+            next_row.map(|nr| nr.method_list[0].row_index()),
+            tables,
+        )?;
         Ok(())
     }
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct FieldPtr {
-    field: codedindex::SimpleCodedIndex, //Field
+    pub field: codedindex::SimpleCodedIndex, //Field
 }
 
 impl MDTableRowTrait for FieldPtr {
@@ -587,6 +668,10 @@ impl MDTableRowTrait for FieldPtr {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
@@ -618,9 +703,10 @@ impl MDTableRowTrait for FieldPtr {
 
 #[derive(Debug, Clone, Default)]
 pub struct Field {
-    flags: Vec<enums::ClrFieldAttr>,
+    pub flags: Vec<enums::ClrFieldAttr>,
+    pub raw_flags: u16,
     pub name: String,
-    signature: Vec<u8>,
+    pub signature: Vec<u8>,
 }
 
 impl MDTableRowTrait for Field {
@@ -628,6 +714,10 @@ impl MDTableRowTrait for Field {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         str_offset_size: usize,
@@ -664,7 +754,8 @@ impl MDTableRowTrait for Field {
         } else {
             return Err(Error::RefToUndefinedHeap("blob"));
         };
-        self.flags = enums::ClrFieldAttr::new(crate::utils::read_usize(&data[0..s1])?);
+        self.raw_flags = crate::utils::read_usize(&data[0..s1])? as u16;
+        self.flags = enums::ClrFieldAttr::new(self.raw_flags as usize);
         self.name = strings_heap.get_string(&data[s1..s2])?;
         self.signature = blobs_heap.get_blob(&data[s2..s3])?;
         Ok(())
@@ -673,7 +764,7 @@ impl MDTableRowTrait for Field {
 
 #[derive(Debug, Clone, Default)]
 pub struct MethodPtr {
-    field: codedindex::SimpleCodedIndex, //MethodDef
+    pub method: codedindex::SimpleCodedIndex, //MethodDef
 }
 
 impl MDTableRowTrait for MethodPtr {
@@ -681,6 +772,10 @@ impl MDTableRowTrait for MethodPtr {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
@@ -705,7 +800,8 @@ impl MDTableRowTrait for MethodPtr {
         _guids_heap: &Option<&crate::stream::ClrStream>,
     ) -> Result<()> {
         let s1 = codedindex::clr_coded_index_struct_size(0, &vec!["MethodDef"], tables_row_counts);
-        self.field = codedindex::SimpleCodedIndex::new(vec!["MethodDef"], 0, &data[0..s1], tables)?;
+        self.method =
+            codedindex::SimpleCodedIndex::new(vec!["MethodDef"], 0, &data[0..s1], tables)?;
         Ok(())
     }
 }
@@ -714,10 +810,12 @@ impl MDTableRowTrait for MethodPtr {
 pub struct MethodDef {
     pub rva: u32,
     pub impl_flags: Vec<enums::ClrMethodImpl>,
+    pub raw_impl_flags: u16,
     pub flags: Vec<enums::ClrMethodAttr>,
+    pub raw_flags: u16,
     pub name: String,
-    signature: Vec<u8>,
-    param_list: Vec<Param>,
+    pub signature: Vec<u8>,
+    pub param_list: Vec<codedindex::SimpleCodedIndex>, //Param
 }
 
 impl MDTableRowTrait for MethodDef {
@@ -725,6 +823,10 @@ impl MDTableRowTrait for MethodDef {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         str_offset_size: usize,
@@ -757,8 +859,7 @@ impl MDTableRowTrait for MethodDef {
         let s3 = s2 + 2;
         let s4 = s3 + str_offset_size;
         let s5 = s4 + blobs_offset_size;
-        let _s6 =
-            s5 + codedindex::clr_coded_index_struct_size(0, &vec!["Param"], tables_row_counts);
+        let s6 = s5 + codedindex::clr_coded_index_struct_size(0, &vec!["Param"], tables_row_counts);
         let strings_heap = if let Some(s) = strings_heap {
             s
         } else {
@@ -770,18 +871,54 @@ impl MDTableRowTrait for MethodDef {
             return Err(Error::RefToUndefinedHeap("blob"));
         };
         self.rva = crate::utils::read_usize(&data[0..s1])? as u32;
-        self.impl_flags = enums::ClrMethodImpl::new(crate::utils::read_usize(&data[s1..s2])?);
-        self.flags = enums::ClrMethodAttr::new(crate::utils::read_usize(&data[s2..s3])?);
+        self.raw_impl_flags = crate::utils::read_usize(&data[s1..s2])? as u16;
+        self.impl_flags = enums::ClrMethodImpl::new(self.raw_impl_flags as usize);
+        self.raw_flags = crate::utils::read_usize(&data[s2..s3])? as u16;
+        self.flags = enums::ClrMethodAttr::new(self.raw_flags as usize);
         self.name = strings_heap.get_string(&data[s3..s4])?;
         self.signature = blobs_heap.get_blob(&data[s4..s5])?;
-        self.param_list = vec![];
+        self.param_list = vec![codedindex::SimpleCodedIndex::list_start(
+            "Param",
+            &data[s5..s6],
+        )?];
+        Ok(())
+    }
+
+    fn parse2(
+        &mut self,
+        _data: &[u8],
+        _str_offset_size: usize,
+        _guids_offset_size: usize,
+        _blobs_offset_size: usize,
+        _tables_row_counts: &[usize],
+        tables: &std::collections::BTreeMap<usize, MetaDataTable>,
+        next_row: Option<&dyn MDTableRowTrait>,
+        _strings_heap: &Option<&crate::stream::ClrStream>,
+        _blobs_heap: &Option<&crate::stream::ClrStream>,
+        _guids_heap: &Option<&crate::stream::ClrStream>,
+    ) -> Result<()> {
+        let next_row = match next_row {
+            Some(nr) => Some(
+                nr.as_any()
+                    .downcast_ref::<MethodDef>()
+                    .ok_or(Error::IncorrectCastTo("MethodDef", file!(), line!()))?,
+            ),
+            None => None,
+        };
+        self.param_list = list_range(
+            "Param",
+            "ParamPtr",
+            self.param_list[0].row_index(),
+            next_row.map(|nr| nr.param_list[0].row_index()),
+            tables,
+        )?;
         Ok(())
     }
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct ParamPtr {
-    field: codedindex::SimpleCodedIndex, //Param
+    pub param: codedindex::SimpleCodedIndex, //Param
 }
 
 impl MDTableRowTrait for ParamPtr {
@@ -789,6 +926,10 @@ impl MDTableRowTrait for ParamPtr {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
@@ -813,16 +954,17 @@ impl MDTableRowTrait for ParamPtr {
         _guids_heap: &Option<&crate::stream::ClrStream>,
     ) -> Result<()> {
         let s1 = codedindex::clr_coded_index_struct_size(0, &vec!["Param"], tables_row_counts);
-        self.field = codedindex::SimpleCodedIndex::new(vec!["Param"], 0, &data[0..s1], tables)?;
+        self.param = codedindex::SimpleCodedIndex::new(vec!["Param"], 0, &data[0..s1], tables)?;
         Ok(())
     }
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct Param {
-    flags: Vec<enums::ClrParamAttr>,
-    sequence: usize,
-    name: String,
+    pub flags: Vec<enums::ClrParamAttr>,
+    pub raw_flags: u16,
+    pub sequence: usize,
+    pub name: String,
 }
 
 impl MDTableRowTrait for Param {
@@ -830,6 +972,10 @@ impl MDTableRowTrait for Param {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         str_offset_size: usize,
@@ -861,7 +1007,8 @@ impl MDTableRowTrait for Param {
         } else {
             return Err(Error::RefToUndefinedHeap("string"));
         };
-        self.flags = enums::ClrParamAttr::new(crate::utils::read_usize(&data[0..s1])?);
+        self.raw_flags = crate::utils::read_usize(&data[0..s1])? as u16;
+        self.flags = enums::ClrParamAttr::new(self.raw_flags as usize);
         self.sequence = crate::utils::read_usize(&data[s1..s2])?;
         self.name = strings_heap.get_string(&data[s2..s3])?;
         Ok(())
@@ -870,8 +1017,8 @@ impl MDTableRowTrait for Param {
 
 #[derive(Debug, Clone, Default)]
 pub struct InterfaceImpl {
-    class: codedindex::SimpleCodedIndex, //TypeDef
-    interface: codedindex::TypeDefOrRef,
+    pub class: codedindex::SimpleCodedIndex, //TypeDef
+    pub interface: codedindex::TypeDefOrRef,
 }
 
 impl MDTableRowTrait for InterfaceImpl {
@@ -879,6 +1026,10 @@ impl MDTableRowTrait for InterfaceImpl {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
@@ -932,6 +1083,10 @@ impl MDTableRowTrait for MemberRef {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         str_offset_size: usize,
@@ -986,10 +1141,10 @@ impl MDTableRowTrait for MemberRef {
 
 #[derive(Debug, Clone, Default)]
 pub struct Constant {
-    _type: u32,
-    padding: u32,
-    parent: codedindex::HasConstant,
-    value: Vec<u8>,
+    pub(crate) _type: u32,
+    pub(crate) padding: u32,
+    pub(crate) parent: codedindex::HasConstant,
+    pub(crate) value: Vec<u8>,
 }
 
 impl MDTableRowTrait for Constant {
@@ -997,6 +1152,10 @@ impl MDTableRowTrait for Constant {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
@@ -1050,9 +1209,9 @@ impl MDTableRowTrait for Constant {
 
 #[derive(Debug, Clone, Default)]
 pub struct CustomAttribute {
-    parent: codedindex::HasCustomAttribute,
-    _type: codedindex::CustomAttributeType,
-    value: Vec<u8>,
+    pub parent: codedindex::HasCustomAttribute,
+    pub _type: codedindex::CustomAttributeType,
+    pub value: Vec<u8>,
 }
 
 impl MDTableRowTrait for CustomAttribute {
@@ -1060,6 +1219,10 @@ impl MDTableRowTrait for CustomAttribute {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
@@ -1116,8 +1279,8 @@ impl MDTableRowTrait for CustomAttribute {
 
 #[derive(Debug, Clone, Default)]
 pub struct FieldMarshal {
-    parent: codedindex::HasFieldMarshall,
-    native_type: Vec<u8>,
+    pub parent: codedindex::HasFieldMarshall,
+    pub native_type: Vec<u8>,
 }
 
 impl MDTableRowTrait for FieldMarshal {
@@ -1125,6 +1288,10 @@ impl MDTableRowTrait for FieldMarshal {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
@@ -1171,9 +1338,9 @@ impl MDTableRowTrait for FieldMarshal {
 
 #[derive(Debug, Clone, Default)]
 pub struct DeclSecurity {
-    action: u32,
-    parent: codedindex::HasDeclSecurity,
-    permission_set: Vec<u8>,
+    pub action: u32,
+    pub parent: codedindex::HasDeclSecurity,
+    pub permission_set: Vec<u8>,
 }
 
 impl MDTableRowTrait for DeclSecurity {
@@ -1181,6 +1348,10 @@ impl MDTableRowTrait for DeclSecurity {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
@@ -1230,9 +1401,9 @@ impl MDTableRowTrait for DeclSecurity {
 
 #[derive(Debug, Clone, Default)]
 pub struct ClassLayout {
-    packing_size: usize,
-    class_size: usize,
-    parent: codedindex::SimpleCodedIndex,
+    pub(crate) packing_size: usize,
+    pub(crate) class_size: usize,
+    pub(crate) parent: codedindex::SimpleCodedIndex,
 }
 
 impl MDTableRowTrait for ClassLayout {
@@ -1240,6 +1411,10 @@ impl MDTableRowTrait for ClassLayout {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
@@ -1264,7 +1439,7 @@ impl MDTableRowTrait for ClassLayout {
         _guids_heap: &Option<&crate::stream::ClrStream>,
     ) -> Result<()> {
         let s1 = 2;
-        let s2 = 4;
+        let s2 = s1 + 4;
         let s3 =
             s2 + codedindex::clr_coded_index_struct_size(0, &vec!["TypeDef"], tables_row_counts);
         self.packing_size = crate::utils::read_usize(&data[0..s1])?;
@@ -1276,8 +1451,8 @@ impl MDTableRowTrait for ClassLayout {
 
 #[derive(Debug, Clone, Default)]
 pub struct FieldLayout {
-    offset: u32,
-    field: codedindex::SimpleCodedIndex, // Field
+    pub(crate) offset: u32,
+    pub(crate) field: codedindex::SimpleCodedIndex, // Field
 }
 
 impl MDTableRowTrait for FieldLayout {
@@ -1285,6 +1460,10 @@ impl MDTableRowTrait for FieldLayout {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
@@ -1318,7 +1497,7 @@ impl MDTableRowTrait for FieldLayout {
 
 #[derive(Debug, Clone, Default)]
 pub struct StandAloneSig {
-    signature: Vec<u8>,
+    pub signature: Vec<u8>,
 }
 
 impl MDTableRowTrait for StandAloneSig {
@@ -1326,6 +1505,10 @@ impl MDTableRowTrait for StandAloneSig {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
@@ -1362,8 +1545,8 @@ impl MDTableRowTrait for StandAloneSig {
 
 #[derive(Debug, Clone, Default)]
 pub struct EventMap {
-    parent: codedindex::SimpleCodedIndex, //  TypeDef,
-    event_list: Vec<Event>,
+    pub parent: codedindex::SimpleCodedIndex, //  TypeDef,
+    pub event_list: Vec<codedindex::SimpleCodedIndex>, //Event
 }
 
 impl MDTableRowTrait for EventMap {
@@ -1371,6 +1554,10 @@ impl MDTableRowTrait for EventMap {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
@@ -1396,54 +1583,96 @@ impl MDTableRowTrait for EventMap {
         _guids_heap: &Option<&crate::stream::ClrStream>,
     ) -> Result<()> {
         let s1 = codedindex::clr_coded_index_struct_size(0, &vec!["TypeDef"], tables_row_counts);
-        let _s2 =
-            s1 + codedindex::clr_coded_index_struct_size(0, &vec!["Event"], tables_row_counts);
+        let s2 = s1 + codedindex::clr_coded_index_struct_size(0, &vec!["Event"], tables_row_counts);
         self.parent = codedindex::SimpleCodedIndex::new(vec!["TypeDef"], 0, &data[0..s1], tables)?;
-        self.event_list = vec![];
+        self.event_list = vec![codedindex::SimpleCodedIndex::list_start(
+            "Event",
+            &data[s1..s2],
+        )?];
+        Ok(())
+    }
+
+    fn parse2(
+        &mut self,
+        _data: &[u8],
+        _str_offset_size: usize,
+        _guids_offset_size: usize,
+        _blobs_offset_size: usize,
+        _tables_row_counts: &[usize],
+        tables: &std::collections::BTreeMap<usize, MetaDataTable>,
+        next_row: Option<&dyn MDTableRowTrait>,
+        _strings_heap: &Option<&crate::stream::ClrStream>,
+        _blobs_heap: &Option<&crate::stream::ClrStream>,
+        _guids_heap: &Option<&crate::stream::ClrStream>,
+    ) -> Result<()> {
+        let next_row = match next_row {
+            Some(nr) => Some(
+                nr.as_any()
+                    .downcast_ref::<EventMap>()
+                    .ok_or(Error::IncorrectCastTo("EventMap", file!(), line!()))?,
+            ),
+            None => None,
+        };
+        self.event_list = list_range(
+            "Event",
+            "EventPtr",
+            self.event_list[0].row_index(),
+            next_row.map(|nr| nr.event_list[0].row_index()),
+            tables,
+        )?;
         Ok(())
     }
 }
 
 #[derive(Debug, Clone, Default)]
-pub struct EventPtr {}
+pub struct EventPtr {
+    pub event: codedindex::SimpleCodedIndex, //Event
+}
 
 impl MDTableRowTrait for EventPtr {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
         _guids_offset_size: usize,
         _blobs_offset_size: usize,
-        _tables_row_counts: &[usize],
+        tables_row_counts: &[usize],
     ) -> usize {
-        0
+        codedindex::clr_coded_index_struct_size(0, &vec!["Event"], tables_row_counts)
     }
 
     fn parse(
         &mut self,
-        _data: &[u8],
+        data: &[u8],
         _str_offset_size: usize,
         _guids_offset_size: usize,
         _blobs_offset_size: usize,
-        _tables_row_counts: &[usize],
-        _tables: &std::collections::BTreeMap<usize, MetaDataTable>,
+        tables_row_counts: &[usize],
+        tables: &std::collections::BTreeMap<usize, MetaDataTable>,
         _next_row: Option<&dyn MDTableRowTrait>,
         _strings_heap: &Option<&crate::stream::ClrStream>,
         _blobss_heap: &Option<&crate::stream::ClrStream>,
         _guids_heap: &Option<&crate::stream::ClrStream>,
     ) -> Result<()> {
-        unimplemented!()
+        let s1 = codedindex::clr_coded_index_struct_size(0, &vec!["Event"], tables_row_counts);
+        self.event = codedindex::SimpleCodedIndex::new(vec!["Event"], 0, &data[0..s1], tables)?;
+        Ok(())
     }
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct Event {
-    event_flags: Vec<enums::ClrEventAttr>,
-    name: String,
-    event_type: codedindex::TypeDefOrRef,
+    pub event_flags: Vec<enums::ClrEventAttr>,
+    pub raw_event_flags: u16,
+    pub name: String,
+    pub event_type: codedindex::TypeDefOrRef,
 }
 
 impl MDTableRowTrait for Event {
@@ -1451,6 +1680,10 @@ impl MDTableRowTrait for Event {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         str_offset_size: usize,
@@ -1492,7 +1725,8 @@ impl MDTableRowTrait for Event {
         } else {
             return Err(Error::RefToUndefinedHeap("string"));
         };
-        self.event_flags = enums::ClrEventAttr::new(crate::utils::read_usize(&data[0..s1])?);
+        self.raw_event_flags = crate::utils::read_usize(&data[0..s1])? as u16;
+        self.event_flags = enums::ClrEventAttr::new(self.raw_event_flags as usize);
         self.name = strings_heap.get_string(&data[s1..s2])?;
         self.event_type.set(&data[s2..s3], tables)?;
         Ok(())
@@ -1502,7 +1736,7 @@ impl MDTableRowTrait for Event {
 #[derive(Debug, Clone, Default)]
 pub struct PropertyMap {
     pub parent: codedindex::SimpleCodedIndex, //typedef
-    pub property_list: Vec<Property>,
+    pub property_list: Vec<codedindex::SimpleCodedIndex>, //Property
 }
 
 impl MDTableRowTrait for PropertyMap {
@@ -1510,6 +1744,10 @@ impl MDTableRowTrait for PropertyMap {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
@@ -1535,52 +1773,96 @@ impl MDTableRowTrait for PropertyMap {
         _guids_heap: &Option<&crate::stream::ClrStream>,
     ) -> Result<()> {
         let s1 = codedindex::clr_coded_index_struct_size(0, &vec!["TypeDef"], tables_row_counts);
-        let _s2 =
+        let s2 =
             s1 + codedindex::clr_coded_index_struct_size(0, &vec!["Property"], tables_row_counts);
         self.parent = codedindex::SimpleCodedIndex::new(vec!["TypeDef"], 0, &data[0..s1], tables)?;
-        self.property_list = vec![];
+        self.property_list = vec![codedindex::SimpleCodedIndex::list_start(
+            "Property",
+            &data[s1..s2],
+        )?];
+        Ok(())
+    }
+
+    fn parse2(
+        &mut self,
+        _data: &[u8],
+        _str_offset_size: usize,
+        _guids_offset_size: usize,
+        _blobs_offset_size: usize,
+        _tables_row_counts: &[usize],
+        tables: &std::collections::BTreeMap<usize, MetaDataTable>,
+        next_row: Option<&dyn MDTableRowTrait>,
+        _strings_heap: &Option<&crate::stream::ClrStream>,
+        _blobs_heap: &Option<&crate::stream::ClrStream>,
+        _guids_heap: &Option<&crate::stream::ClrStream>,
+    ) -> Result<()> {
+        let next_row = match next_row {
+            Some(nr) => Some(
+                nr.as_any()
+                    .downcast_ref::<PropertyMap>()
+                    .ok_or(Error::IncorrectCastTo("PropertyMap", file!(), line!()))?,
+            ),
+            None => None,
+        };
+        self.property_list = list_range(
+            "Property",
+            "PropertyPtr",
+            self.property_list[0].row_index(),
+            next_row.map(|nr| nr.property_list[0].row_index()),
+            tables,
+        )?;
         Ok(())
     }
 }
 
 #[derive(Debug, Clone, Default)]
-pub struct PropertyPtr {}
+pub struct PropertyPtr {
+    pub property: codedindex::SimpleCodedIndex, //Property
+}
 
 impl MDTableRowTrait for PropertyPtr {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
         _guids_offset_size: usize,
         _blobs_offset_size: usize,
-        _tables_row_counts: &[usize],
+        tables_row_counts: &[usize],
     ) -> usize {
-        0
+        codedindex::clr_coded_index_struct_size(0, &vec!["Property"], tables_row_counts)
     }
 
     fn parse(
         &mut self,
-        _data: &[u8],
+        data: &[u8],
         _str_offset_size: usize,
         _guids_offset_size: usize,
         _blobs_offset_size: usize,
-        _tables_row_counts: &[usize],
-        _tables: &std::collections::BTreeMap<usize, MetaDataTable>,
+        tables_row_counts: &[usize],
+        tables: &std::collections::BTreeMap<usize, MetaDataTable>,
         _next_row: Option<&dyn MDTableRowTrait>,
         _strings_heap: &Option<&crate::stream::ClrStream>,
         _blobss_heap: &Option<&crate::stream::ClrStream>,
         _guids_heap: &Option<&crate::stream::ClrStream>,
     ) -> Result<()> {
-        unimplemented!()
+        let s1 = codedindex::clr_coded_index_struct_size(0, &vec!["Property"], tables_row_counts);
+        self.property =
+            codedindex::SimpleCodedIndex::new(vec!["Property"], 0, &data[0..s1], tables)?;
+        Ok(())
     }
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct Property {
     pub flags: Vec<enums::ClrPropertyAttr>,
+    pub raw_flags: u16,
     pub name: String,
     pub _type: Vec<u8>,
 }
@@ -1590,6 +1872,10 @@ impl MDTableRowTrait for Property {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         str_offset_size: usize,
@@ -1626,7 +1912,8 @@ impl MDTableRowTrait for Property {
         } else {
             return Err(Error::RefToUndefinedHeap("blob"));
         };
-        self.flags = enums::ClrPropertyAttr::new(crate::utils::read_usize(&data[0..s1])?);
+        self.raw_flags = crate::utils::read_usize(&data[0..s1])? as u16;
+        self.flags = enums::ClrPropertyAttr::new(self.raw_flags as usize);
         self.name = strings_heap.get_string(&data[s1..s2])?;
         self._type = blobs_heap.get_blob(&data[s2..s3])?;
         Ok(())
@@ -1636,6 +1923,7 @@ impl MDTableRowTrait for Property {
 #[derive(Debug, Clone, Default)]
 pub struct MethodSemantics {
     pub semantics: Vec<enums::ClrMethodSemanticsAttr>,
+    pub raw_semantics: u16,
     pub method: codedindex::SimpleCodedIndex,
     pub association: codedindex::HasSemantics,
 }
@@ -1645,6 +1933,10 @@ impl MDTableRowTrait for MethodSemantics {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
@@ -1682,8 +1974,8 @@ impl MDTableRowTrait for MethodSemantics {
                 &self.association.table_names,
                 tables_row_counts,
             );
-        self.semantics =
-            enums::ClrMethodSemanticsAttr::new(crate::utils::read_usize(&data[0..s1])?);
+        self.raw_semantics = crate::utils::read_usize(&data[0..s1])? as u16;
+        self.semantics = enums::ClrMethodSemanticsAttr::new(self.raw_semantics as usize);
         self.method =
             codedindex::SimpleCodedIndex::new(vec!["MethodDef"], 0, &data[s1..s2], tables)?;
         self.association.set(&data[s2..s3], tables)?;
@@ -1693,9 +1985,9 @@ impl MDTableRowTrait for MethodSemantics {
 
 #[derive(Debug, Clone, Default)]
 pub struct MethodImpl {
-    class: codedindex::SimpleCodedIndex, // TypeDef,
-    method_body: codedindex::MethodDefOrRef,
-    method_declaration: codedindex::MethodDefOrRef,
+    pub class: codedindex::SimpleCodedIndex, // TypeDef,
+    pub method_body: codedindex::MethodDefOrRef,
+    pub method_declaration: codedindex::MethodDefOrRef,
 }
 
 impl MDTableRowTrait for MethodImpl {
@@ -1703,6 +1995,10 @@ impl MDTableRowTrait for MethodImpl {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
@@ -1766,6 +2062,10 @@ impl MDTableRowTrait for ModuleRef {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         str_offset_size: usize,
@@ -1802,7 +2102,7 @@ impl MDTableRowTrait for ModuleRef {
 
 #[derive(Debug, Clone, Default)]
 pub struct TypeSpec {
-    signature: Vec<u8>,
+    pub signature: Vec<u8>,
 }
 
 impl MDTableRowTrait for TypeSpec {
@@ -1810,6 +2110,10 @@ impl MDTableRowTrait for TypeSpec {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
@@ -1847,6 +2151,7 @@ impl MDTableRowTrait for TypeSpec {
 #[derive(Debug, Clone, Default)]
 pub struct ImplMap {
     pub mapping_flags: Vec<enums::ClrPinvokeMap>,
+    pub raw_mapping_flags: u16,
     pub member_forwarded: codedindex::MemberForwarded,
     pub import_name: String,
     pub import_scope: codedindex::SimpleCodedIndex, //moduleref
@@ -1857,6 +2162,10 @@ impl MDTableRowTrait for ImplMap {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         str_offset_size: usize,
@@ -1900,7 +2209,8 @@ impl MDTableRowTrait for ImplMap {
         } else {
             return Err(Error::RefToUndefinedHeap("blob"));
         };
-        self.mapping_flags = enums::ClrPinvokeMap::new(crate::utils::read_usize(&data[0..s1])?);
+        self.raw_mapping_flags = crate::utils::read_usize(&data[0..s1])? as u16;
+        self.mapping_flags = enums::ClrPinvokeMap::new(self.raw_mapping_flags as usize);
         self.member_forwarded.set(&data[s1..s2], tables)?;
         self.import_name = strings_heap.get_string(&data[s2..s3])?;
         self.import_scope =
@@ -1911,8 +2221,8 @@ impl MDTableRowTrait for ImplMap {
 
 #[derive(Debug, Clone, Default)]
 pub struct FieldRva {
-    rva: u32,
-    field: codedindex::SimpleCodedIndex, //Field
+    pub(crate) rva: u32,
+    pub(crate) field: codedindex::SimpleCodedIndex, //Field
 }
 
 impl MDTableRowTrait for FieldRva {
@@ -1920,6 +2230,10 @@ impl MDTableRowTrait for FieldRva {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
@@ -1953,8 +2267,8 @@ impl MDTableRowTrait for FieldRva {
 
 #[derive(Debug, Clone, Default)]
 pub struct EncLog {
-    token: u32,
-    func_code: u32,
+    pub(crate) token: u32,
+    pub(crate) func_code: u32,
 }
 
 impl MDTableRowTrait for EncLog {
@@ -1962,6 +2276,10 @@ impl MDTableRowTrait for EncLog {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
@@ -1995,13 +2313,17 @@ impl MDTableRowTrait for EncLog {
 
 #[derive(Debug, Clone, Default)]
 pub struct EncMap {
-    token: u32,
+    pub(crate) token: u32,
 }
 impl MDTableRowTrait for EncMap {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
@@ -2033,15 +2355,17 @@ impl MDTableRowTrait for EncMap {
 
 #[derive(Debug, Clone, Default)]
 pub struct Assembly {
-    hash_alg_id: enums::AssemblyHashAlgorithm,
-    major_version: u32,
-    minor_version: u32,
-    build_number: u32,
-    revision_number: u32,
-    flags: Vec<enums::ClrAssemblyFlags>,
-    public_key: Vec<u8>,
-    name: String,
-    culture: String,
+    pub hash_alg_id: enums::AssemblyHashAlgorithm,
+    pub raw_hash_alg_id: u32,
+    pub major_version: u32,
+    pub minor_version: u32,
+    pub build_number: u32,
+    pub revision_number: u32,
+    pub flags: Vec<enums::ClrAssemblyFlags>,
+    pub raw_flags: u32,
+    pub public_key: Vec<u8>,
+    pub name: String,
+    pub culture: String,
 }
 
 impl MDTableRowTrait for Assembly {
@@ -2049,6 +2373,10 @@ impl MDTableRowTrait for Assembly {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         str_offset_size: usize,
@@ -2091,13 +2419,14 @@ impl MDTableRowTrait for Assembly {
         } else {
             return Err(Error::RefToUndefinedHeap("blob"));
         };
-        self.hash_alg_id =
-            enums::AssemblyHashAlgorithm::new(crate::utils::read_usize(&data[0..s1])?);
+        self.raw_hash_alg_id = crate::utils::read_usize(&data[0..s1])? as u32;
+        self.hash_alg_id = enums::AssemblyHashAlgorithm::new(self.raw_hash_alg_id as usize);
         self.major_version = crate::utils::read_usize(&data[s1..s2])? as u32;
         self.minor_version = crate::utils::read_usize(&data[s2..s3])? as u32;
         self.build_number = crate::utils::read_usize(&data[s3..s4])? as u32;
         self.revision_number = crate::utils::read_usize(&data[s4..s5])? as u32;
-        self.flags = enums::ClrAssemblyFlags::new(crate::utils::read_usize(&data[s5..s6])?);
+        self.raw_flags = crate::utils::read_usize(&data[s5..s6])? as u32;
+        self.flags = enums::ClrAssemblyFlags::new(self.raw_flags as usize);
         self.public_key = blobs_heap.get_blob(&data[s6..s7])?;
         self.name = strings_heap.get_string(&data[s7..s8])?;
         self.culture = strings_heap.get_string(&data[s8..s9])?;
@@ -2107,7 +2436,7 @@ impl MDTableRowTrait for Assembly {
 
 #[derive(Debug, Clone, Default)]
 pub struct AssemblyProcessor {
-    processor: u32,
+    pub(crate) processor: u32,
 }
 
 impl MDTableRowTrait for AssemblyProcessor {
@@ -2115,6 +2444,10 @@ impl MDTableRowTrait for AssemblyProcessor {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
@@ -2146,15 +2479,19 @@ impl MDTableRowTrait for AssemblyProcessor {
 
 #[derive(Debug, Clone, Default)]
 pub struct AssemblyOS {
-    os_platform_id: u32,
-    os_major_version: u32,
-    os_minor_version: u32,
+    pub(crate) os_platform_id: u32,
+    pub(crate) os_major_version: u32,
+    pub(crate) os_minor_version: u32,
 }
 impl MDTableRowTrait for AssemblyOS {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
@@ -2190,15 +2527,16 @@ impl MDTableRowTrait for AssemblyOS {
 
 #[derive(Debug, Clone, Default)]
 pub struct AssemblyRef {
-    major_version: u32,
-    minor_version: u32,
-    build_number: u32,
-    revision_number: u32,
-    flags: Vec<enums::ClrAssemblyFlags>,
-    public_key: Vec<u8>,
-    name: String,
-    culture: String,
-    hash_value: Vec<u8>,
+    pub major_version: u32,
+    pub minor_version: u32,
+    pub build_number: u32,
+    pub revision_number: u32,
+    pub flags: Vec<enums::ClrAssemblyFlags>,
+    pub raw_flags: u32,
+    pub public_key: Vec<u8>,
+    pub name: String,
+    pub culture: String,
+    pub hash_value: Vec<u8>,
 }
 
 impl MDTableRowTrait for AssemblyRef {
@@ -2206,6 +2544,10 @@ impl MDTableRowTrait for AssemblyRef {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         str_offset_size: usize,
@@ -2259,7 +2601,8 @@ impl MDTableRowTrait for AssemblyRef {
         self.minor_version = crate::utils::read_usize(&data[s1..s2])? as u32;
         self.build_number = crate::utils::read_usize(&data[s2..s3])? as u32;
         self.revision_number = crate::utils::read_usize(&data[s3..s4])? as u32;
-        self.flags = enums::ClrAssemblyFlags::new(crate::utils::read_usize(&data[s4..s5])?);
+        self.raw_flags = crate::utils::read_usize(&data[s4..s5])? as u32;
+        self.flags = enums::ClrAssemblyFlags::new(self.raw_flags as usize);
         self.public_key = blobs_heap.get_blob(&data[s5..s6])?;
         self.name = strings_heap.get_string(&data[s6..s7])?;
         self.culture = strings_heap.get_string(&data[s7..s8])?;
@@ -2270,8 +2613,8 @@ impl MDTableRowTrait for AssemblyRef {
 
 #[derive(Debug, Clone, Default)]
 pub struct AssemblyRefProcessor {
-    processor: u32,
-    assembly_ref: codedindex::SimpleCodedIndex, // AssemblyRef
+    pub(crate) processor: u32,
+    pub(crate) assembly_ref: codedindex::SimpleCodedIndex, // AssemblyRef
 }
 
 impl MDTableRowTrait for AssemblyRefProcessor {
@@ -2279,6 +2622,10 @@ impl MDTableRowTrait for AssemblyRefProcessor {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
@@ -2314,10 +2661,10 @@ impl MDTableRowTrait for AssemblyRefProcessor {
 
 #[derive(Debug, Clone, Default)]
 pub struct AssemblyRefOS {
-    os_platform_id: u32,
-    os_major_version: u32,
-    os_minor_version: u32,
-    assembly_ref: codedindex::SimpleCodedIndex, // AssemblyRef
+    pub(crate) os_platform_id: u32,
+    pub(crate) os_major_version: u32,
+    pub(crate) os_minor_version: u32,
+    pub(crate) assembly_ref: codedindex::SimpleCodedIndex, // AssemblyRef
 }
 
 impl MDTableRowTrait for AssemblyRefOS {
@@ -2325,6 +2672,10 @@ impl MDTableRowTrait for AssemblyRefOS {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
@@ -2366,9 +2717,10 @@ impl MDTableRowTrait for AssemblyRefOS {
 
 #[derive(Debug, Clone, Default)]
 pub struct File {
-    flags: Vec<enums::ClrFileFlags>,
-    name: String,
-    hash_value: Vec<u8>,
+    pub flags: Vec<enums::ClrFileFlags>,
+    pub raw_flags: u32,
+    pub name: String,
+    pub hash_value: Vec<u8>,
 }
 
 impl MDTableRowTrait for File {
@@ -2376,6 +2728,10 @@ impl MDTableRowTrait for File {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         str_offset_size: usize,
@@ -2412,7 +2768,8 @@ impl MDTableRowTrait for File {
         } else {
             return Err(Error::RefToUndefinedHeap("blob"));
         };
-        self.flags = enums::ClrFileFlags::new(crate::utils::read_usize(&data[0..s1])?);
+        self.raw_flags = crate::utils::read_usize(&data[0..s1])? as u32;
+        self.flags = enums::ClrFileFlags::new(self.raw_flags as usize);
         self.name = strings_heap.get_string(&data[s1..s2])?;
         self.hash_value = blobs_heap.get_blob(&data[s2..s3])?;
         Ok(())
@@ -2422,10 +2779,11 @@ impl MDTableRowTrait for File {
 #[derive(Debug, Clone, Default)]
 pub struct ExportedType {
     flags: enums::ClrTypeAttr,
-    type_def_id: u32,
-    type_name: String,
-    type_namespace: String,
-    implementation: codedindex::Implementation,
+    pub raw_flags: u32,
+    pub(crate) type_def_id: u32,
+    pub(crate) type_name: String,
+    pub(crate) type_namespace: String,
+    pub(crate) implementation: codedindex::Implementation,
 }
 
 impl MDTableRowTrait for ExportedType {
@@ -2433,6 +2791,10 @@ impl MDTableRowTrait for ExportedType {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         str_offset_size: usize,
@@ -2478,6 +2840,7 @@ impl MDTableRowTrait for ExportedType {
         } else {
             return Err(Error::RefToUndefinedHeap("string"));
         };
+        self.raw_flags = crate::utils::read_usize(&data[0..s1])? as u32;
         self.flags.set(&data[0..s1])?;
         self.type_def_id = crate::utils::read_usize(&data[s1..s2])? as u32;
         self.type_name = strings_heap.get_string(&data[s2..s3])?;
@@ -2489,10 +2852,11 @@ impl MDTableRowTrait for ExportedType {
 
 #[derive(Debug, Clone, Default)]
 pub struct ManifestResource {
-    offset: u32,
-    flags: Vec<enums::ClrManifestResourceFlags>,
-    name: String,
-    implementation: codedindex::Implementation,
+    pub offset: u32,
+    pub flags: Vec<enums::ClrManifestResourceFlags>,
+    pub raw_flags: u32,
+    pub name: String,
+    pub implementation: codedindex::Implementation,
 }
 
 impl MDTableRowTrait for ManifestResource {
@@ -2500,6 +2864,10 @@ impl MDTableRowTrait for ManifestResource {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         str_offset_size: usize,
@@ -2544,7 +2912,8 @@ impl MDTableRowTrait for ManifestResource {
             return Err(Error::RefToUndefinedHeap("string"));
         };
         self.offset = crate::utils::read_usize(&data[0..s1])? as u32;
-        self.flags = enums::ClrManifestResourceFlags::new(crate::utils::read_usize(&data[s1..s2])?);
+        self.raw_flags = crate::utils::read_usize(&data[s1..s2])? as u32;
+        self.flags = enums::ClrManifestResourceFlags::new(self.raw_flags as usize);
         self.name = strings_heap.get_string(&data[s2..s3])?;
         self.implementation.set(&data[s3..s4], tables)?;
         Ok(())
@@ -2553,8 +2922,8 @@ impl MDTableRowTrait for ManifestResource {
 
 #[derive(Debug, Clone, Default)]
 pub struct NestedClass {
-    nested_class: codedindex::SimpleCodedIndex,    //  TypeDef,
-    enclosing_class: codedindex::SimpleCodedIndex, // TypeDef
+    pub nested_class: codedindex::SimpleCodedIndex, //  TypeDef,
+    pub enclosing_class: codedindex::SimpleCodedIndex, // TypeDef
 }
 
 impl MDTableRowTrait for NestedClass {
@@ -2562,6 +2931,10 @@ impl MDTableRowTrait for NestedClass {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
@@ -2599,10 +2972,11 @@ impl MDTableRowTrait for NestedClass {
 
 #[derive(Debug, Clone, Default)]
 pub struct GenericParam {
-    number: u32,
-    flags: Vec<enums::ClrGenericParamAttr>,
-    owner: codedindex::TypeOrMethodDef,
-    name: String,
+    pub number: u32,
+    pub flags: Vec<enums::ClrGenericParamAttr>,
+    pub raw_flags: u16,
+    pub owner: codedindex::TypeOrMethodDef,
+    pub name: String,
 }
 
 impl MDTableRowTrait for GenericParam {
@@ -2610,6 +2984,10 @@ impl MDTableRowTrait for GenericParam {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         str_offset_size: usize,
@@ -2654,7 +3032,8 @@ impl MDTableRowTrait for GenericParam {
             return Err(Error::RefToUndefinedHeap("string"));
         };
         self.number = crate::utils::read_usize(&data[0..s1])? as u32;
-        self.flags = enums::ClrGenericParamAttr::new(crate::utils::read_usize(&data[s1..s2])?);
+        self.raw_flags = crate::utils::read_usize(&data[s1..s2])? as u16;
+        self.flags = enums::ClrGenericParamAttr::new(self.raw_flags as usize);
         self.owner.set(&data[s2..s3], tables)?;
         self.name = strings_heap.get_string(&data[s3..s4])?;
         Ok(())
@@ -2662,16 +3041,20 @@ impl MDTableRowTrait for GenericParam {
 }
 
 #[derive(Debug, Clone, Default)]
-pub struct GenericMethod {
-    unknown1: codedindex::MethodDefOrRef,
-    unknown2: Vec<u8>,
+pub struct MethodSpec {
+    pub method: codedindex::MethodDefOrRef,
+    pub instantiation: Vec<u8>,
 }
 
-impl MDTableRowTrait for GenericMethod {
+impl MDTableRowTrait for MethodSpec {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
@@ -2680,8 +3063,8 @@ impl MDTableRowTrait for GenericMethod {
         tables_row_counts: &[usize],
     ) -> usize {
         codedindex::clr_coded_index_struct_size(
-            self.unknown1.tag_bits,
-            &self.unknown1.table_names,
+            self.method.tag_bits,
+            &self.method.table_names,
             tables_row_counts,
         ) + blobs_offset_size
     }
@@ -2700,8 +3083,8 @@ impl MDTableRowTrait for GenericMethod {
         _guids_heap: &Option<&crate::stream::ClrStream>,
     ) -> Result<()> {
         let s1 = codedindex::clr_coded_index_struct_size(
-            self.unknown1.tag_bits,
-            &self.unknown1.table_names,
+            self.method.tag_bits,
+            &self.method.table_names,
             tables_row_counts,
         );
         let s2 = s1 + blobs_offset_size;
@@ -2710,16 +3093,16 @@ impl MDTableRowTrait for GenericMethod {
         } else {
             return Err(Error::RefToUndefinedHeap("blob"));
         };
-        self.unknown1.set(&data[0..s1], tables)?;
-        self.unknown2 = blobs_heap.get_blob(&data[s1..s2])?;
+        self.method.set(&data[0..s1], tables)?;
+        self.instantiation = blobs_heap.get_blob(&data[s1..s2])?;
         Ok(())
     }
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct GenericParamConstraint {
-    owner: codedindex::SimpleCodedIndex, //  GenericParam,
-    constraint: codedindex::TypeDefOrRef,
+    pub owner: codedindex::SimpleCodedIndex, //  GenericParam,
+    pub constraint: codedindex::TypeDefOrRef,
 }
 
 impl MDTableRowTrait for GenericParamConstraint {
@@ -2727,6 +3110,10 @@ impl MDTableRowTrait for GenericParamConstraint {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
@@ -2778,6 +3165,10 @@ impl MDTableRowTrait for Unused {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
@@ -2813,6 +3204,10 @@ impl MDTableRowTrait for MaxTable {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
     fn size(
         &self,
         _str_offset_size: usize,
@@ -2851,6 +3246,12 @@ pub struct MetaDataTable {
 }
 
 impl MetaDataTable {
+    pub fn number(&self) -> usize {
+        self.number
+    }
+    pub fn is_sorted(&self) -> bool {
+        self.is_sorted
+    }
     pub fn set_data(&mut self, data: &[u8]) -> Result<()> {
         self.table.set_data(data)
     }
@@ -2876,6 +3277,42 @@ impl MetaDataTable {
             .ok_or_else(|| Error::RowIndexOutOfBound(i, self.row_count()))?;
         Ok(res)
     }
+
+    pub fn row_mut<T>(&mut self, i: usize) -> Result<&mut T>
+    where
+        T: MDTableRowTrait + 'static,
+    {
+        let row_count = self.row_count();
+        let r = self.get_mut_row(i)?;
+        let res = r
+            .get_mut_row()
+            .as_any_mut()
+            .downcast_mut::<T>()
+            .ok_or(Error::RowIndexOutOfBound(i, row_count))?;
+        Ok(res)
+    }
+
+    /// Appends `row` and returns its rid. Rows are removed through
+    /// [`crate::MetaData::remove_row`], which also renumbers the rows that
+    /// refer to them.
+    pub fn push_row<T>(&mut self, row: T) -> Result<usize>
+    where
+        T: MDTableRowTrait + 'static,
+    {
+        self.table.push_row(&row)?;
+        self.num_rows = self.row_count();
+        Ok(self.num_rows)
+    }
+
+    pub(crate) fn remove_row(&mut self, i: usize) -> Result<()> {
+        self.table.remove_row(i)?;
+        self.num_rows = self.row_count();
+        Ok(())
+    }
+
+    pub(crate) fn index_columns(&mut self, i: usize) -> Result<Vec<IndexColumn<'_>>> {
+        self.table.index_columns(i)
+    }
 }
 
 impl crate::DnPe {
@@ -3284,8 +3721,8 @@ impl crate::DnPe {
                 blobs_offset_size,
                 tables_row_counts,
             )?)),
-            43 => Ok(Box::new(self.new_table::<GenericMethod>(
-                "GenericMethod",
+            43 => Ok(Box::new(self.new_table::<MethodSpec>(
+                "MethodSpec",
                 num_rows,
                 strings_offset_size,
                 guids_offset_size,
@@ -3417,7 +3854,7 @@ pub fn table_name_2_index(name: &'static str) -> Result<usize> {
         "ManifestResource" => Ok(40),
         "NestedClass" => Ok(41),
         "GenericParam" => Ok(42),
-        "GenericMethod" => Ok(43),
+        "MethodSpec" => Ok(43),
         "GenericParamConstraint" => Ok(44),
         // 45 through 63 are not used
         "Unused" => Ok(62),
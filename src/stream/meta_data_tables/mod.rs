@@ -12,24 +12,40 @@ const MAX_TABLES: usize = 64;
 
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct MetaDataTable {
-    //#[serde(skip_serializing)]
-    //data: Vec<u8>,
     rva: u32,
+    uncompressed: bool,
+    header: Option<MDTablesStruct>,
     pub tables: std::collections::BTreeMap<usize, mdtables::MetaDataTable>,
 }
 
+impl MetaDataTable {
+    pub fn rva(&self) -> u32 {
+        self.rva
+    }
+
+    pub fn header(&self) -> Option<&MDTablesStruct> {
+        self.header.as_ref()
+    }
+
+    pub fn is_uncompressed(&self) -> bool {
+        self.uncompressed
+    }
+}
+
 impl crate::DnPe {
     pub fn new_meta_data_table(
         &self,
         metadata_rva: &u32,
         stream_offset: &u32,
         _stream_size: &usize,
-        _stream_name: &str,
+        stream_name: &str,
         _stream_data: Vec<u8>,
     ) -> Result<super::Stream> {
         Ok(super::Stream::MetaDataTables(MetaDataTable {
             //data: stream_data,
             rva: metadata_rva + stream_offset,
+            uncompressed: stream_name == "#-",
+            header: None,
             tables: std::collections::BTreeMap::new(),
         }))
     }
@@ -42,17 +58,21 @@ impl crate::DnPe {
         let mut tables = std::collections::BTreeMap::new();
         let header_len = std::mem::size_of::<MDTablesStruct>();
         let header: MDTablesStruct = self.get_data(&s.rva, &header_len)?;
-        let strings_offset_size = if header.heap_offset_sizes & STRINGS_MASK != 0 {
+        s.header = Some(header.clone());
+        // minimal ENC deltas (#- with a #JTD marker stream) always use 4 byte
+        // heap indexes; a plain #- honours the heap size flags like #~ does
+        let large_heaps = s.uncompressed && stream_map.contains_key("#JTD");
+        let strings_offset_size = if large_heaps || header.heap_offset_sizes & STRINGS_MASK != 0 {
             4
         } else {
             2
         };
-        let guids_offset_size = if header.heap_offset_sizes & GUIDS_MASK != 0 {
+        let guids_offset_size = if large_heaps || header.heap_offset_sizes & GUIDS_MASK != 0 {
             4
         } else {
             2
         };
-        let blobs_offset_size = if header.heap_offset_sizes & BLOBS_MASK != 0 {
+        let blobs_offset_size = if large_heaps || header.heap_offset_sizes & BLOBS_MASK != 0 {
             4
         } else {
             2
@@ -123,13 +143,13 @@ impl crate::DnPe {
 }
 
 #[repr(C)]
-#[derive(serde::Deserialize, Debug, scroll::Pread)]
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, scroll::Pread)]
 pub struct MDTablesStruct {
-    reserved_1: u32,
-    major_version: u8,
-    minor_version: u8,
-    heap_offset_sizes: u8,
-    reserved_2: u8,
-    mask_valid: u64,
-    mask_sorted: u64,
+    pub reserved_1: u32,
+    pub major_version: u8,
+    pub minor_version: u8,
+    pub heap_offset_sizes: u8,
+    pub reserved_2: u8,
+    pub mask_valid: u64,
+    pub mask_sorted: u64,
 }
@@ -17,6 +17,13 @@ pub enum Stream {
     UserStringHeap(user_string_heap::UserStringHeap),
 }
 
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct StreamHeader {
+    pub name: String,
+    pub offset: u32,
+    pub size: u32,
+}
+
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct ClrStream {
     pub name: String,
@@ -109,14 +116,13 @@ impl crate::DnPe {
                     stream_name,
                     stream_data,
                 )?,
-                "_" => self.new_generic_stream(
+                _ => self.new_generic_stream(
                     metadata_rva,
                     stream_offset,
                     stream_size,
                     stream_name,
                     stream_data,
                 )?,
-                &_ => return Err(Error::UndefinedStream),
             },
         })
     }
@@ -7,6 +7,10 @@ pub struct StringHeap {
 }
 
 impl StringHeap {
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
     pub fn get(&self, index: usize) -> Result<String> {
         if index >= self.data.len() {
             return Err(Error::StringHeapReadOutOfBound(index, self.data.len()));
@@ -21,6 +25,40 @@ impl StringHeap {
         }
         Ok(String::from_utf8_lossy(&res_buf).to_string())
     }
+
+    pub fn iter(&self) -> StringHeapIter<'_> {
+        StringHeapIter {
+            data: &self.data,
+            offset: 0,
+        }
+    }
+}
+
+pub struct StringHeapIter<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl Iterator for StringHeapIter<'_> {
+    type Item = (usize, String);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.offset < self.data.len() {
+            let start = self.offset;
+            let len = self.data[start..]
+                .iter()
+                .position(|b| *b == 0)
+                .unwrap_or(self.data.len() - start);
+            self.offset = start + len + 1;
+            if len > 0 {
+                return Some((
+                    start,
+                    String::from_utf8_lossy(&self.data[start..start + len]).to_string(),
+                ));
+            }
+        }
+        None
+    }
 }
 
 impl crate::DnPe {
@@ -7,6 +7,10 @@ pub struct UserStringHeap {
 }
 
 impl UserStringHeap {
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
     pub fn get(&self, index: usize) -> Result<Vec<u8>> {
         if index >= self.data.len() {
             return Err(Error::UserStringHeapReadOutOfBound(index, self.data.len()));
@@ -15,7 +19,7 @@ impl UserStringHeap {
         let (data_length, length_size) = crate::utils::read_compressed_usize(
             self.data
                 .get(index..)
-                .ok_or_else(|| Error::UserStringHeapReadOutOfBound(index, self.data.len()))?,
+                .ok_or(Error::UserStringHeapReadOutOfBound(index, self.data.len()))?,
         )?;
 
         let end_index = index
@@ -35,6 +39,50 @@ impl UserStringHeap {
             .collect();
         Ok(String::from_utf16(&utf16)?)
     }
+
+    pub fn iter(&self) -> UserStringHeapIter<'_> {
+        UserStringHeapIter {
+            data: &self.data,
+            offset: 0,
+        }
+    }
+}
+
+pub struct UserStringHeapIter<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl Iterator for UserStringHeapIter<'_> {
+    type Item = (usize, String, Option<u8>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.offset < self.data.len() {
+            let start = self.offset;
+            let (data_length, length_size) =
+                crate::utils::read_compressed_usize(&self.data[start..]).ok()?;
+            let end = start + length_size + data_length;
+            if end > self.data.len() {
+                self.offset = self.data.len();
+                return None;
+            }
+            self.offset = end;
+            if data_length > 0 {
+                let entry = &self.data[start + length_size..end];
+                let utf16: Vec<u16> = entry
+                    .chunks_exact(2)
+                    .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+                    .collect();
+                let flag = if data_length % 2 == 1 {
+                    Some(entry[data_length - 1])
+                } else {
+                    None
+                };
+                return Some((start, String::from_utf16_lossy(&utf16), flag));
+            }
+        }
+        None
+    }
 }
 
 impl crate::DnPe {
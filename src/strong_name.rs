@@ -0,0 +1,248 @@
+use crate::{
+    error::Error,
+    stream::meta_data_tables::mdtables::{enums::AssemblyHashAlgorithm, Assembly},
+    ClrHeaderFlags, DnPe, Result,
+};
+use sha1::Digest;
+use sha2::digest::DynDigest;
+
+const PUBLIC_KEY_BLOB: u8 = 0x06;
+const RSA1_MAGIC: u32 = 0x3141_5352;
+const CHECKSUM_OFFSET: usize = 64;
+const SECURITY_DIRECTORY_INDEX: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum StrongNameStatus {
+    NotSigned,
+    DelaySigned,
+    Signed,
+    Valid,
+    Tampered,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct StrongName {
+    pub status: StrongNameStatus,
+    pub public_key: Vec<u8>,
+    pub public_key_token: Vec<u8>,
+    pub hash_algorithm: AssemblyHashAlgorithm,
+    pub signature_rva: u32,
+    pub signature: Vec<u8>,
+}
+
+struct RsaPublicKey {
+    hash_algorithm: AssemblyHashAlgorithm,
+    exponent: u32,
+    modulus: Vec<u8>,
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn parse_public_key(public_key: &[u8]) -> Option<RsaPublicKey> {
+    let hash_algorithm = match read_u32(public_key, 4)? {
+        0 => AssemblyHashAlgorithm::Sha1,
+        id => AssemblyHashAlgorithm::new(id as usize),
+    };
+    let blob = public_key.get(12..)?;
+    if *blob.first()? != PUBLIC_KEY_BLOB || read_u32(blob, 8)? != RSA1_MAGIC {
+        return None;
+    }
+    let bit_len = read_u32(blob, 12)? as usize;
+    let exponent = read_u32(blob, 16)?;
+    let mut modulus = blob.get(20..20 + bit_len / 8)?.to_vec();
+    modulus.reverse();
+    Some(RsaPublicKey {
+        hash_algorithm,
+        exponent,
+        modulus,
+    })
+}
+
+pub fn public_key_token(public_key: &[u8]) -> Vec<u8> {
+    let hash = sha1::Sha1::digest(public_key);
+    hash.iter().rev().take(8).copied().collect()
+}
+
+pub(crate) fn hasher(algorithm: AssemblyHashAlgorithm) -> Result<Box<dyn DynDigest>> {
+    Ok(match algorithm {
+        AssemblyHashAlgorithm::Sha1 => Box::new(sha1::Sha1::new()),
+        AssemblyHashAlgorithm::Sha256 => Box::new(sha2::Sha256::new()),
+        AssemblyHashAlgorithm::Sha384 => Box::new(sha2::Sha384::new()),
+        AssemblyHashAlgorithm::Sha512 => Box::new(sha2::Sha512::new()),
+        a => {
+            return Err(Error::FormatError(format!(
+                "unsupported hash algorithm {:?}",
+                a
+            )))
+        }
+    })
+}
+
+pub(crate) fn digest_info_prefix(algorithm: AssemblyHashAlgorithm) -> &'static [u8] {
+    match algorithm {
+        AssemblyHashAlgorithm::Sha1 => &[
+            0x30, 0x21, 0x30, 0x09, 0x06, 0x05, 0x2b, 0x0e, 0x03, 0x02, 0x1a, 0x05, 0x00, 0x04,
+            0x14,
+        ],
+        AssemblyHashAlgorithm::Sha256 => &[
+            0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
+            0x01, 0x05, 0x00, 0x04, 0x20,
+        ],
+        AssemblyHashAlgorithm::Sha384 => &[
+            0x30, 0x41, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
+            0x02, 0x05, 0x00, 0x04, 0x30,
+        ],
+        AssemblyHashAlgorithm::Sha512 => &[
+            0x30, 0x51, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
+            0x03, 0x05, 0x00, 0x04, 0x40,
+        ],
+        _ => &[],
+    }
+}
+
+pub(crate) fn rsa_pkcs1_verify(
+    modulus: &[u8],
+    exponent: &[u8],
+    signature: &[u8],
+    algorithm: AssemblyHashAlgorithm,
+    hash: &[u8],
+) -> bool {
+    let n = num_bigint::BigUint::from_bytes_be(modulus);
+    let s = num_bigint::BigUint::from_bytes_be(signature);
+    if s >= n {
+        return false;
+    }
+    let m = s
+        .modpow(&num_bigint::BigUint::from_bytes_be(exponent), &n)
+        .to_bytes_be();
+    let prefix = digest_info_prefix(algorithm);
+    let len = modulus.iter().skip_while(|b| **b == 0).count();
+    let t_len = prefix.len() + hash.len();
+    if len < t_len + 11 || m.len() != len - 1 {
+        return false;
+    }
+    let padding = len - t_len - 3;
+    m[0] == 0x01
+        && m[1..=padding].iter().all(|b| *b == 0xff)
+        && m[padding + 1] == 0x00
+        && m[padding + 2..padding + 2 + prefix.len()] == *prefix
+        && m[padding + 2 + prefix.len()..] == *hash
+}
+
+impl DnPe {
+    pub fn strong_name(&self) -> Result<StrongName> {
+        let net = self.net()?;
+        let clr_struct = self.clr_struct()?;
+        let public_key = match net.md_table("Assembly") {
+            Ok(t) if t.row_count() > 0 => t.row::<Assembly>(0)?.public_key.clone(),
+            _ => vec![],
+        };
+        let key = parse_public_key(&public_key);
+        let mut res = StrongName {
+            status: StrongNameStatus::NotSigned,
+            public_key_token: if public_key.is_empty() {
+                vec![]
+            } else {
+                public_key_token(&public_key)
+            },
+            public_key,
+            hash_algorithm: key
+                .as_ref()
+                .map(|k| k.hash_algorithm)
+                .unwrap_or(AssemblyHashAlgorithm::Sha1),
+            signature_rva: clr_struct.strong_name_signature_rva,
+            signature: vec![],
+        };
+        if res.public_key.is_empty()
+            || clr_struct.strong_name_signature_rva == 0
+            || clr_struct.strong_name_signature_size == 0
+        {
+            return Ok(res);
+        }
+        let offset = self.offset(clr_struct.strong_name_signature_rva)?;
+        let size = clr_struct.strong_name_signature_size as usize;
+        res.signature = self
+            .data
+            .get(offset..offset + size)
+            .ok_or(Error::NotEnoughData(self.data.len(), offset + size))?
+            .to_vec();
+        if !net.flags.contains(&ClrHeaderFlags::StrongNamesSigned)
+            || res.signature.iter().all(|b| *b == 0)
+        {
+            res.status = StrongNameStatus::DelaySigned;
+            return Ok(res);
+        }
+        let key = match key {
+            Some(k) => k,
+            None => {
+                res.status = StrongNameStatus::Signed;
+                return Ok(res);
+            }
+        };
+        let hash = self.strong_name_hash(key.hash_algorithm, offset, size)?;
+        let mut signature = res.signature.clone();
+        signature.reverse();
+        res.status = if rsa_pkcs1_verify(
+            &key.modulus,
+            &key.exponent.to_be_bytes(),
+            &signature,
+            key.hash_algorithm,
+            &hash,
+        ) {
+            StrongNameStatus::Valid
+        } else {
+            StrongNameStatus::Tampered
+        };
+        Ok(res)
+    }
+
+    fn strong_name_hash(
+        &self,
+        algorithm: AssemblyHashAlgorithm,
+        signature_offset: usize,
+        signature_size: usize,
+    ) -> Result<Vec<u8>> {
+        let pe = self.pe()?;
+        let mut hasher = hasher(algorithm)?;
+        let nt_headers = pe.header.dos_header.pe_pointer as usize;
+        let optional_header = nt_headers + 24;
+        let section_headers =
+            optional_header + pe.header.coff_header.size_of_optional_header as usize;
+        let sections_end = section_headers + pe.sections.len() * 40;
+        let mut headers = self
+            .data
+            .get(..sections_end)
+            .ok_or(Error::NotEnoughData(self.data.len(), sections_end))?
+            .to_vec();
+        let data_directories = optional_header + if pe.is_64 { 112 } else { 96 };
+        let security_directory = data_directories + SECURITY_DIRECTORY_INDEX * 8;
+        for range in [
+            optional_header + CHECKSUM_OFFSET..optional_header + CHECKSUM_OFFSET + 4,
+            security_directory..security_directory + 8,
+        ] {
+            if let Some(bytes) = headers.get_mut(range) {
+                bytes.fill(0);
+            }
+        }
+        hasher.update(&headers);
+        let signature = signature_offset..signature_offset + signature_size;
+        for section in &pe.sections {
+            let start = section.pointer_to_raw_data as usize;
+            let end = (start + section.size_of_raw_data as usize).min(self.data.len());
+            if start >= end {
+                continue;
+            }
+            if signature.start >= start && signature.end <= end {
+                hasher.update(&self.data[start..signature.start]);
+                hasher.update(&self.data[signature.end..end]);
+            } else {
+                hasher.update(&self.data[start..end]);
+            }
+        }
+        Ok(hasher.finalize().to_vec())
+    }
+}
@@ -11,19 +11,42 @@ pub fn read_usize(data: &[u8]) -> Result<usize> {
 }
 
 pub fn read_compressed_usize(data: &[u8]) -> Result<(usize, usize)> {
-    if data[0] & 0x80 == 0 {
-        Ok((data[0] as usize, 1))
-    } else if data[0] & 0x40 == 0 {
-        let mut value = (data[0] as usize & 0x7F) << 8;
-        value |= data[1] as usize;
-        Ok((value, 2))
-    } else if data[0] & 0x20 == 0 {
-        let mut value = (data[0] as usize & 0x3F) << 24;
-        value |= (data[1] as usize) << 16;
-        value |= (data[2] as usize) << 8;
-        value |= data[3] as usize;
-        Ok((value, 4))
+    let size = match data.first() {
+        Some(b) if b & 0x80 == 0 => 1,
+        Some(b) if b & 0x40 == 0 => 2,
+        Some(b) if b & 0x20 == 0 => 4,
+        _ => return Err(Error::ReadCompressedUsize),
+    };
+    let data = data.get(..size).ok_or(Error::ReadCompressedUsize)?;
+    match size {
+        1 => Ok((data[0] as usize, 1)),
+        2 => Ok((((data[0] as usize & 0x7F) << 8) | data[1] as usize, 2)),
+        _ => Ok((
+            ((data[0] as usize & 0x3F) << 24)
+                | ((data[1] as usize) << 16)
+                | ((data[2] as usize) << 8)
+                | data[3] as usize,
+            4,
+        )),
+    }
+}
+
+pub fn write_compressed_usize(value: usize) -> Result<Vec<u8>> {
+    if value < 0x80 {
+        Ok(vec![value as u8])
+    } else if value < 0x4000 {
+        Ok(vec![0x80 | (value >> 8) as u8, value as u8])
+    } else if value < 0x20000000 {
+        Ok(vec![
+            0xC0 | (value >> 24) as u8,
+            (value >> 16) as u8,
+            (value >> 8) as u8,
+            value as u8,
+        ])
     } else {
-        Err(Error::ReadCompressedUsize)
+        Err(Error::FormatError(format!(
+            "{} is too large for a compressed integer",
+            value
+        )))
     }
 }
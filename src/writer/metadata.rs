@@ -0,0 +1,544 @@
+use crate::{
+    error::Error,
+    stream::meta_data_tables::mdtables::{
+        codedindex::{self, CodedIndex},
+        table_name_2_index, EventPtr, FieldPtr, MetaDataTable, MethodPtr, ParamPtr, PropertyPtr,
+    },
+    Result,
+};
+
+const METADATA_SIGNATURE: u32 = 0x424A5342;
+const STRINGS_MASK: u8 = 0x01;
+const GUIDS_MASK: u8 = 0x02;
+const BLOBS_MASK: u8 = 0x04;
+const MAX_TABLES: usize = 64;
+const PTR_TABLES: [&str; 5] = [
+    "FieldPtr",
+    "MethodPtr",
+    "ParamPtr",
+    "EventPtr",
+    "PropertyPtr",
+];
+const PTR_TARGETS: [&str; 5] = ["Field", "MethodDef", "Param", "Event", "Property"];
+
+fn heap_offset_size(heap: &[u8]) -> usize {
+    if heap.len() < 0x10000 {
+        2
+    } else {
+        4
+    }
+}
+
+fn column_bytes(value: usize, size: usize) -> Result<Vec<u8>> {
+    let max = if size == 2 {
+        u16::MAX as usize
+    } else {
+        u32::MAX as usize
+    };
+    if value > max {
+        return Err(Error::MetaDataEncodeError(format!(
+            "value 0x{:x} does not fit a {} byte column",
+            value, size
+        )));
+    }
+    Ok(value.to_le_bytes()[..size].to_vec())
+}
+
+fn ptr_lists(
+    tables: &std::collections::BTreeMap<usize, MetaDataTable>,
+) -> Result<std::collections::HashMap<&'static str, Vec<usize>>> {
+    let mut res = std::collections::HashMap::new();
+    for (ptr, table) in PTR_TABLES.iter().zip(PTR_TARGETS) {
+        let t = match tables.get(&table_name_2_index(ptr)?) {
+            Some(t) if t.row_count() > 0 => t,
+            _ => continue,
+        };
+        let mut rows = vec![];
+        for i in 0..t.row_count() {
+            let row = t.get_row(i)?.get_row().as_any();
+            let index = if let Some(r) = row.downcast_ref::<FieldPtr>() {
+                &r.field
+            } else if let Some(r) = row.downcast_ref::<MethodPtr>() {
+                &r.method
+            } else if let Some(r) = row.downcast_ref::<ParamPtr>() {
+                &r.param
+            } else if let Some(r) = row.downcast_ref::<EventPtr>() {
+                &r.event
+            } else if let Some(r) = row.downcast_ref::<PropertyPtr>() {
+                &r.property
+            } else {
+                return Err(Error::IncorrectCastTo(ptr, file!(), line!()));
+            };
+            rows.push(index.row_index());
+        }
+        res.insert(table, rows);
+    }
+    Ok(res)
+}
+
+fn pad(data: &mut Vec<u8>) {
+    data.resize(super::align(data.len(), 4), 0);
+}
+
+#[derive(Debug)]
+struct StringHeapBuilder {
+    data: Vec<u8>,
+    offsets: std::collections::HashMap<String, usize>,
+}
+
+impl StringHeapBuilder {
+    fn new() -> Self {
+        Self {
+            data: vec![0],
+            offsets: std::collections::HashMap::new(),
+        }
+    }
+
+    fn add(&mut self, value: &str) -> usize {
+        if value.is_empty() {
+            return 0;
+        }
+        if let Some(offset) = self.offsets.get(value) {
+            return *offset;
+        }
+        let offset = self.data.len();
+        self.data.extend_from_slice(value.as_bytes());
+        self.data.push(0);
+        self.offsets.insert(value.to_string(), offset);
+        offset
+    }
+}
+
+#[derive(Debug)]
+struct BlobHeapBuilder {
+    data: Vec<u8>,
+    offsets: std::collections::HashMap<Vec<u8>, usize>,
+}
+
+impl BlobHeapBuilder {
+    fn new() -> Self {
+        Self {
+            data: vec![0],
+            offsets: std::collections::HashMap::new(),
+        }
+    }
+
+    fn add(&mut self, value: &[u8]) -> Result<usize> {
+        if value.is_empty() {
+            return Ok(0);
+        }
+        if let Some(offset) = self.offsets.get(value) {
+            return Ok(*offset);
+        }
+        let offset = self.data.len();
+        self.data
+            .extend(crate::utils::write_compressed_usize(value.len())?);
+        self.data.extend_from_slice(value);
+        self.offsets.insert(value.to_vec(), offset);
+        Ok(offset)
+    }
+}
+
+#[derive(Debug)]
+struct GuidHeapBuilder {
+    data: Vec<u8>,
+    indexes: std::collections::HashMap<uuid::Uuid, usize>,
+}
+
+impl GuidHeapBuilder {
+    fn new() -> Self {
+        Self {
+            data: vec![],
+            indexes: std::collections::HashMap::new(),
+        }
+    }
+
+    fn add(&mut self, value: &uuid::Uuid) -> usize {
+        if value.is_nil() {
+            return 0;
+        }
+        if let Some(index) = self.indexes.get(value) {
+            return *index;
+        }
+        self.data.extend_from_slice(value.as_bytes());
+        let index = self.data.len() / 16;
+        self.indexes.insert(*value, index);
+        index
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Column {
+    Fixed(usize),
+    String(usize),
+    Blob(usize),
+    Guid(usize),
+    Index {
+        size: usize,
+        tag_bits: usize,
+        tables: Vec<&'static str>,
+        list: bool,
+    },
+}
+
+impl Column {
+    pub fn size(&self) -> usize {
+        match self {
+            Self::Fixed(s) | Self::String(s) | Self::Blob(s) | Self::Guid(s) => *s,
+            Self::Index { size, .. } => *size,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct TableWriter {
+    strings: StringHeapBuilder,
+    blobs: BlobHeapBuilder,
+    guids: GuidHeapBuilder,
+    str_offset_size: usize,
+    guids_offset_size: usize,
+    blobs_offset_size: usize,
+    tables_row_counts: Vec<usize>,
+    data: Vec<u8>,
+    pending_lists: std::collections::HashMap<&'static str, Vec<(usize, usize)>>,
+    ptr_lists: std::collections::HashMap<&'static str, Vec<usize>>,
+    columns: Option<Vec<Column>>,
+}
+
+impl TableWriter {
+    fn new(tables_row_counts: Vec<usize>) -> Self {
+        Self {
+            strings: StringHeapBuilder::new(),
+            blobs: BlobHeapBuilder::new(),
+            guids: GuidHeapBuilder::new(),
+            str_offset_size: 4,
+            guids_offset_size: 4,
+            blobs_offset_size: 4,
+            tables_row_counts,
+            data: vec![],
+            pending_lists: std::collections::HashMap::new(),
+            ptr_lists: std::collections::HashMap::new(),
+            columns: None,
+        }
+    }
+
+    pub(crate) fn layout(
+        str_offset_size: usize,
+        guids_offset_size: usize,
+        blobs_offset_size: usize,
+        tables_row_counts: &[usize],
+    ) -> Self {
+        Self {
+            str_offset_size,
+            guids_offset_size,
+            blobs_offset_size,
+            columns: Some(vec![]),
+            ..Self::new(tables_row_counts.to_vec())
+        }
+    }
+
+    pub(crate) fn into_columns(self) -> Vec<Column> {
+        self.columns.unwrap_or_default()
+    }
+
+    fn column(&mut self, column: Column) {
+        if let Some(columns) = &mut self.columns {
+            columns.push(column);
+        }
+    }
+
+    fn write_sized(&mut self, value: usize, size: usize) -> Result<()> {
+        let column = column_bytes(value, size)?;
+        self.data.extend(column);
+        Ok(())
+    }
+
+    fn patch_sized(&mut self, pos: usize, value: usize, size: usize) -> Result<()> {
+        let column = column_bytes(value, size)?;
+        self.data[pos..pos + size].copy_from_slice(&column);
+        Ok(())
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.column(Column::Fixed(1));
+        self.data.push(value);
+    }
+
+    pub fn write_u16(&mut self, value: u16) {
+        self.column(Column::Fixed(2));
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.column(Column::Fixed(4));
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_string(&mut self, value: &str) -> Result<()> {
+        self.column(Column::String(self.str_offset_size));
+        let offset = self.strings.add(value);
+        self.write_sized(offset, self.str_offset_size)
+    }
+
+    pub fn write_blob(&mut self, value: &[u8]) -> Result<()> {
+        self.column(Column::Blob(self.blobs_offset_size));
+        let offset = self.blobs.add(value)?;
+        self.write_sized(offset, self.blobs_offset_size)
+    }
+
+    pub fn write_guid(&mut self, value: &uuid::Uuid) -> Result<()> {
+        self.column(Column::Guid(self.guids_offset_size));
+        let index = self.guids.add(value);
+        self.write_sized(index, self.guids_offset_size)
+    }
+
+    pub fn write_coded_index(&mut self, index: &dyn CodedIndex) -> Result<()> {
+        let size = codedindex::clr_coded_index_struct_size(
+            index.get_tag_bits(),
+            &index.get_table_names().to_vec(),
+            &self.tables_row_counts,
+        );
+        self.column(Column::Index {
+            size,
+            tag_bits: index.get_tag_bits(),
+            tables: index.get_table_names().to_vec(),
+            list: false,
+        });
+        let value = index.value()?;
+        self.write_sized(value, size)
+    }
+
+    pub fn write_list(
+        &mut self,
+        table: &'static str,
+        list: &[codedindex::SimpleCodedIndex],
+    ) -> Result<()> {
+        let size =
+            codedindex::clr_coded_index_struct_size(0, &vec![table], &self.tables_row_counts);
+        self.column(Column::Index {
+            size,
+            tag_bits: 0,
+            tables: vec![table],
+            list: true,
+        });
+        match list.first() {
+            Some(first) => {
+                let index = match self.ptr_lists.get(table) {
+                    Some(ptr) => {
+                        ptr.iter()
+                            .position(|r| *r == first.row_index)
+                            .ok_or_else(|| {
+                                Error::MetaDataEncodeError(format!(
+                                    "{} row {} is missing from its Ptr table",
+                                    table, first.row_index
+                                ))
+                            })?
+                            + 1
+                    }
+                    None => first.row_index,
+                };
+                for (pos, size) in self.pending_lists.remove(table).unwrap_or_default() {
+                    self.patch_sized(pos, index, size)?;
+                }
+                self.write_sized(index, size)
+            }
+            None => {
+                self.pending_lists
+                    .entry(table)
+                    .or_default()
+                    .push((self.data.len(), size));
+                self.write_sized(0, size)
+            }
+        }
+    }
+
+    pub fn finish_table(&mut self) -> Result<()> {
+        for (table, pending) in std::mem::take(&mut self.pending_lists) {
+            let end = match self.ptr_lists.get(table) {
+                Some(ptr) => ptr.len() + 1,
+                None => self.tables_row_counts[table_name_2_index(table)?] + 1,
+            };
+            for (pos, size) in pending {
+                self.patch_sized(pos, end, size)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct MetaDataBuilder<'a> {
+    metadata: &'a crate::MetaData,
+    user_strings: Vec<u8>,
+    table_offsets: std::collections::BTreeMap<usize, (usize, usize)>,
+}
+
+impl<'a> MetaDataBuilder<'a> {
+    pub fn new(metadata: &'a crate::MetaData) -> Self {
+        Self {
+            metadata,
+            user_strings: vec![],
+            table_offsets: std::collections::BTreeMap::new(),
+        }
+    }
+
+    pub fn append_user_strings(&mut self, data: &[u8]) {
+        self.user_strings.extend_from_slice(data);
+    }
+
+    pub fn table_offset(&self, name: &'static str) -> Result<(usize, usize)> {
+        self.table_offsets
+            .get(&table_name_2_index(name)?)
+            .copied()
+            .ok_or(Error::UndefinedMetaDataTableName(name))
+    }
+
+    pub fn build(&mut self) -> Result<Vec<u8>> {
+        let tables = self.tables()?;
+        let mut tables_row_counts = vec![0; MAX_TABLES];
+        for (n, t) in tables {
+            tables_row_counts[*n] = t.row_count();
+        }
+
+        let mut w = TableWriter::new(tables_row_counts.clone());
+        w.ptr_lists = ptr_lists(tables)?;
+        for t in tables.values() {
+            t.table.write(&mut w)?;
+        }
+        // Ptr tables need the uncompressed #- layout; no #JTD stream is
+        // written, so its heap index sizes follow the flags as for #~
+        let uncompressed = PTR_TABLES
+            .iter()
+            .any(|p| table_name_2_index(p).is_ok_and(|i| tables_row_counts[i] > 0));
+        w.str_offset_size = heap_offset_size(&w.strings.data);
+        w.guids_offset_size = heap_offset_size(&w.guids.data);
+        w.blobs_offset_size = heap_offset_size(&w.blobs.data);
+        w.data.clear();
+
+        let mut heap_offset_sizes = 0;
+        if w.str_offset_size == 4 {
+            heap_offset_sizes |= STRINGS_MASK;
+        }
+        if w.guids_offset_size == 4 {
+            heap_offset_sizes |= GUIDS_MASK;
+        }
+        if w.blobs_offset_size == 4 {
+            heap_offset_sizes |= BLOBS_MASK;
+        }
+        let mut mask_valid = 0u64;
+        let mut mask_sorted = 0u64;
+        for (n, t) in tables {
+            mask_valid |= 1 << n;
+            if t.is_sorted() {
+                mask_sorted |= 1 << n;
+            }
+        }
+        let header_size = 24 + 4 * tables.len();
+        self.table_offsets.clear();
+        for (n, t) in tables {
+            let start = w.data.len();
+            t.table.write(&mut w)?;
+            let row_size = match t.row_count() {
+                0 => 0,
+                c => (w.data.len() - start) / c,
+            };
+            self.table_offsets
+                .insert(*n, (header_size + start, row_size));
+        }
+
+        let mut tables_stream = vec![];
+        tables_stream.extend_from_slice(&0u32.to_le_bytes());
+        tables_stream.extend_from_slice(&[2, 0, heap_offset_sizes, 1]);
+        tables_stream.extend_from_slice(&mask_valid.to_le_bytes());
+        tables_stream.extend_from_slice(&mask_sorted.to_le_bytes());
+        for n in tables.keys() {
+            tables_stream.extend_from_slice(&(tables_row_counts[*n] as u32).to_le_bytes());
+        }
+        tables_stream.extend_from_slice(&w.data);
+        pad(&mut tables_stream);
+
+        let tables_name = if uncompressed { "#-" } else { "#~" };
+        let mut streams = vec![(tables_name, tables_stream)];
+        let mut strings = w.strings.data;
+        pad(&mut strings);
+        streams.push(("#Strings", strings));
+        let mut user_strings = self.user_string_heap();
+        if !user_strings.is_empty() {
+            pad(&mut user_strings);
+            streams.push(("#US", user_strings));
+        }
+        if !w.guids.data.is_empty() {
+            streams.push(("#GUID", w.guids.data));
+        }
+        let mut blobs = w.blobs.data;
+        pad(&mut blobs);
+        streams.push(("#Blob", blobs));
+
+        self.root(streams)
+    }
+
+    fn tables(&self) -> Result<&'a std::collections::BTreeMap<usize, MetaDataTable>> {
+        for s in self.metadata.streams.values() {
+            if let crate::stream::Stream::MetaDataTables(mt) = &s.stream {
+                return Ok(&mt.tables);
+            }
+        }
+        Err(Error::UndefinedStream)
+    }
+
+    fn user_string_heap(&self) -> Vec<u8> {
+        let mut res = vec![];
+        for s in self.metadata.streams.values() {
+            if let crate::stream::Stream::UserStringHeap(us) = &s.stream {
+                res.extend_from_slice(us.data());
+            }
+        }
+        if res.is_empty() && !self.user_strings.is_empty() {
+            res.push(0);
+        }
+        res.extend_from_slice(&self.user_strings);
+        res
+    }
+
+    fn root(&mut self, streams: Vec<(&str, Vec<u8>)>) -> Result<Vec<u8>> {
+        let mut version = self
+            .metadata
+            ._version
+            .trim_end_matches('\0')
+            .as_bytes()
+            .to_vec();
+        version.push(0);
+        pad(&mut version);
+
+        let headers_size: usize = streams
+            .iter()
+            .map(|(name, _)| 8 + super::align(name.len() + 1, 4))
+            .sum();
+        let mut offset = 16 + version.len() + 4 + headers_size;
+        let mut res = vec![];
+        res.extend_from_slice(&METADATA_SIGNATURE.to_le_bytes());
+        res.extend_from_slice(&1u16.to_le_bytes());
+        res.extend_from_slice(&1u16.to_le_bytes());
+        res.extend_from_slice(&0u32.to_le_bytes());
+        res.extend_from_slice(&(version.len() as u32).to_le_bytes());
+        res.extend(version);
+        res.extend_from_slice(&self.metadata.flags.to_le_bytes());
+        res.extend_from_slice(&(streams.len() as u16).to_le_bytes());
+        for (name, data) in &streams {
+            res.extend_from_slice(&(offset as u32).to_le_bytes());
+            res.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            let mut name = name.as_bytes().to_vec();
+            name.push(0);
+            pad(&mut name);
+            res.extend(name);
+            offset += data.len();
+        }
+        let tables_offset = res.len();
+        for (_, data) in streams {
+            res.extend(data);
+        }
+        for (offset, _) in self.table_offsets.values_mut() {
+            *offset += tables_offset;
+        }
+        Ok(res)
+    }
+}
@@ -0,0 +1,448 @@
+use crate::{
+    error::Error,
+    lang::{
+        cil::function::{writer::MethodBody, Function},
+        clr::token::{Token, RID_MASK, TABLE_SHIFT},
+    },
+    stream::meta_data_tables::mdtables::table_name_2_index,
+    Result,
+};
+use scroll::{Pread, Pwrite, LE};
+
+pub mod metadata;
+pub mod rows;
+
+const SECTION_NAME: &[u8; 8] = b".dnfile\0";
+const SECTION_HEADER_SIZE: usize = 40;
+const CODE_SECTION_CHARACTERISTICS: u32 = 0x60000020;
+const DATA_SECTION_CHARACTERISTICS: u32 = 0x40000040;
+const SECURITY_DIRECTORY: usize = 4;
+const DEBUG_DIRECTORY: usize = 6;
+const DEBUG_ENTRY_SIZE: usize = 28;
+const USER_STRING_TABLE: usize = 0x70;
+
+fn align(value: usize, alignment: usize) -> usize {
+    if alignment == 0 {
+        value
+    } else {
+        value.div_ceil(alignment) * alignment
+    }
+}
+
+fn has_special_chars(utf16: &[u16]) -> bool {
+    utf16
+        .iter()
+        .any(|c| *c > 0xFF || matches!(*c, 0x01..=0x08 | 0x0E..=0x1F | 0x27 | 0x2D | 0x7F))
+}
+
+fn pe_checksum(data: &[u8], checksum_offset: usize) -> u32 {
+    let mut sum: u64 = 0;
+    for (i, chunk) in data.chunks(2).enumerate() {
+        if i * 2 == checksum_offset || i * 2 == checksum_offset + 2 {
+            continue;
+        }
+        let word = if chunk.len() == 2 {
+            u16::from_le_bytes([chunk[0], chunk[1]])
+        } else {
+            chunk[0] as u16
+        };
+        sum += word as u64;
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+    sum = (sum & 0xFFFF) + (sum >> 16);
+    (sum as u32).wrapping_add(data.len() as u32)
+}
+
+fn stream_header(metadata: &[u8], name: &str) -> Result<Option<(usize, u32, u32)>> {
+    let version_length: u32 = metadata.pread_with(12, LE)?;
+    let mut pos = 16 + version_length as usize + 2;
+    let number_of_streams: u16 = metadata.pread_with(pos, LE)?;
+    pos += 2;
+    for _ in 0..number_of_streams {
+        let offset: u32 = metadata.pread_with(pos, LE)?;
+        let size: u32 = metadata.pread_with(pos + 4, LE)?;
+        let name_start = pos + 8;
+        let name_end = match metadata[name_start..].iter().position(|c| *c == 0) {
+            Some(s) => name_start + s,
+            None => return Err(Error::PeWriteError("bad stream header".to_string())),
+        };
+        if &metadata[name_start..name_end] == name.as_bytes() {
+            return Ok(Some((pos, offset, size)));
+        }
+        pos = name_start + align(name_end - name_start + 1, 4);
+    }
+    Ok(None)
+}
+
+struct Headers {
+    coff_offset: usize,
+    opt_offset: usize,
+    data_directories_offset: usize,
+    section_table_offset: usize,
+    size_of_headers: usize,
+    file_alignment: usize,
+    section_alignment: usize,
+    sections: Vec<(usize, usize)>,
+    debug_entries: Vec<usize>,
+}
+
+impl Headers {
+    fn new(data: &[u8]) -> Result<Self> {
+        let coff_offset = data.pread_with::<u32>(0x3C, LE)? as usize + 4;
+        let opt_offset = coff_offset + 20;
+        let number_of_sections: u16 = data.pread_with(coff_offset + 2, LE)?;
+        let size_of_optional_header: u16 = data.pread_with(coff_offset + 16, LE)?;
+        let is_64 = data.pread_with::<u16>(opt_offset, LE)? == 0x20B;
+        let data_directories_offset = opt_offset + if is_64 { 112 } else { 96 };
+        let section_table_offset = opt_offset + size_of_optional_header as usize;
+        let mut sections = vec![];
+        let mut debug_entries = vec![];
+        let debug_rva: u32 = data.pread_with(data_directories_offset + DEBUG_DIRECTORY * 8, LE)?;
+        let debug_size: u32 =
+            data.pread_with(data_directories_offset + DEBUG_DIRECTORY * 8 + 4, LE)?;
+        for i in 0..number_of_sections as usize {
+            let header = section_table_offset + i * SECTION_HEADER_SIZE;
+            let virtual_size: u32 = data.pread_with(header + 8, LE)?;
+            let virtual_address: u32 = data.pread_with(header + 12, LE)?;
+            let size_of_raw_data: u32 = data.pread_with(header + 16, LE)?;
+            let pointer_to_raw_data: u32 = data.pread_with(header + 20, LE)?;
+            if debug_rva >= virtual_address
+                && debug_rva < virtual_address + std::cmp::max(virtual_size, size_of_raw_data)
+            {
+                let offset = (pointer_to_raw_data + debug_rva - virtual_address) as usize;
+                for i in 0..debug_size as usize / DEBUG_ENTRY_SIZE {
+                    debug_entries.push(offset + i * DEBUG_ENTRY_SIZE + 24);
+                }
+            }
+            sections.push((pointer_to_raw_data as usize, size_of_raw_data as usize));
+        }
+        Ok(Self {
+            coff_offset,
+            opt_offset,
+            data_directories_offset,
+            section_table_offset,
+            size_of_headers: data.pread_with::<u32>(opt_offset + 60, LE)? as usize,
+            file_alignment: data.pread_with::<u32>(opt_offset + 36, LE)? as usize,
+            section_alignment: data.pread_with::<u32>(opt_offset + 32, LE)? as usize,
+            sections,
+            debug_entries,
+        })
+    }
+
+    fn next_section_header(&self) -> usize {
+        self.section_table_offset + self.sections.len() * SECTION_HEADER_SIZE
+    }
+
+    fn first_raw_data(&self) -> usize {
+        self.sections
+            .iter()
+            .filter(|(pointer, _)| *pointer != 0)
+            .map(|(pointer, _)| *pointer)
+            .min()
+            .unwrap_or(self.size_of_headers)
+    }
+
+    fn has_room(&self, data: &[u8]) -> bool {
+        let start = self.next_section_header();
+        start + SECTION_HEADER_SIZE <= std::cmp::min(self.size_of_headers, self.first_raw_data())
+            && data[start..start + SECTION_HEADER_SIZE]
+                .iter()
+                .all(|c| *c == 0)
+    }
+
+    fn grow(&self, mut data: Vec<u8>) -> Result<Vec<u8>> {
+        let start = self.first_raw_data();
+        let size = align(SECTION_HEADER_SIZE, self.file_alignment);
+        if self.next_section_header() + SECTION_HEADER_SIZE > start + size {
+            return Err(Error::PeWriteError(
+                "no room for a new section header".to_string(),
+            ));
+        }
+        for entry in &self.debug_entries {
+            let pointer: u32 = data.pread_with(*entry, LE)?;
+            if pointer as usize >= start {
+                data.pwrite_with(pointer + size as u32, *entry, LE)?;
+            }
+        }
+        for (i, (pointer, _)) in self.sections.iter().enumerate() {
+            if *pointer >= start {
+                data.pwrite_with(
+                    (pointer + size) as u32,
+                    self.section_table_offset + i * SECTION_HEADER_SIZE + 20,
+                    LE,
+                )?;
+            }
+        }
+        data.pwrite_with(
+            std::cmp::max(self.size_of_headers, start + size) as u32,
+            self.opt_offset + 60,
+            LE,
+        )?;
+        self.shift_certificates(&mut data, start, size)?;
+        data.splice(start..start, std::iter::repeat_n(0, size));
+        data[self.next_section_header()..start].fill(0);
+        Ok(data)
+    }
+
+    fn shift_certificates(&self, data: &mut [u8], start: usize, size: usize) -> Result<()> {
+        let offset = self.data_directories_offset + SECURITY_DIRECTORY * 8;
+        let certificates: u32 = data.pread_with(offset, LE)?;
+        if certificates != 0 && certificates as usize >= start {
+            data.pwrite_with(certificates + size as u32, offset, LE)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct PeWriter<'a> {
+    pe: &'a crate::DnPe,
+    bodies: std::collections::BTreeMap<usize, Vec<u8>>,
+    user_strings: Vec<u8>,
+    user_string_tokens: std::collections::HashMap<String, Token>,
+    rebuild_metadata: bool,
+}
+
+impl<'a> PeWriter<'a> {
+    pub fn new(pe: &'a crate::DnPe) -> Self {
+        Self {
+            pe,
+            bodies: std::collections::BTreeMap::new(),
+            user_strings: vec![],
+            user_string_tokens: std::collections::HashMap::new(),
+            rebuild_metadata: false,
+        }
+    }
+
+    pub fn set_rebuild_metadata(&mut self, value: bool) {
+        self.rebuild_metadata = value;
+    }
+
+    pub fn set_raw_method_body(&mut self, method: &Token, body: Vec<u8>) -> Result<()> {
+        let table = self.pe.net()?.md_table("MethodDef")?;
+        if method.table() != table_name_2_index("MethodDef")? {
+            return Err(Error::IncorrectTableRequested(
+                "MethodDef",
+                file!(),
+                line!(),
+            ));
+        }
+        if method.rid() == 0 || method.rid() > table.row_count() {
+            return Err(Error::RowIndexOutOfBound(method.rid(), table.row_count()));
+        }
+        self.bodies.insert(method.rid(), body);
+        Ok(())
+    }
+
+    pub fn set_method_body(&mut self, method: &Token, body: &MethodBody) -> Result<()> {
+        let encoded = self.pe.net()?.encode_method_body(method, body)?;
+        self.set_raw_method_body(method, encoded)
+    }
+
+    pub fn set_function(&mut self, function: &Function) -> Result<()> {
+        let mut body = function.method_body();
+        body.max_stack = None;
+        self.set_method_body(&function.method_token, &body)
+    }
+
+    pub fn add_user_string(&mut self, value: &str) -> Result<Token> {
+        if let Some(token) = self.user_string_tokens.get(value) {
+            return Ok(token.clone());
+        }
+        let heap_size = match self.pe.net()?.metadata.streams.get("#US") {
+            Some(s) => s.size,
+            None => return Err(Error::RefToUndefinedHeap("user string")),
+        };
+        let offset = heap_size + self.user_strings.len();
+        if offset > RID_MASK {
+            return Err(Error::PeWriteError("#US heap is full".to_string()));
+        }
+        let utf16: Vec<u16> = value.encode_utf16().collect();
+        self.user_strings
+            .extend(crate::utils::write_compressed_usize(utf16.len() * 2 + 1)?);
+        for c in &utf16 {
+            self.user_strings.extend(c.to_le_bytes());
+        }
+        self.user_strings.push(has_special_chars(&utf16) as u8);
+        let token = Token::new((USER_STRING_TABLE << TABLE_SHIFT) | offset);
+        self.user_string_tokens
+            .insert(value.to_string(), token.clone());
+        Ok(token)
+    }
+
+    pub fn build(&self) -> Result<Vec<u8>> {
+        let net = self.pe.net()?;
+        let pe = self.pe.pe()?;
+        let opt = match pe.header.optional_header {
+            Some(s) => s,
+            None => return Err(Error::UnsupportedBinaryFormat("optional header absence")),
+        };
+        let section_alignment = opt.windows_fields.section_alignment as usize;
+        let section_va = align(
+            pe.sections
+                .iter()
+                .map(|s| {
+                    s.virtual_address as usize
+                        + std::cmp::max(s.virtual_size, s.size_of_raw_data) as usize
+                })
+                .max()
+                .unwrap_or_default(),
+            section_alignment,
+        );
+        let mut data = self.pe.data.clone();
+        let mut section = vec![];
+
+        let mut method_rvas = vec![];
+        for (rid, body) in &self.bodies {
+            let token = Token::new((table_name_2_index("MethodDef")? << TABLE_SHIFT) | rid);
+            match net.function_for(&token) {
+                Some(f) if body.len() <= f.size() => {
+                    data[f.offset..f.offset + body.len()].copy_from_slice(body);
+                    data[f.offset + body.len()..f.offset + f.size()].fill(0);
+                }
+                _ => {
+                    section.resize(align(section.len(), 4), 0);
+                    method_rvas.push((*rid, (section_va + section.len()) as u32));
+                    section.extend_from_slice(body);
+                }
+            }
+        }
+
+        let method_def = net.md_table("MethodDef")?;
+        let clr_directory = match opt.data_directories.get_clr_runtime_header() {
+            Some(s) => s,
+            None => return Err(Error::UnsupportedBinaryFormat("ClR runtime header absence")),
+        };
+        let clr_offset = self.pe.offset(clr_directory.virtual_address)?;
+        let metadata_rva: u32 = data.pread_with(clr_offset + 8, LE)?;
+        let metadata_size: u32 = data.pread_with(clr_offset + 12, LE)?;
+        if self.rebuild_metadata {
+            let mut builder = metadata::MetaDataBuilder::new(&net.metadata);
+            builder.append_user_strings(&self.user_strings);
+            let mut metadata = builder.build()?;
+            let (table_offset, row_size) = builder.table_offset("MethodDef")?;
+            for (rid, rva) in &method_rvas {
+                metadata.pwrite_with(*rva, table_offset + (rid - 1) * row_size, LE)?;
+            }
+
+            section.resize(align(section.len(), 4), 0);
+            data.pwrite_with((section_va + section.len()) as u32, clr_offset + 8, LE)?;
+            data.pwrite_with(metadata.len() as u32, clr_offset + 12, LE)?;
+            section.extend(metadata);
+        } else if self.user_strings.is_empty() {
+            let table_offset = self.pe.offset(method_def.rva)?;
+            for (rid, rva) in &method_rvas {
+                data.pwrite_with(*rva, table_offset + (rid - 1) * method_def.row_size, LE)?;
+            }
+        } else {
+            let metadata_offset = self.pe.offset(metadata_rva)?;
+            let mut metadata =
+                data[metadata_offset..metadata_offset + metadata_size as usize].to_vec();
+            let table_offset = (method_def.rva - metadata_rva) as usize;
+            for (rid, rva) in &method_rvas {
+                metadata.pwrite_with(*rva, table_offset + (rid - 1) * method_def.row_size, LE)?;
+            }
+            let (header_pos, us_offset, us_size) = match stream_header(&metadata, "#US")? {
+                Some(s) => s,
+                None => return Err(Error::RefToUndefinedHeap("user string")),
+            };
+            let us_data = metadata[us_offset as usize..(us_offset + us_size) as usize].to_vec();
+            metadata.resize(align(metadata.len(), 4), 0);
+            let new_us_offset = metadata.len();
+            metadata.extend(us_data);
+            metadata.extend_from_slice(&self.user_strings);
+            metadata.resize(align(metadata.len(), 4), 0);
+            let new_us_size = metadata.len() - new_us_offset;
+            metadata.pwrite_with(new_us_offset as u32, header_pos, LE)?;
+            metadata.pwrite_with(new_us_size as u32, header_pos + 4, LE)?;
+
+            section.resize(align(section.len(), 4), 0);
+            data.pwrite_with((section_va + section.len()) as u32, clr_offset + 8, LE)?;
+            data.pwrite_with(metadata.len() as u32, clr_offset + 12, LE)?;
+            section.extend(metadata);
+        }
+
+        if section.is_empty() {
+            return Ok(data);
+        }
+        self.add_section(data, section, section_va, !method_rvas.is_empty())
+    }
+
+    fn add_section(
+        &self,
+        data: Vec<u8>,
+        section: Vec<u8>,
+        section_va: usize,
+        has_code: bool,
+    ) -> Result<Vec<u8>> {
+        let headers = Headers::new(&data)?;
+        let mut data = if headers.has_room(&data) {
+            data
+        } else {
+            headers.grow(data)?
+        };
+        let headers = Headers::new(&data)?;
+        if !headers.has_room(&data) {
+            return Err(Error::PeWriteError(
+                "no room for a new section header".to_string(),
+            ));
+        }
+
+        let raw_pointer = align(
+            headers
+                .sections
+                .iter()
+                .map(|(pointer, size)| pointer + size)
+                .max()
+                .unwrap_or_default(),
+            headers.file_alignment,
+        );
+        let raw_size = align(section.len(), headers.file_alignment);
+        let overlay = data.get(raw_pointer..).unwrap_or_default().to_vec();
+        data.resize(raw_pointer, 0);
+        data.extend_from_slice(&section);
+        data.resize(raw_pointer + raw_size, 0);
+        data.extend(overlay);
+
+        let mut header = [0u8; SECTION_HEADER_SIZE];
+        header[..8].copy_from_slice(SECTION_NAME);
+        header.pwrite_with(section.len() as u32, 8, LE)?;
+        header.pwrite_with(section_va as u32, 12, LE)?;
+        header.pwrite_with(raw_size as u32, 16, LE)?;
+        header.pwrite_with(raw_pointer as u32, 20, LE)?;
+        let (characteristics, size_offset) = if has_code {
+            (CODE_SECTION_CHARACTERISTICS, headers.opt_offset + 4)
+        } else {
+            (DATA_SECTION_CHARACTERISTICS, headers.opt_offset + 8)
+        };
+        header.pwrite_with(characteristics, 36, LE)?;
+        let header_offset = headers.next_section_header();
+        data[header_offset..header_offset + SECTION_HEADER_SIZE].copy_from_slice(&header);
+
+        data.pwrite_with(
+            headers.sections.len() as u16 + 1,
+            headers.coff_offset + 2,
+            LE,
+        )?;
+        let size: u32 = data.pread_with(size_offset, LE)?;
+        data.pwrite_with(size + raw_size as u32, size_offset, LE)?;
+        data.pwrite_with(
+            align(section_va + section.len(), headers.section_alignment) as u32,
+            headers.opt_offset + 56,
+            LE,
+        )?;
+        headers.shift_certificates(&mut data, raw_pointer, raw_size)?;
+        if data.pread_with::<u32>(headers.opt_offset + 64, LE)? != 0 {
+            let checksum = pe_checksum(&data, headers.opt_offset + 64);
+            data.pwrite_with(checksum, headers.opt_offset + 64, LE)?;
+        }
+        Ok(data)
+    }
+
+    pub fn build_dnpe(&self) -> Result<crate::DnPe> {
+        crate::DnPe::from_bytes(self.pe.name(), self.build()?)
+    }
+
+    pub fn save(&self, name: &str) -> Result<()> {
+        Ok(std::fs::write(name, self.build()?)?)
+    }
+}
@@ -0,0 +1,751 @@
+use crate::{
+    error::Error,
+    lang::clr::token::{Token, TABLE_SHIFT},
+    stream::{
+        meta_data_tables::mdtables::{codedindex::CodedIndex, *},
+        Stream,
+    },
+    writer::metadata::TableWriter,
+    Result,
+};
+
+/// Writes the columns of one metadata table row in the order ECMA-335
+/// II.22 lays them out.
+pub trait RowWriter {
+    fn write(&self, w: &mut TableWriter) -> Result<()>;
+}
+
+impl RowWriter for Module {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_u16(self.generation);
+        w.write_string(&self.name)?;
+        w.write_guid(&self.mvid)?;
+        w.write_guid(&self.enc_id)?;
+        w.write_guid(&self.enc_base_id)
+    }
+}
+
+impl RowWriter for TypeRef {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_coded_index(&self.resolution_scope)?;
+        w.write_string(&self.type_name)?;
+        w.write_string(&self.type_namespace)
+    }
+}
+
+impl RowWriter for TypeDef {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_u32(self.raw_flags);
+        w.write_string(&self.type_name)?;
+        w.write_string(&self.type_namespace)?;
+        w.write_coded_index(&self.extends)?;
+        w.write_list("Field", &self.field_list)?;
+        w.write_list("MethodDef", &self.method_list)
+    }
+}
+
+impl RowWriter for FieldPtr {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_coded_index(&self.field)
+    }
+}
+
+impl RowWriter for Field {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_u16(self.raw_flags);
+        w.write_string(&self.name)?;
+        w.write_blob(&self.signature)
+    }
+}
+
+impl RowWriter for MethodPtr {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_coded_index(&self.method)
+    }
+}
+
+impl RowWriter for MethodDef {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_u32(self.rva);
+        w.write_u16(self.raw_impl_flags);
+        w.write_u16(self.raw_flags);
+        w.write_string(&self.name)?;
+        w.write_blob(&self.signature)?;
+        w.write_list("Param", &self.param_list)
+    }
+}
+
+impl RowWriter for ParamPtr {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_coded_index(&self.param)
+    }
+}
+
+impl RowWriter for Param {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_u16(self.raw_flags);
+        w.write_u16(self.sequence as u16);
+        w.write_string(&self.name)
+    }
+}
+
+impl RowWriter for InterfaceImpl {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_coded_index(&self.class)?;
+        w.write_coded_index(&self.interface)
+    }
+}
+
+impl RowWriter for MemberRef {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_coded_index(&self.class)?;
+        w.write_string(&self.name)?;
+        w.write_blob(&self.signature)
+    }
+}
+
+impl RowWriter for Constant {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_u8(self._type as u8);
+        w.write_u8(self.padding as u8);
+        w.write_coded_index(&self.parent)?;
+        w.write_blob(&self.value)
+    }
+}
+
+impl RowWriter for CustomAttribute {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_coded_index(&self.parent)?;
+        w.write_coded_index(&self._type)?;
+        w.write_blob(&self.value)
+    }
+}
+
+impl RowWriter for FieldMarshal {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_coded_index(&self.parent)?;
+        w.write_blob(&self.native_type)
+    }
+}
+
+impl RowWriter for DeclSecurity {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_u16(self.action as u16);
+        w.write_coded_index(&self.parent)?;
+        w.write_blob(&self.permission_set)
+    }
+}
+
+impl RowWriter for ClassLayout {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_u16(self.packing_size as u16);
+        w.write_u32(self.class_size as u32);
+        w.write_coded_index(&self.parent)
+    }
+}
+
+impl RowWriter for FieldLayout {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_u32(self.offset);
+        w.write_coded_index(&self.field)
+    }
+}
+
+impl RowWriter for StandAloneSig {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_blob(&self.signature)
+    }
+}
+
+impl RowWriter for EventMap {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_coded_index(&self.parent)?;
+        w.write_list("Event", &self.event_list)
+    }
+}
+
+impl RowWriter for EventPtr {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_coded_index(&self.event)
+    }
+}
+
+impl RowWriter for Event {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_u16(self.raw_event_flags);
+        w.write_string(&self.name)?;
+        w.write_coded_index(&self.event_type)
+    }
+}
+
+impl RowWriter for PropertyMap {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_coded_index(&self.parent)?;
+        w.write_list("Property", &self.property_list)
+    }
+}
+
+impl RowWriter for PropertyPtr {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_coded_index(&self.property)
+    }
+}
+
+impl RowWriter for Property {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_u16(self.raw_flags);
+        w.write_string(&self.name)?;
+        w.write_blob(&self._type)
+    }
+}
+
+impl RowWriter for MethodSemantics {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_u16(self.raw_semantics);
+        w.write_coded_index(&self.method)?;
+        w.write_coded_index(&self.association)
+    }
+}
+
+impl RowWriter for MethodImpl {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_coded_index(&self.class)?;
+        w.write_coded_index(&self.method_body)?;
+        w.write_coded_index(&self.method_declaration)
+    }
+}
+
+impl RowWriter for ModuleRef {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_string(&self.name)
+    }
+}
+
+impl RowWriter for TypeSpec {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_blob(&self.signature)
+    }
+}
+
+impl RowWriter for ImplMap {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_u16(self.raw_mapping_flags);
+        w.write_coded_index(&self.member_forwarded)?;
+        w.write_string(&self.import_name)?;
+        w.write_coded_index(&self.import_scope)
+    }
+}
+
+impl RowWriter for FieldRva {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_u32(self.rva);
+        w.write_coded_index(&self.field)
+    }
+}
+
+impl RowWriter for EncLog {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_u32(self.token);
+        w.write_u32(self.func_code);
+        Ok(())
+    }
+}
+
+impl RowWriter for EncMap {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_u32(self.token);
+        Ok(())
+    }
+}
+
+impl RowWriter for Assembly {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_u32(self.raw_hash_alg_id);
+        w.write_u16(self.major_version as u16);
+        w.write_u16(self.minor_version as u16);
+        w.write_u16(self.build_number as u16);
+        w.write_u16(self.revision_number as u16);
+        w.write_u32(self.raw_flags);
+        w.write_blob(&self.public_key)?;
+        w.write_string(&self.name)?;
+        w.write_string(&self.culture)
+    }
+}
+
+impl RowWriter for AssemblyProcessor {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_u32(self.processor);
+        Ok(())
+    }
+}
+
+impl RowWriter for AssemblyOS {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_u32(self.os_platform_id);
+        w.write_u32(self.os_major_version);
+        w.write_u32(self.os_minor_version);
+        Ok(())
+    }
+}
+
+impl RowWriter for AssemblyRef {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_u16(self.major_version as u16);
+        w.write_u16(self.minor_version as u16);
+        w.write_u16(self.build_number as u16);
+        w.write_u16(self.revision_number as u16);
+        w.write_u32(self.raw_flags);
+        w.write_blob(&self.public_key)?;
+        w.write_string(&self.name)?;
+        w.write_string(&self.culture)?;
+        w.write_blob(&self.hash_value)
+    }
+}
+
+impl RowWriter for AssemblyRefProcessor {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_u32(self.processor);
+        w.write_coded_index(&self.assembly_ref)
+    }
+}
+
+impl RowWriter for AssemblyRefOS {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_u32(self.os_platform_id);
+        w.write_u32(self.os_major_version);
+        w.write_u32(self.os_minor_version);
+        w.write_coded_index(&self.assembly_ref)
+    }
+}
+
+impl RowWriter for File {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_u32(self.raw_flags);
+        w.write_string(&self.name)?;
+        w.write_blob(&self.hash_value)
+    }
+}
+
+impl RowWriter for ExportedType {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_u32(self.raw_flags);
+        w.write_u32(self.type_def_id);
+        w.write_string(&self.type_name)?;
+        w.write_string(&self.type_namespace)?;
+        w.write_coded_index(&self.implementation)
+    }
+}
+
+impl RowWriter for ManifestResource {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_u32(self.offset);
+        w.write_u32(self.raw_flags);
+        w.write_string(&self.name)?;
+        w.write_coded_index(&self.implementation)
+    }
+}
+
+impl RowWriter for NestedClass {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_coded_index(&self.nested_class)?;
+        w.write_coded_index(&self.enclosing_class)
+    }
+}
+
+impl RowWriter for GenericParam {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_u16(self.number as u16);
+        w.write_u16(self.raw_flags);
+        w.write_coded_index(&self.owner)?;
+        w.write_string(&self.name)
+    }
+}
+
+impl RowWriter for MethodSpec {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_coded_index(&self.method)?;
+        w.write_blob(&self.instantiation)
+    }
+}
+
+impl RowWriter for GenericParamConstraint {
+    fn write(&self, w: &mut TableWriter) -> Result<()> {
+        w.write_coded_index(&self.owner)?;
+        w.write_coded_index(&self.constraint)
+    }
+}
+
+impl RowWriter for Unused {
+    fn write(&self, _w: &mut TableWriter) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl RowWriter for MaxTable {
+    fn write(&self, _w: &mut TableWriter) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// How a row refers to another row, which decides what happens to it when
+/// [`crate::MetaData::remove_row`] removes the target.
+pub enum IndexColumn<'a> {
+    /// A reference that may be null, so it is nulled with its target.
+    Reference(&'a mut dyn CodedIndex),
+    /// A reference the row cannot exist without, so the row is removed too.
+    Owner(&'a mut dyn CodedIndex),
+    /// A Field, MethodDef, Param, Event or Property list whose rows belong
+    /// to this row and are removed with it.
+    List(&'a mut Vec<codedindex::SimpleCodedIndex>),
+}
+
+/// Lists the index columns of a row so removals can renumber them.
+pub trait RowIndexes {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![]
+    }
+}
+
+impl RowIndexes for Module {}
+
+impl RowIndexes for TypeRef {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![IndexColumn::Reference(&mut self.resolution_scope)]
+    }
+}
+
+impl RowIndexes for TypeDef {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![
+            IndexColumn::Reference(&mut self.extends),
+            IndexColumn::List(&mut self.field_list),
+            IndexColumn::List(&mut self.method_list),
+        ]
+    }
+}
+
+impl RowIndexes for FieldPtr {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![IndexColumn::Owner(&mut self.field)]
+    }
+}
+
+impl RowIndexes for Field {}
+
+impl RowIndexes for MethodPtr {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![IndexColumn::Owner(&mut self.method)]
+    }
+}
+
+impl RowIndexes for MethodDef {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![IndexColumn::List(&mut self.param_list)]
+    }
+}
+
+impl RowIndexes for ParamPtr {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![IndexColumn::Owner(&mut self.param)]
+    }
+}
+
+impl RowIndexes for Param {}
+
+impl RowIndexes for InterfaceImpl {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![
+            IndexColumn::Owner(&mut self.class),
+            IndexColumn::Owner(&mut self.interface),
+        ]
+    }
+}
+
+impl RowIndexes for MemberRef {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![IndexColumn::Owner(&mut self.class)]
+    }
+}
+
+impl RowIndexes for Constant {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![IndexColumn::Owner(&mut self.parent)]
+    }
+}
+
+impl RowIndexes for CustomAttribute {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![
+            IndexColumn::Owner(&mut self.parent),
+            IndexColumn::Owner(&mut self._type),
+        ]
+    }
+}
+
+impl RowIndexes for FieldMarshal {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![IndexColumn::Owner(&mut self.parent)]
+    }
+}
+
+impl RowIndexes for DeclSecurity {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![IndexColumn::Owner(&mut self.parent)]
+    }
+}
+
+impl RowIndexes for ClassLayout {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![IndexColumn::Owner(&mut self.parent)]
+    }
+}
+
+impl RowIndexes for FieldLayout {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![IndexColumn::Owner(&mut self.field)]
+    }
+}
+
+impl RowIndexes for StandAloneSig {}
+
+impl RowIndexes for EventMap {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![
+            IndexColumn::Owner(&mut self.parent),
+            IndexColumn::List(&mut self.event_list),
+        ]
+    }
+}
+
+impl RowIndexes for EventPtr {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![IndexColumn::Owner(&mut self.event)]
+    }
+}
+
+impl RowIndexes for Event {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![IndexColumn::Reference(&mut self.event_type)]
+    }
+}
+
+impl RowIndexes for PropertyMap {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![
+            IndexColumn::Owner(&mut self.parent),
+            IndexColumn::List(&mut self.property_list),
+        ]
+    }
+}
+
+impl RowIndexes for PropertyPtr {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![IndexColumn::Owner(&mut self.property)]
+    }
+}
+
+impl RowIndexes for Property {}
+
+impl RowIndexes for MethodSemantics {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![
+            IndexColumn::Owner(&mut self.method),
+            IndexColumn::Owner(&mut self.association),
+        ]
+    }
+}
+
+impl RowIndexes for MethodImpl {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![
+            IndexColumn::Owner(&mut self.class),
+            IndexColumn::Owner(&mut self.method_body),
+            IndexColumn::Owner(&mut self.method_declaration),
+        ]
+    }
+}
+
+impl RowIndexes for ModuleRef {}
+
+impl RowIndexes for TypeSpec {}
+
+impl RowIndexes for ImplMap {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![
+            IndexColumn::Owner(&mut self.member_forwarded),
+            IndexColumn::Owner(&mut self.import_scope),
+        ]
+    }
+}
+
+impl RowIndexes for FieldRva {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![IndexColumn::Owner(&mut self.field)]
+    }
+}
+
+impl RowIndexes for EncLog {}
+
+impl RowIndexes for EncMap {}
+
+impl RowIndexes for Assembly {}
+
+impl RowIndexes for AssemblyProcessor {}
+
+impl RowIndexes for AssemblyOS {}
+
+impl RowIndexes for AssemblyRef {}
+
+impl RowIndexes for AssemblyRefProcessor {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![IndexColumn::Owner(&mut self.assembly_ref)]
+    }
+}
+
+impl RowIndexes for AssemblyRefOS {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![IndexColumn::Owner(&mut self.assembly_ref)]
+    }
+}
+
+impl RowIndexes for File {}
+
+impl RowIndexes for ExportedType {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![IndexColumn::Owner(&mut self.implementation)]
+    }
+}
+
+impl RowIndexes for ManifestResource {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![IndexColumn::Owner(&mut self.implementation)]
+    }
+}
+
+impl RowIndexes for NestedClass {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![
+            IndexColumn::Owner(&mut self.nested_class),
+            IndexColumn::Owner(&mut self.enclosing_class),
+        ]
+    }
+}
+
+impl RowIndexes for GenericParam {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![IndexColumn::Owner(&mut self.owner)]
+    }
+}
+
+impl RowIndexes for MethodSpec {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![IndexColumn::Owner(&mut self.method)]
+    }
+}
+
+impl RowIndexes for GenericParamConstraint {
+    fn index_columns(&mut self) -> Vec<IndexColumn<'_>> {
+        vec![
+            IndexColumn::Owner(&mut self.owner),
+            IndexColumn::Owner(&mut self.constraint),
+        ]
+    }
+}
+
+impl RowIndexes for Unused {}
+
+impl RowIndexes for MaxTable {}
+
+fn target(index: &dyn CodedIndex) -> Option<(usize, usize)> {
+    match index.row_index() {
+        0 => None,
+        rid => Some((table_name_2_index(index.table()).ok()?, rid)),
+    }
+}
+
+impl crate::MetaData {
+    /// Removes row `rid` of `table` together with the rows that cannot exist
+    /// without it: the members in its list columns and every row with an
+    /// [`IndexColumn::Owner`] column pointing at a removed row. The index and
+    /// list columns of the remaining rows are renumbered, and references to
+    /// removed rows become null. Tokens inside signature blobs and method
+    /// bodies are not rewritten. Returns the tokens of all removed rows.
+    pub fn remove_row(&mut self, table: &'static str, rid: usize) -> Result<Vec<Token>> {
+        let index = table_name_2_index(table)?;
+        let tables = self
+            .streams
+            .values_mut()
+            .find_map(|s| match &mut s.stream {
+                Stream::MetaDataTables(mt) => Some(&mut mt.tables),
+                _ => None,
+            })
+            .ok_or(Error::UndefinedStream)?;
+        let row_count = tables.get(&index).map_or(0, |t| t.row_count());
+        if rid == 0 || rid > row_count {
+            return Err(Error::RowIndexOutOfBound(rid, row_count));
+        }
+
+        let mut removed = std::collections::BTreeSet::from([(index, rid)]);
+        loop {
+            let mut found = vec![];
+            for (n, t) in tables.iter_mut() {
+                for i in 0..t.row_count() {
+                    let owned = removed.contains(&(*n, i + 1));
+                    for column in t.index_columns(i)? {
+                        match column {
+                            IndexColumn::List(list) if owned => {
+                                found.extend(list.iter().filter_map(|r| target(r)))
+                            }
+                            IndexColumn::Owner(c)
+                                if !owned && target(c).is_some_and(|t| removed.contains(&t)) =>
+                            {
+                                found.push((*n, i + 1))
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            let before = removed.len();
+            removed.extend(found);
+            if removed.len() == before {
+                break;
+            }
+        }
+
+        for (n, rid) in removed.iter().rev() {
+            if let Some(t) = tables.get_mut(n) {
+                t.remove_row(rid - 1)?;
+            }
+        }
+        let renumber = |(table, rid): (usize, usize)| match removed.contains(&(table, rid)) {
+            true => 0,
+            false => rid - removed.range((table, 0)..(table, rid)).count(),
+        };
+        for t in tables.values_mut() {
+            for i in 0..t.row_count() {
+                for column in t.index_columns(i)? {
+                    match column {
+                        IndexColumn::Reference(c) | IndexColumn::Owner(c) => {
+                            if let Some(t) = target(c) {
+                                c.set_row_index(renumber(t));
+                            }
+                        }
+                        IndexColumn::List(list) => {
+                            list.retain(|r| !target(r).is_some_and(|t| removed.contains(&t)));
+                            for r in list.iter_mut() {
+                                if let Some(t) = target(r) {
+                                    r.row_index = renumber(t);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(removed
+            .into_iter()
+            .map(|(n, rid)| Token::new((n << TABLE_SHIFT) | rid))
+            .collect())
+    }
+}
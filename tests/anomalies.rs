@@ -0,0 +1,67 @@
+use dnfile::{
+    anomalies::AnomalyKind,
+    stream::meta_data_tables::mdtables::{codedindex::CodedIndex, CustomAttribute, MethodDef},
+};
+
+const SAMPLE: &str = "tests/samples/crafted_2.exe";
+
+#[test]
+fn method_rva_outside_sections_is_reported() -> dnfile::Result<()> {
+    let mut pe = dnfile::DnPe::new(SAMPLE)?;
+    let function = pe.net()?.functions()[0].clone();
+    pe.net_mut()?
+        .md_table_mut("MethodDef")?
+        .row_mut::<MethodDef>(function.method_rid() - 1)?
+        .rva = 0x7fff_0000;
+    let mut writer = pe.writer();
+    writer.set_rebuild_metadata(true);
+    let corrupt = writer.build_dnpe()?;
+
+    let net = corrupt.net()?;
+    assert_eq!(net.functions().len(), pe.net()?.functions().len() - 1);
+    assert!(net.function_for(&function.method_token).is_none());
+    let errors = net.method_body_errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].method_token, function.method_token);
+    assert_eq!(errors[0].rva, 0x7fff_0000);
+
+    let anomalies = corrupt.anomalies()?;
+    let found: Vec<_> = anomalies
+        .iter()
+        .filter(|a| a.kind == AnomalyKind::MethodRvaOutsideSections)
+        .collect();
+    assert_eq!(found.len(), 1);
+    assert_eq!(
+        found[0].location,
+        format!("MethodDef[{}]", function.method_rid())
+    );
+    assert_eq!(found[0].rva, Some(0x7fff_0000));
+    Ok(())
+}
+
+#[test]
+fn index_into_absent_table_is_reported() -> dnfile::Result<()> {
+    let mut pe = dnfile::DnPe::new(SAMPLE)?;
+    assert!(pe.net()?.md_table("File").is_err());
+    let row = pe
+        .net_mut()?
+        .md_table_mut("CustomAttribute")?
+        .row_mut::<CustomAttribute>(0)?;
+    row.parent.set_table("File");
+    row.parent.set_row_index(1);
+    let mut writer = pe.writer();
+    writer.set_rebuild_metadata(true);
+    let corrupt = writer.build_dnpe()?;
+
+    assert_eq!(
+        corrupt.net()?.functions().len(),
+        pe.net()?.functions().len()
+    );
+    let anomalies = corrupt.anomalies()?;
+    assert!(anomalies
+        .iter()
+        .any(|a| a.kind == AnomalyKind::CodedIndexOutOfRange
+            && a.location.starts_with("CustomAttribute")
+            && a.description.contains("File row 1 of 0")));
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use dnfile::batch::Glob;
+
+fn matches(pattern: &str, relative: &str) -> bool {
+    let file_name = relative.rsplit('/').next().unwrap();
+    Glob::new(pattern).unwrap().matches(relative, file_name)
+}
+
+#[test]
+fn star_stays_within_a_directory() {
+    assert!(matches("*.dll", "lib/net48/a.dll"));
+    assert!(!matches("*.dll", "lib/a.dll.bak"));
+    assert!(matches("lib/*.dll", "lib/a.dll"));
+    assert!(!matches("lib/*.dll", "lib/net48/a.dll"));
+}
+
+#[test]
+fn double_star_crosses_directories() {
+    assert!(matches("**/bin/*.exe", "bin/a.exe"));
+    assert!(matches("**/bin/*.exe", "src/app/bin/a.exe"));
+    assert!(!matches("**/bin/*.exe", "src/bin/debug/a.exe"));
+    assert!(matches("src/**", "src/app/bin/a.exe"));
+}
+
+#[test]
+fn question_mark_matches_one_character() {
+    assert!(matches("a?.dll", "ab.dll"));
+    assert!(!matches("a?.dll", "a.dll"));
+    assert!(!matches("a?.dll", "abc.dll"));
+    assert!(!matches("lib?a.dll", "lib/a.dll"));
+}
+
+#[test]
+fn brackets_match_a_set_or_range() {
+    assert!(matches("[ab].dll", "a.dll"));
+    assert!(!matches("[ab].dll", "c.dll"));
+    assert!(matches("v[0-9].exe", "v7.exe"));
+    assert!(!matches("v[!0-9].exe", "v7.exe"));
+    assert!(matches("v[^0-9].exe", "vx.exe"));
+    assert!(matches("[]x].dll", "].dll"));
+    assert!(matches("[a-].dll", "-.dll"));
+    assert!(!matches("lib[!x]a.dll", "lib/a.dll"));
+    assert!(Glob::new("[ab.dll").is_err());
+}
+
+#[test]
+fn backslash_escapes_wildcards() {
+    assert!(matches(r"a\*.dll", "a*.dll"));
+    assert!(!matches(r"a\*.dll", "ab.dll"));
+    assert!(matches(r"a\?.dll", "a?.dll"));
+    assert!(!matches(r"a\?.dll", "ab.dll"));
+    assert!(matches(r"\[ab\].dll", "[ab].dll"));
+    assert!(matches("a.dll", "a.dll"));
+    assert!(!matches("a.dll", "abdll"));
+}
@@ -0,0 +1,42 @@
+use dnfile::stream::meta_data_tables::mdtables::{codedindex::CodedIndex, TypeDef, TypeSpec};
+
+const SAMPLE: &str = "tests/samples/crafted_2.exe";
+
+fn type_rid(pe: &dnfile::DnPe, name: &str) -> dnfile::Result<usize> {
+    let table = pe.net()?.md_table("TypeDef")?;
+    for i in 0..table.row_count() {
+        if table.row::<TypeDef>(i)?.type_name == name {
+            return Ok(i + 1);
+        }
+    }
+    panic!("{} not found", name);
+}
+
+#[test]
+fn generic_instance_base_resolves_to_its_definition() -> dnfile::Result<()> {
+    let mut pe = dnfile::DnPe::new(SAMPLE)?;
+    let generic = type_rid(&pe, "GenericStruct`2")?;
+    let derived = type_rid(&pe, "Employee")?;
+    // GENERICINST VALUETYPE GenericStruct`2 <int32, string>
+    let spec = pe.net_mut()?.md_table_mut("TypeSpec")?.push_row(TypeSpec {
+        signature: vec![0x15, 0x11, (generic << 2) as u8, 0x02, 0x08, 0x0e],
+    })?;
+    let extends = &mut pe
+        .net_mut()?
+        .md_table_mut("TypeDef")?
+        .row_mut::<TypeDef>(derived - 1)?
+        .extends;
+    extends.set_table("TypeSpec");
+    extends.set_row_index(spec);
+    let mut writer = pe.writer();
+    writer.set_rebuild_metadata(true);
+    let rebuilt = writer.build_dnpe()?;
+
+    let net = rebuilt.net()?;
+    let base = net.base_type(derived)?.unwrap();
+    assert_eq!(base.type_def, Some(generic));
+    assert_eq!(net.base_types(derived)?.len(), 2);
+    assert_eq!(net.derived_types(generic)?, vec![derived]);
+    assert_eq!(net.types_deriving_from("GenericStruct`2")?, vec![derived]);
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use dnfile::stream::meta_data_tables::mdtables::StandAloneSig;
+
+const SAMPLE: &str = "tests/samples/crafted_2.exe";
+
+#[test]
+fn corrupt_local_var_sig_only_affects_its_method() -> dnfile::Result<()> {
+    let mut pe = dnfile::DnPe::new(SAMPLE)?;
+    let function = pe
+        .net()?
+        .functions()
+        .iter()
+        .find(|f| !f.locals.is_empty())
+        .unwrap()
+        .clone();
+    let rid = function.local_var_sig_tok().unwrap().rid();
+    // LOCAL_SIG with one local of an undefined element type
+    pe.net_mut()?
+        .md_table_mut("StandAloneSig")?
+        .row_mut::<StandAloneSig>(rid - 1)?
+        .signature = vec![0x07, 0x01, 0xff];
+    let mut writer = pe.writer();
+    writer.set_rebuild_metadata(true);
+    let corrupt = writer.build_dnpe()?;
+
+    let original = dnfile::DnPe::new(SAMPLE)?;
+    let net = corrupt.net()?;
+    assert_eq!(net.functions().len(), original.net()?.functions().len());
+    for (a, b) in original.net()?.functions().iter().zip(net.functions()) {
+        if b.local_var_sig_tok().map(|t| t.rid()) == Some(rid) {
+            assert!(b.locals.is_empty());
+            assert!(b.locals_error.is_some(), "{}", b.full_name());
+        } else {
+            assert_eq!(a.locals, b.locals, "{}", b.full_name());
+            assert!(b.locals_error.is_none());
+        }
+        assert_eq!(a, b);
+    }
+    Ok(())
+}
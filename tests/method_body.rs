@@ -0,0 +1,123 @@
+use dnfile::lang::{
+    cil::{
+        enums::OpCodeValue,
+        function::{reader::Reader, Function},
+        instruction::Operand,
+        opcode::OpCodes,
+    },
+    clr::local::Local,
+};
+
+const SAMPLE: &str = "tests/samples/crafted_2.exe";
+
+#[test]
+fn method_bodies_round_trip() -> dnfile::Result<()> {
+    let pe = dnfile::DnPe::new(SAMPLE)?;
+    let functions = pe.net()?.functions();
+    assert!(!functions.is_empty());
+    for function in functions {
+        let encoded = function.encode()?;
+        let decoded = Function::new(&mut Reader::new(&encoded))?;
+        assert_eq!(decoded, *function, "{}", function.full_name());
+        assert_eq!(decoded.encode()?, encoded, "{}", function.full_name());
+    }
+    Ok(())
+}
+
+#[test]
+fn short_branches_keep_instructions() -> dnfile::Result<()> {
+    let pe = dnfile::DnPe::new(SAMPLE)?;
+    for function in pe.net()?.functions() {
+        let mut body = function.method_body();
+        body.short_branches = true;
+        let decoded = Function::new(&mut Reader::new(&body.encode()?))?;
+        assert_eq!(
+            decoded.instructions.len(),
+            function.instructions.len(),
+            "{}",
+            function.full_name()
+        );
+        assert_eq!(
+            decoded.exception_handlers().len(),
+            function.exception_handlers().len()
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn max_stack_is_computed_from_signatures() -> dnfile::Result<()> {
+    let pe = dnfile::DnPe::new(SAMPLE)?;
+    let net = pe.net()?;
+    for function in net.functions() {
+        let mut body = function.method_body();
+        body.max_stack = None;
+        let encoded = net.encode_method_body(&function.method_token, &body)?;
+        let decoded = Function::new(&mut Reader::new(&encoded))?;
+        let computed = net.compute_max_stack(function, &body)?;
+        assert!(
+            computed <= function.max_stack(),
+            "{}: computed {} declared {}",
+            function.full_name(),
+            computed,
+            function.max_stack()
+        );
+        assert_eq!(decoded.instructions.len(), function.instructions.len());
+    }
+    Ok(())
+}
+
+const WINDOWS_BASE_BODIES: &[&str] = &[
+    "WB_METHOD_TINY_0600032D",
+    "WB_METHOD_FAT_0600033E",
+    "WB_METHOD_FAT_EXCEPTION_06000341",
+    "WB_METHOD_FAT_EXCEPTION_N1_2LOCALS_060001AA",
+    "WB_METHOD_FAT_EXCEPTION_N2_06000421",
+    "WB_METHOD_FAT_EXCEPTION_N2_06000D54",
+];
+
+#[test]
+fn compiler_bodies_round_trip() -> dnfile::Result<()> {
+    for name in WINDOWS_BASE_BODIES {
+        let data = std::fs::read(format!("tests/samples/{}.bin", name))?;
+        let function = Function::new(&mut Reader::new(&data))?;
+        let encoded = function.encode()?;
+        assert_eq!(encoded, data, "{}", name);
+        assert_eq!(Function::new(&mut Reader::new(&encoded))?, function);
+    }
+    Ok(())
+}
+
+#[test]
+fn declared_max_stack_is_kept() -> dnfile::Result<()> {
+    let pe = dnfile::DnPe::new(SAMPLE)?;
+    let function = pe
+        .net()?
+        .functions()
+        .iter()
+        .find(|f| f.header_size() == 1)
+        .unwrap();
+    let mut body = function.method_body();
+    body.max_stack = Some(2);
+    let decoded = Function::new(&mut Reader::new(&body.encode()?))?;
+    assert_eq!(decoded.header_size(), 12);
+    assert_eq!(decoded.max_stack(), 2);
+    Ok(())
+}
+
+#[test]
+fn short_variable_index_out_of_range_fails() -> dnfile::Result<()> {
+    let pe = dnfile::DnPe::new(SAMPLE)?;
+    let function = pe.net()?.functions().first().unwrap();
+    let mut load = function.instructions[0].clone();
+    load.opcode = OpCodes::new().get(OpCodeValue::Ldloc_S).clone();
+    load.operand = Operand::Local(Local::new(300));
+    let mut body = function.method_body();
+    body.instructions.insert(0, load);
+    body.max_stack = Some(8);
+    assert!(matches!(
+        body.encode(),
+        Err(dnfile::error::Error::MethodBodyEncodeError(_))
+    ));
+    Ok(())
+}
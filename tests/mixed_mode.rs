@@ -0,0 +1,75 @@
+const SAMPLE: &str = "tests/samples/crafted_2.exe";
+
+/// Points the CLR header's VTableFixups directory at a table written into
+/// the slack after .text, returning the patched image.
+fn with_fixups(
+    entries: &[(u32, u16, u16)],
+    declared_size: Option<u32>,
+) -> dnfile::Result<dnfile::DnPe> {
+    let pe = dnfile::DnPe::new(SAMPLE)?;
+    let mut data = pe.data().to_vec();
+    let goblin = pe.pe()?;
+    let text = goblin
+        .sections
+        .iter()
+        .find(|s| &s.name == b".text\0\0\0")
+        .unwrap();
+    let table_rva = (text.virtual_address + text.virtual_size + 3) & !3;
+    let table_offset = (text.pointer_to_raw_data + table_rva - text.virtual_address) as usize;
+    let table_size = entries.len() * 8;
+    assert!(table_rva + table_size as u32 <= text.virtual_address + text.size_of_raw_data);
+    for (i, (rva, count, flags)) in entries.iter().enumerate() {
+        let entry = &mut data[table_offset + i * 8..table_offset + i * 8 + 8];
+        entry[0..4].copy_from_slice(&rva.to_le_bytes());
+        entry[4..6].copy_from_slice(&count.to_le_bytes());
+        entry[6..8].copy_from_slice(&flags.to_le_bytes());
+    }
+
+    let clr = goblin
+        .header
+        .optional_header
+        .unwrap()
+        .data_directories
+        .get_clr_runtime_header()
+        .unwrap()
+        .virtual_address;
+    let clr_section = goblin
+        .sections
+        .iter()
+        .find(|s| clr >= s.virtual_address && clr < s.virtual_address + s.virtual_size)
+        .unwrap();
+    // VTableFixups directory of the COR20 header
+    let directory =
+        (clr_section.pointer_to_raw_data + clr - clr_section.virtual_address) as usize + 48;
+    data[directory..directory + 4].copy_from_slice(&table_rva.to_le_bytes());
+    data[directory + 4..directory + 8]
+        .copy_from_slice(&declared_size.unwrap_or(table_size as u32).to_le_bytes());
+    dnfile::DnPe::from_bytes(pe.name(), data)
+}
+
+#[test]
+fn bad_fixup_is_recorded_and_others_are_read() -> dnfile::Result<()> {
+    let metadata_rva = dnfile::DnPe::new(SAMPLE)?.net()?.clr_header().meta_data_rva;
+    let pe = with_fixups(&[(metadata_rva, 2, 1), (0x7fff_0000, 4, 1)], None)?;
+    let fixups = pe.vtable_fixups()?;
+    assert_eq!(fixups.len(), 2);
+    assert_eq!(fixups[0].slots.len(), 2);
+    assert!(fixups[0].error.is_none());
+    assert!(fixups[1].slots.is_empty());
+    assert!(fixups[1].error.is_some());
+    pe.managed_exports()?;
+    pe.entry_points()?;
+    Ok(())
+}
+
+#[test]
+fn truncated_fixup_table_is_an_error() -> dnfile::Result<()> {
+    let pe = with_fixups(&[(0, 0, 1)], Some(0x7fff_fff8))?;
+    assert!(matches!(
+        pe.vtable_fixups(),
+        Err(dnfile::error::Error::NotEnoughData(..))
+    ));
+    pe.managed_exports()?;
+    pe.entry_points()?;
+    Ok(())
+}
@@ -0,0 +1,226 @@
+use dnfile::stream::{
+    meta_data_tables::mdtables::{
+        codedindex::SimpleCodedIndex, table_name_2_index, MethodPtr, Module, TypeDef,
+    },
+    Stream,
+};
+
+const SAMPLE: &str = "tests/samples/crafted_2.exe";
+
+fn method_lists(pe: &dnfile::DnPe) -> dnfile::Result<Vec<Vec<usize>>> {
+    let table = pe.net()?.md_table("TypeDef")?;
+    (0..table.row_count())
+        .map(|i| {
+            Ok(table
+                .row::<TypeDef>(i)?
+                .method_list
+                .iter()
+                .map(|m| m.row_index)
+                .collect())
+        })
+        .collect()
+}
+
+/// Rebuilds the sample with a MethodPtr table that swaps the first two
+/// methods of one type, the way ENC and obfuscated assemblies reorder
+/// MethodDef rows behind the Ptr indirection.
+fn with_method_ptr() -> dnfile::Result<(dnfile::DnPe, dnfile::DnPe, usize)> {
+    let mut pe = dnfile::DnPe::new(SAMPLE)?;
+    let lists = method_lists(&pe)?;
+    let type_index = lists.iter().position(|l| l.len() >= 2).unwrap();
+    let (a, b) = (lists[type_index][0], lists[type_index][1]);
+
+    let method_ptr = table_name_2_index("MethodPtr")?;
+    let methods = pe.net()?.md_table("MethodDef")?.row_count();
+    let mut table = pe.create_md_table(&method_ptr, &[0; 64], false, 2, 2, 2)?;
+    for rid in 1..=methods {
+        let rid = if rid == a {
+            b
+        } else if rid == b {
+            a
+        } else {
+            rid
+        };
+        table.push_row(MethodPtr {
+            method: SimpleCodedIndex {
+                tag_bits: 0,
+                table_names: vec!["MethodDef"],
+                row_index: rid,
+                table: "MethodDef",
+            },
+        })?;
+    }
+
+    let net = pe.net_mut()?;
+    net.md_table_mut("TypeDef")?
+        .row_mut::<TypeDef>(type_index)?
+        .method_list
+        .swap(0, 1);
+    if let Some(Stream::MetaDataTables(t)) =
+        net.metadata.streams.get_mut("#~").map(|s| &mut s.stream)
+    {
+        t.tables.insert(method_ptr, table);
+    }
+    let mut writer = pe.writer();
+    writer.set_rebuild_metadata(true);
+    let rebuilt = writer.build_dnpe()?;
+    Ok((pe, rebuilt, type_index))
+}
+
+#[test]
+fn ptr_tables_use_uncompressed_stream() -> dnfile::Result<()> {
+    let (_, rebuilt, _) = with_method_ptr()?;
+    let net = rebuilt.net()?;
+    let stream = net.metadata.streams.get("#-").unwrap();
+    let tables = match &stream.stream {
+        Stream::MetaDataTables(t) => t,
+        _ => unreachable!(),
+    };
+    assert!(tables.is_uncompressed());
+    // the sample's heaps are small and no #JTD stream is written
+    assert_eq!(tables.header().unwrap().heap_offset_sizes & 0x07, 0);
+    assert!(!net.metadata.streams.contains_key("#~"));
+    Ok(())
+}
+
+#[test]
+fn method_lists_resolve_through_method_ptr() -> dnfile::Result<()> {
+    let (pe, rebuilt, type_index) = with_method_ptr()?;
+    let before = method_lists(&pe)?;
+    let after = method_lists(&rebuilt)?;
+    assert_eq!(after, before);
+    assert!(after[type_index][0] > after[type_index][1]);
+
+    let net = rebuilt.net()?;
+    let type_def = net.md_table("TypeDef")?.row::<TypeDef>(type_index)?;
+    let names: Vec<_> = net
+        .type_methods(type_def)?
+        .iter()
+        .map(|m| m.name.clone())
+        .collect();
+    let original = pe.net()?;
+    let expected: Vec<_> = original
+        .type_methods(original.md_table("TypeDef")?.row::<TypeDef>(type_index)?)?
+        .iter()
+        .map(|m| m.name.clone())
+        .collect();
+    assert_eq!(names, expected);
+    assert_eq!(net.functions(), original.functions());
+    Ok(())
+}
+
+/// Hand-assembled metadata root with a `#-` stream holding one Module row
+/// and an empty MethodDef table. Heap indexes are 4 bytes when `jtd` adds
+/// the #JTD marker stream and 2 bytes otherwise; the header flags are clear
+/// in both cases.
+fn uncompressed_metadata(jtd: bool) -> Vec<u8> {
+    let index = |value: u32, out: &mut Vec<u8>| {
+        if jtd {
+            out.extend_from_slice(&value.to_le_bytes());
+        } else {
+            out.extend_from_slice(&(value as u16).to_le_bytes());
+        }
+    };
+    let mut tables = vec![];
+    tables.extend_from_slice(&0u32.to_le_bytes());
+    tables.extend_from_slice(&[2, 0, 0, 1]);
+    tables.extend_from_slice(&((1u64 << 0x00) | (1u64 << 0x06)).to_le_bytes());
+    tables.extend_from_slice(&0u64.to_le_bytes());
+    tables.extend_from_slice(&1u32.to_le_bytes());
+    tables.extend_from_slice(&0u32.to_le_bytes());
+    tables.extend_from_slice(&0u16.to_le_bytes());
+    index(1, &mut tables);
+    index(1, &mut tables);
+    index(0, &mut tables);
+    index(0, &mut tables);
+    while tables.len() % 4 != 0 {
+        tables.push(0);
+    }
+    let mut streams: Vec<(&str, Vec<u8>)> = vec![
+        ("#-", tables),
+        ("#Strings", b"\0fixture.dll\0\0\0".to_vec()),
+        ("#GUID", (1..=16).collect()),
+    ];
+    if jtd {
+        streams.push(("#JTD", vec![]));
+    }
+
+    let mut root = vec![];
+    root.extend_from_slice(&0x424a_5342u32.to_le_bytes());
+    root.extend_from_slice(&[1, 0, 1, 0, 0, 0, 0, 0]);
+    root.extend_from_slice(&12u32.to_le_bytes());
+    root.extend_from_slice(b"v4.0.30319\0\0");
+    root.extend_from_slice(&0u16.to_le_bytes());
+    root.extend_from_slice(&(streams.len() as u16).to_le_bytes());
+    let headers_size: usize = streams
+        .iter()
+        .map(|(name, _)| 8 + (name.len() + 4) / 4 * 4)
+        .sum();
+    let mut offset = root.len() + headers_size;
+    for (name, data) in &streams {
+        root.extend_from_slice(&(offset as u32).to_le_bytes());
+        root.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        let mut name = name.as_bytes().to_vec();
+        name.resize((name.len() + 4) / 4 * 4, 0);
+        root.extend_from_slice(&name);
+        offset += data.len();
+    }
+    for (_, data) in &streams {
+        root.extend_from_slice(data);
+    }
+    root
+}
+
+/// Replaces the sample's metadata in place with `metadata`.
+fn with_metadata(metadata: &[u8]) -> dnfile::Result<dnfile::DnPe> {
+    let pe = dnfile::DnPe::new(SAMPLE)?;
+    let clr_header = pe.net()?.clr_header();
+    assert!(metadata.len() <= clr_header.meta_data_size as usize);
+    let goblin = pe.pe()?;
+    let offset_of = |rva: u32| {
+        let s = goblin
+            .sections
+            .iter()
+            .find(|s| rva >= s.virtual_address && rva < s.virtual_address + s.virtual_size)
+            .unwrap();
+        (s.pointer_to_raw_data + rva - s.virtual_address) as usize
+    };
+    let clr = goblin
+        .header
+        .optional_header
+        .unwrap()
+        .data_directories
+        .get_clr_runtime_header()
+        .unwrap()
+        .virtual_address;
+    let mut data = pe.data().to_vec();
+    let start = offset_of(clr_header.meta_data_rva);
+    data[start..start + metadata.len()].copy_from_slice(metadata);
+    let size = offset_of(clr) + 12;
+    data[size..size + 4].copy_from_slice(&(metadata.len() as u32).to_le_bytes());
+    dnfile::DnPe::from_bytes(pe.name(), data)
+}
+
+fn module(pe: &dnfile::DnPe) -> dnfile::Result<Module> {
+    Ok(pe.net()?.md_table("Module")?.row::<Module>(0)?.clone())
+}
+
+#[test]
+fn renamed_tables_stream_keeps_compact_heaps() -> dnfile::Result<()> {
+    let pe = with_metadata(&uncompressed_metadata(false))?;
+    let module = module(&pe)?;
+    assert_eq!(module.name, "fixture.dll");
+    assert_eq!(module.mvid.as_bytes()[0], 1);
+    assert!(module.enc_id.is_nil());
+    Ok(())
+}
+
+#[test]
+fn jtd_stream_forces_large_heaps() -> dnfile::Result<()> {
+    let pe = with_metadata(&uncompressed_metadata(true))?;
+    let module = module(&pe)?;
+    assert_eq!(module.name, "fixture.dll");
+    assert_eq!(module.mvid.as_bytes()[0], 1);
+    assert!(module.enc_id.is_nil());
+    Ok(())
+}
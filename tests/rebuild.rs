@@ -0,0 +1,120 @@
+use dnfile::{
+    stream::meta_data_tables::mdtables::{table_name_2_index, ModuleRef, TypeDef},
+    writer::metadata::MetaDataBuilder,
+};
+
+const SAMPLE: &str = "tests/samples/crafted_2.exe";
+
+fn type_names(pe: &dnfile::DnPe) -> dnfile::Result<Vec<String>> {
+    let table = pe.net()?.md_table("TypeDef")?;
+    (0..table.row_count())
+        .map(|i| {
+            let row = table.row::<TypeDef>(i)?;
+            Ok(format!("{}.{}", row.type_namespace, row.type_name))
+        })
+        .collect()
+}
+
+#[test]
+fn rebuilt_metadata_reparses() -> dnfile::Result<()> {
+    let pe = dnfile::DnPe::new(SAMPLE)?;
+    let mut writer = pe.writer();
+    writer.set_rebuild_metadata(true);
+    let rebuilt = writer.build_dnpe()?;
+
+    let before = MetaDataBuilder::new(&pe.net()?.metadata).build()?;
+    let after = MetaDataBuilder::new(&rebuilt.net()?.metadata).build()?;
+    assert_eq!(before, after);
+    assert_eq!(rebuilt.net()?.functions(), pe.net()?.functions());
+    assert_eq!(type_names(&rebuilt)?, type_names(&pe)?);
+    for i in 0..64 {
+        let count = |pe: &dnfile::DnPe| {
+            pe.net()
+                .ok()
+                .and_then(|n| n.md_table_by_index(&i).ok())
+                .map(|t| t.row_count())
+        };
+        assert_eq!(count(&rebuilt), count(&pe), "table {}", i);
+    }
+    Ok(())
+}
+
+#[test]
+fn appended_row_survives_rebuild() -> dnfile::Result<()> {
+    let mut pe = dnfile::DnPe::new(SAMPLE)?;
+    let rid = pe
+        .net_mut()?
+        .md_table_mut("ModuleRef")?
+        .push_row(ModuleRef {
+            name: "appended.dll".to_string(),
+        })?;
+    let mut writer = pe.writer();
+    writer.set_rebuild_metadata(true);
+    let rebuilt = writer.build_dnpe()?;
+
+    let table = rebuilt.net()?.md_table("ModuleRef")?;
+    assert_eq!(table.row_count(), rid);
+    assert_eq!(table.row::<ModuleRef>(rid - 1)?.name, "appended.dll");
+    assert_eq!(rebuilt.net()?.functions(), pe.net()?.functions());
+    Ok(())
+}
+
+#[test]
+fn removed_type_takes_its_members_along() -> dnfile::Result<()> {
+    let mut pe = dnfile::DnPe::new(SAMPLE)?;
+    let names = type_names(&pe)?;
+    let type_index = names
+        .iter()
+        .position(|n| n == ".MetadataTestAttribute")
+        .unwrap();
+    let functions: Vec<_> = pe
+        .net()?
+        .functions()
+        .iter()
+        .map(|f| f.full_name())
+        .filter(|n| !n.starts_with("MetadataTestAttribute::"))
+        .collect();
+    let properties: Vec<_> = pe
+        .net()?
+        .properties()?
+        .into_iter()
+        .filter(|p| p.declaring_type != Some(type_index + 1))
+        .map(|p| p.name)
+        .collect();
+    let counts = |pe: &dnfile::DnPe, table| pe.net().unwrap().md_table(table).unwrap().row_count();
+    let methods = counts(&pe, "MethodDef");
+    let attributes = counts(&pe, "CustomAttribute");
+
+    let removed = pe
+        .net_mut()?
+        .metadata
+        .remove_row("TypeDef", type_index + 1)?;
+    let removed_from = |table| {
+        let index = table_name_2_index(table).unwrap();
+        removed.iter().filter(|t| t.table() == index).count()
+    };
+    assert_eq!(removed_from("TypeDef"), 1);
+    assert_eq!(removed_from("MethodDef"), 5);
+    assert_eq!(removed_from("Property"), 2);
+    assert_eq!(removed_from("PropertyMap"), 1);
+    assert_eq!(removed_from("MethodSemantics"), 4);
+    assert!(removed_from("CustomAttribute") > 0);
+
+    let mut writer = pe.writer();
+    writer.set_rebuild_metadata(true);
+    let rebuilt = writer.build_dnpe()?;
+    let mut expected = names;
+    expected.remove(type_index);
+    assert_eq!(type_names(&rebuilt)?, expected);
+    assert_eq!(counts(&rebuilt, "MethodDef"), methods - 5);
+    assert_eq!(
+        counts(&rebuilt, "CustomAttribute"),
+        attributes - removed_from("CustomAttribute")
+    );
+    let net = rebuilt.net()?;
+    let after: Vec<_> = net.functions().iter().map(|f| f.full_name()).collect();
+    assert_eq!(after, functions);
+    let after: Vec<_> = net.properties()?.into_iter().map(|p| p.name).collect();
+    assert_eq!(after, properties);
+    Ok(())
+}
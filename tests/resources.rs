@@ -0,0 +1,344 @@
+use dnfile::{
+    resources::{parse_resource_set, ResourceValue},
+    search::{Location, SearchOptions},
+    stream::{
+        meta_data_tables::mdtables::{
+            codedindex::Implementation, table_name_2_index, ManifestResource,
+        },
+        Stream,
+    },
+};
+
+const SAMPLE: &str = "tests/samples/crafted_2.exe";
+
+fn resource(name: &str, offset: u32, table: &'static str, row_index: usize) -> ManifestResource {
+    ManifestResource {
+        offset,
+        flags: vec![],
+        raw_flags: 1,
+        name: name.to_string(),
+        implementation: Implementation {
+            row_index,
+            table,
+            ..Default::default()
+        },
+    }
+}
+
+/// Rebuilds the sample with a ManifestResource table holding one valid
+/// AssemblyRef resource and two that cannot be read.
+fn with_resources() -> dnfile::Result<dnfile::DnPe> {
+    let mut pe = dnfile::DnPe::new(SAMPLE)?;
+    let index = table_name_2_index("ManifestResource")?;
+    let mut table = pe.create_md_table(&index, &[0; 64], false, 2, 2, 2)?;
+    table.push_row(resource("linked", 0, "AssemblyRef", 1))?;
+    table.push_row(resource("outside", 0x1000, "File", 0))?;
+    table.push_row(resource("dangling", 0, "AssemblyRef", 99))?;
+    if let Some(Stream::MetaDataTables(t)) = pe
+        .net_mut()?
+        .metadata
+        .streams
+        .get_mut("#~")
+        .map(|s| &mut s.stream)
+    {
+        t.tables.insert(index, table);
+    }
+    let mut writer = pe.writer();
+    writer.set_rebuild_metadata(true);
+    writer.build_dnpe()
+}
+
+#[test]
+fn bad_resources_are_reported_per_entry() -> dnfile::Result<()> {
+    let pe = with_resources()?;
+    let resources = pe.resources()?;
+    assert_eq!(resources.len(), 3);
+    assert_eq!(resources[0].implementation.as_deref(), Some("mscorlib"));
+    assert!(resources[0].error.is_none());
+    assert!(resources[1].implementation.is_none());
+    assert!(resources[1].error.is_some());
+    assert!(resources[1].data.is_empty());
+    assert_eq!(
+        resources[2].implementation.as_deref(),
+        Some("AssemblyRef#99")
+    );
+    assert!(resources[2].error.is_some());
+    pe.search(
+        &[regex::Regex::new("x").unwrap()],
+        &SearchOptions::default(),
+    )?;
+    Ok(())
+}
+
+fn encoded(mut value: usize, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn string(value: &str, out: &mut Vec<u8>) {
+    encoded(value.len(), out);
+    out.extend_from_slice(value.as_bytes());
+}
+
+/// Writes a `.resources` file the way ResourceWriter does, each value given
+/// as its type code (or type index for version 1) and encoded data.
+fn resource_set(version: u32, types: &[&str], entries: &[(&str, usize, &[u8])]) -> Vec<u8> {
+    let mut res = vec![];
+    res.extend_from_slice(&0xbeef_caceu32.to_le_bytes());
+    res.extend_from_slice(&1u32.to_le_bytes());
+    let mut header = vec![];
+    string("System.Resources.ResourceReader, mscorlib", &mut header);
+    string("System.Resources.RuntimeResourceSet", &mut header);
+    res.extend_from_slice(&(header.len() as u32).to_le_bytes());
+    res.extend(header);
+    res.extend_from_slice(&version.to_le_bytes());
+    res.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+    res.extend_from_slice(&(types.len() as u32).to_le_bytes());
+    for t in types {
+        string(t, &mut res);
+    }
+    while res.len() % 8 != 0 {
+        res.push(b"PAD"[res.len() % 3]);
+    }
+    let mut names = vec![];
+    let mut positions = vec![];
+    let mut values = vec![];
+    for (name, code, data) in entries {
+        positions.push(names.len() as u32);
+        let name: Vec<u8> = name.encode_utf16().flat_map(|u| u.to_le_bytes()).collect();
+        encoded(name.len(), &mut names);
+        names.extend(name);
+        names.extend_from_slice(&(values.len() as u32).to_le_bytes());
+        encoded(*code, &mut values);
+        values.extend_from_slice(data);
+    }
+    for _ in entries {
+        res.extend_from_slice(&0u32.to_le_bytes());
+    }
+    for p in positions {
+        res.extend_from_slice(&p.to_le_bytes());
+    }
+    let data_section = res.len() + 4 + names.len();
+    res.extend_from_slice(&(data_section as u32).to_le_bytes());
+    res.extend(names);
+    res.extend(values);
+    res
+}
+
+fn string_value(value: &str) -> Vec<u8> {
+    let mut res = vec![];
+    string(value, &mut res);
+    res
+}
+
+#[test]
+fn resource_set_values_are_decoded() -> dnfile::Result<()> {
+    let greeting = string_value("héllo http://example.com/a");
+    let blob = [4, 0, 0, 0, b'M', b'Z', 0x90, 0];
+    let data = resource_set(
+        2,
+        &["Custom.Point, Custom"],
+        &[
+            ("Greeting", 0x01, &greeting),
+            ("Answer", 0x08, &42i32.to_le_bytes()),
+            ("Flag", 0x02, &[1]),
+            ("Blob", 0x20, &blob),
+            ("Point", 0x40, &[0, 1, 2, 3]),
+            ("Nothing", 0x00, &[]),
+        ],
+    );
+    let entries = parse_resource_set(&data)?;
+    let values: Vec<_> = entries
+        .iter()
+        .map(|e| (e.name.as_str(), e.type_name.as_str(), e.value.clone()))
+        .collect();
+    assert_eq!(
+        values,
+        vec![
+            (
+                "Greeting",
+                "System.String",
+                Some(ResourceValue::String(
+                    "héllo http://example.com/a".to_string()
+                ))
+            ),
+            (
+                "Answer",
+                "System.Int32",
+                Some(ResourceValue::Primitive("42".to_string()))
+            ),
+            (
+                "Flag",
+                "System.Boolean",
+                Some(ResourceValue::Primitive("true".to_string()))
+            ),
+            (
+                "Blob",
+                "System.Byte[]",
+                Some(ResourceValue::Bytes(vec![b'M', b'Z', 0x90, 0]))
+            ),
+            (
+                "Point",
+                "Custom.Point, Custom",
+                Some(ResourceValue::Serialized(vec![0, 1, 2, 3]))
+            ),
+            ("Nothing", "", Some(ResourceValue::Null)),
+        ]
+    );
+    assert!(entries.iter().all(|e| e.error.is_none()));
+    Ok(())
+}
+
+#[test]
+fn version_1_resource_set_uses_the_type_table() -> dnfile::Result<()> {
+    let data = resource_set(
+        1,
+        &[
+            "System.String, mscorlib, Version=2.0.0.0",
+            "System.Boolean, mscorlib, Version=2.0.0.0",
+        ],
+        &[
+            ("Text", 0, &string_value("value")),
+            ("Flag", 1, &[0, 1, 2]),
+            ("Null", u32::MAX as usize, &[]),
+        ],
+    );
+    let entries = parse_resource_set(&data)?;
+    assert_eq!(
+        entries[0].value,
+        Some(ResourceValue::String("value".to_string()))
+    );
+    assert_eq!(entries[1].type_name, "System.Boolean");
+    assert_eq!(
+        entries[1].value,
+        Some(ResourceValue::Serialized(vec![0, 1, 2]))
+    );
+    assert_eq!(entries[2].value, Some(ResourceValue::Null));
+    Ok(())
+}
+
+#[test]
+fn bad_resource_value_is_reported_per_entry() -> dnfile::Result<()> {
+    let truncated = [0xff];
+    let data = resource_set(
+        2,
+        &[],
+        &[
+            ("Unknown", 0x30, &[]),
+            ("Text", 0x01, &string_value("fine")),
+            ("Truncated", 0x01, &truncated),
+        ],
+    );
+    let entries = parse_resource_set(&data)?;
+    assert!(entries[0].value.is_none() && entries[0].error.is_some());
+    assert_eq!(
+        entries[1].value,
+        Some(ResourceValue::String("fine".to_string()))
+    );
+    assert!(entries[2].value.is_none() && entries[2].error.is_some());
+    assert!(parse_resource_set(b"not a resource set").is_err());
+    Ok(())
+}
+
+/// Rebuilds the sample with one embedded resource holding `data`, placed in
+/// the slack at the end of a section.
+fn with_embedded(data: &[u8]) -> dnfile::Result<dnfile::DnPe> {
+    let mut pe = dnfile::DnPe::new(SAMPLE)?;
+    let index = table_name_2_index("ManifestResource")?;
+    let mut table = pe.create_md_table(&index, &[0; 64], false, 2, 2, 2)?;
+    table.push_row(resource("Strings.resources", 0, "File", 0))?;
+    if let Some(Stream::MetaDataTables(t)) = pe
+        .net_mut()?
+        .metadata
+        .streams
+        .get_mut("#~")
+        .map(|s| &mut s.stream)
+    {
+        t.tables.insert(index, table);
+    }
+    let mut writer = pe.writer();
+    writer.set_rebuild_metadata(true);
+    let rebuilt = writer.build_dnpe()?;
+
+    let mut image = rebuilt.data().to_vec();
+    let goblin = rebuilt.pe()?;
+    let size = 4 + data.len() as u32;
+    let section = goblin
+        .sections
+        .iter()
+        .find(|s| s.size_of_raw_data >= ((s.virtual_size + 3) & !3) + size)
+        .unwrap();
+    let used = (section.virtual_size + 3) & !3;
+    let rva = section.virtual_address + used;
+    let offset = (section.pointer_to_raw_data + used) as usize;
+    image[offset..offset + 4].copy_from_slice(&(data.len() as u32).to_le_bytes());
+    image[offset + 4..offset + 4 + data.len()].copy_from_slice(data);
+
+    let clr = goblin
+        .header
+        .optional_header
+        .unwrap()
+        .data_directories
+        .get_clr_runtime_header()
+        .unwrap()
+        .virtual_address;
+    let clr_section = goblin
+        .sections
+        .iter()
+        .find(|s| clr >= s.virtual_address && clr < s.virtual_address + s.virtual_size)
+        .unwrap();
+    // Resources directory of the COR20 header
+    let directory =
+        (clr_section.pointer_to_raw_data + clr - clr_section.virtual_address) as usize + 24;
+    image[directory..directory + 4].copy_from_slice(&rva.to_le_bytes());
+    image[directory + 4..directory + 8].copy_from_slice(&size.to_le_bytes());
+    dnfile::DnPe::from_bytes(rebuilt.name(), image)
+}
+
+#[test]
+fn search_reads_resource_set_entries() -> dnfile::Result<()> {
+    let url = string_value("see http://example.com/x");
+    let wide: Vec<u8> = "http://example.org/y"
+        .encode_utf16()
+        .flat_map(|u| u.to_le_bytes())
+        .collect();
+    let mut blob = (wide.len() as u32).to_le_bytes().to_vec();
+    blob.extend(&wide);
+    let data = resource_set(
+        2,
+        &[],
+        &[("Link", 0x01, &url), ("http://example.net/z", 0x20, &blob)],
+    );
+    let pe = with_embedded(&data)?;
+    let resources = pe.resources()?;
+    assert_eq!(resources.len(), 1);
+    assert_eq!(resources[0].data, data);
+
+    let pattern = regex::Regex::new(r"http://\S+").unwrap();
+    let found: Vec<_> = pe
+        .search(&[pattern], &SearchOptions::default())?
+        .into_iter()
+        .filter_map(|m| match m.location {
+            Location::ResourceEntry { entry, .. } => Some((entry, m.matched)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            ("Link".to_string(), "http://example.com/x".to_string()),
+            (
+                "http://example.net/z".to_string(),
+                "http://example.net/z".to_string()
+            ),
+            (
+                "http://example.net/z".to_string(),
+                "http://example.org/y".to_string()
+            ),
+        ]
+    );
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use dnfile::{
+    error::Error,
+    lang::clr::{
+        marshal::parse_marshal_spec,
+        signature::{parse_local_var_sig, SigType, MAX_SIGNATURE_DEPTH},
+    },
+};
+
+fn local_sig(nesting: &[u8], depth: usize) -> Vec<u8> {
+    let mut blob = vec![0x07, 0x01];
+    for i in 0..depth {
+        blob.push(nesting[i % nesting.len()]);
+    }
+    blob.push(0x08);
+    blob
+}
+
+#[test]
+fn nested_types_within_limit_parse() -> dnfile::Result<()> {
+    let sig = parse_local_var_sig(&local_sig(&[0x0f, 0x10, 0x1d], MAX_SIGNATURE_DEPTH - 1))?;
+    let mut t = &sig.locals[0];
+    let mut depth = 0;
+    while let SigType::Ptr(inner) | SigType::ByRef(inner) | SigType::SzArray(inner) = t {
+        t = inner;
+        depth += 1;
+    }
+    assert_eq!(depth, MAX_SIGNATURE_DEPTH - 1);
+    assert_eq!(*t, SigType::I4);
+    Ok(())
+}
+
+#[test]
+fn deeply_nested_types_are_rejected() {
+    for nesting in [0x0f, 0x10, 0x1d] {
+        let blob = local_sig(&[nesting], 100_000);
+        assert!(matches!(
+            parse_local_var_sig(&blob),
+            Err(Error::SignatureFormatError(_))
+        ));
+    }
+}
+
+#[test]
+fn deeply_nested_marshal_arrays_are_rejected() {
+    let blob = vec![0x1e; 200_000];
+    assert!(matches!(
+        parse_marshal_spec(&blob),
+        Err(Error::SignatureFormatError(_))
+    ));
+}
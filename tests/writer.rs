@@ -0,0 +1,86 @@
+use dnfile::lang::cil::function::{writer::MethodBody, Function};
+
+const SAMPLE: &str = "tests/samples/crafted_2.exe";
+
+fn new_section(pe: &dnfile::DnPe) -> Option<u32> {
+    pe.pe()
+        .ok()?
+        .sections
+        .iter()
+        .find(|s| &s.name == b".dnfile\0")
+        .map(|s| s.characteristics)
+}
+
+fn tiny_function(pe: &dnfile::DnPe) -> &Function {
+    pe.net()
+        .unwrap()
+        .functions()
+        .iter()
+        .find(|f| f.header_size() == 1 && f.name == ".ctor")
+        .unwrap()
+}
+
+#[test]
+fn patch_in_place() -> dnfile::Result<()> {
+    let pe = dnfile::DnPe::new(SAMPLE)?;
+    let function = tiny_function(&pe);
+    let ret = function.instructions.last().unwrap().clone();
+    let mut writer = pe.writer();
+    writer.set_method_body(&function.method_token, &MethodBody::new(vec![ret]))?;
+    let patched = writer.build()?;
+    assert_eq!(patched.len(), pe.data().len());
+
+    let reparsed = dnfile::DnPe::from_bytes(pe.name(), patched)?;
+    assert!(new_section(&reparsed).is_none());
+    let net = reparsed.net()?;
+    let stub = net.function_for(&function.method_token).unwrap();
+    assert_eq!(stub.offset, function.offset);
+    assert_eq!(stub.instructions.len(), 1);
+    assert_eq!(stub.max_stack(), 8);
+    for (a, b) in pe.net()?.functions().iter().zip(net.functions()) {
+        if a.method_token != function.method_token {
+            assert_eq!(a, b);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn relocate_body_and_append_user_string() -> dnfile::Result<()> {
+    let pe = dnfile::DnPe::new(SAMPLE)?;
+    let function = tiny_function(&pe);
+    let mut body = function.method_body();
+    // a fat header no longer fits in the tiny body, so it moves to .dnfile
+    body.init_locals = true;
+    let mut writer = pe.writer();
+    writer.set_method_body(&function.method_token, &body)?;
+    let token = writer.add_user_string("dnfile writer test")?;
+    let reparsed = writer.build_dnpe()?;
+
+    assert_eq!(new_section(&reparsed), Some(0x60000020));
+    let net = reparsed.net()?;
+    assert_eq!(net.get_us(token.rid())?, "dnfile writer test");
+    let moved = net.function_for(&function.method_token).unwrap();
+    assert_eq!(moved.header_size(), 12);
+    assert!(moved.method_body().init_locals);
+    assert_eq!(moved.instructions.len(), function.instructions.len());
+    for (a, b) in pe.net()?.functions().iter().zip(net.functions()) {
+        if a.method_token != function.method_token {
+            assert_eq!(a, b);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn user_strings_only_add_data_section() -> dnfile::Result<()> {
+    let pe = dnfile::DnPe::new(SAMPLE)?;
+    let mut writer = pe.writer();
+    let token = writer.add_user_string("dnfile")?;
+    let reparsed = writer.build_dnpe()?;
+
+    assert_eq!(new_section(&reparsed), Some(0x40000040));
+    assert_eq!(reparsed.net()?.get_us(token.rid())?, "dnfile");
+    assert_eq!(reparsed.net()?.functions(), pe.net()?.functions());
+    Ok(())
+}